@@ -1,2 +0,0 @@
-export declare function encodeCbor(value: unknown): Uint8Array;
-export declare function decodeCbor(input: Uint8Array): unknown;
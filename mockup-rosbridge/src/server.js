@@ -1,6 +1,6 @@
 import { WebSocketServer } from "ws";
 import { DEFAULT_TICK_MS, TOPIC_PAYLOADS, deterministicTopicPayload } from "./mock-data.js";
-import { decodeCbor, encodeCbor } from "./cbor.js";
+import { decodeCbor, encodeCbor } from "tachybridge-wasm/node";
 const activeActions = new Map();
 function actionKey(action, sessionId) {
     return `${action}::${sessionId ?? "default"}`;
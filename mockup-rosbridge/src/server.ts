@@ -1,6 +1,6 @@
 import { WebSocketServer, type WebSocket } from "ws";
 import { DEFAULT_TICK_MS, TOPIC_PAYLOADS, deterministicRawBytes, deterministicTopicPayload } from "./mock-data.js";
-import { decodeCbor, encodeCbor } from "./cbor.js";
+import { decodeCbor, encodeCbor } from "tachybridge-wasm/node";
 
 type OpMessage = {
   op: string;
@@ -12,8 +12,13 @@ type OpMessage = {
   action_type?: string;
   msg?: Record<string, unknown>;
   args?: Record<string, unknown>;
+  values?: unknown;
+  result?: boolean;
+  status?: number;
+  level?: string;
   goal?: Record<string, unknown>;
   compression?: string;
+  throttle_rate?: number;
   id?: string;
   session_id?: string;
 };
@@ -23,8 +28,11 @@ type ActionState = {
   action: string;
   actionType: string;
   sessionId?: string;
-  interval: NodeJS.Timeout;
-  ws: WebSocket;
+  interval?: NodeJS.Timeout;
+  /** Unset while the client that sent the goal is disconnected. */
+  ws?: WebSocket;
+  /** Result of a goal that finished while detached, kept for `query_action_goal`. */
+  result?: Record<string, unknown>;
 };
 
 export type MockupRosbridgeServer = {
@@ -33,8 +41,21 @@ export type MockupRosbridgeServer = {
   stop: () => Promise<void>;
 };
 
+type ForwardedGoal = {
+  ws: WebSocket;
+  host: WebSocket;
+  action: string;
+  actionType: string;
+  id?: string;
+  sessionId?: string;
+};
+
 const activeActions = new Map<string, ActionState>();
 
+function isRecord(value: unknown): value is Record<string, unknown> {
+  return typeof value === "object" && value !== null && !Array.isArray(value);
+}
+
 function actionKey(action: string, sessionId?: string): string {
   return `${action}::${sessionId ?? "default"}`;
 }
@@ -45,6 +66,87 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
   const subscriptionCompression = new Map<WebSocket, Map<string, string | undefined>>();
   const advertised = new Map<string, string>();
   const connectionCodec = new Map<WebSocket, "json" | "cbor">();
+  // Verbosity chosen with `set_level`; rosbridge defaults to errors only.
+  const statusLevels = new Map<WebSocket, string>();
+  // Services hosted by clients via `advertise_service`, and calls forwarded to them.
+  const hostedServices = new Map<string, { ws: WebSocket; type: string }>();
+  const forwardedCalls = new Map<string, { ws: WebSocket; host: WebSocket; service: string; id?: string }>();
+  let forwardedCallCounter = 0;
+  // Actions hosted by clients via `advertise_action`, and goals forwarded to them.
+  const hostedActions = new Map<string, { ws: WebSocket; type: string }>();
+  const forwardedGoals = new Map<string, ForwardedGoal>();
+  let forwardedGoalCounter = 0;
+
+  /** Sends a rosbridge `status` message if the connection's level lets it through. */
+  function sendStatus(ws: WebSocket, level: "info" | "warning" | "error", msg: string, id?: string): void {
+    const order = ["info", "warning", "error", "none"];
+    if (order.indexOf(level) < order.indexOf(statusLevels.get(ws) ?? "error")) {
+      return;
+    }
+    send(ws, { op: "status", level, msg, id });
+  }
+
+  function forwardActionGoal(ws: WebSocket, message: OpMessage, host: { ws: WebSocket; type: string }): void {
+    const action = message.action ?? "";
+    forwardedGoalCounter += 1;
+    const forwardedId = `action_goal:${action}:${forwardedGoalCounter}`;
+    const goal: ForwardedGoal = {
+      ws,
+      host: host.ws,
+      action,
+      actionType: message.action_type ?? host.type,
+      id: message.id,
+      sessionId: message.session_id
+    };
+    forwardedGoals.set(forwardedId, goal);
+    send(host.ws, {
+      op: "send_action_goal",
+      id: forwardedId,
+      action,
+      action_type: goal.actionType,
+      args: message.goal ?? {},
+      feedback: true
+    });
+    send(ws, {
+      type: "request",
+      action,
+      action_type: goal.actionType,
+      id: goal.id,
+      session_id: goal.sessionId,
+      goal: message.goal ?? {}
+    });
+  }
+
+  /** Relays a hosted goal's feedback or result to its caller in the mock's action event format. */
+  function relayActionEvent(goal: ForwardedGoal, event: Record<string, unknown>): void {
+    send(goal.ws, {
+      action: goal.action,
+      action_type: goal.actionType,
+      id: goal.id,
+      session_id: goal.sessionId,
+      ...event
+    });
+  }
+
+  function relayActionResult(forwardedId: string, message: OpMessage): void {
+    const goal = forwardedGoals.get(forwardedId);
+    if (!goal) {
+      return;
+    }
+    forwardedGoals.delete(forwardedId);
+    const values = isRecord(message.values) ? message.values : {};
+    // Hosts report action_msgs/GoalStatus codes: 4 succeeded, 5 canceled, 6 aborted.
+    if (message.status === 4) {
+      relayActionEvent(goal, { type: "result", status: 0, result: values });
+    } else if (message.status === 5) {
+      relayActionEvent(goal, { type: "result", status: 2, result: { ...values, canceled: true } });
+    } else {
+      relayActionEvent(goal, {
+        type: "error",
+        message: typeof message.values === "string" ? message.values : "action_aborted"
+      });
+    }
+  }
 
   function send(ws: WebSocket, data: unknown): void {
     const codec = connectionCodec.get(ws) ?? "json";
@@ -70,7 +172,7 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
     return JSON.parse(String(raw)) as OpMessage;
   }
 
-  function startTopicStream(ws: WebSocket, topic: string): void {
+  function startTopicStream(ws: WebSocket, topic: string, throttleRateMs = 0): void {
     if (!(topic in TOPIC_PAYLOADS)) {
       // Only predefined status-like topics are periodic.
       // Other topics emit only when a publish request arrives.
@@ -99,7 +201,7 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
       } else {
         send(ws, { op: "publish", topic, msg: deterministicTopicPayload(topic, tick) });
       }
-    }, DEFAULT_TICK_MS);
+    }, Math.max(DEFAULT_TICK_MS, throttleRateMs));
     streams.set(topic, timer);
     subscriptions.set(ws, streams);
   }
@@ -155,12 +257,17 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
       goal: message.goal ?? {}
     });
 
+    // The goal keeps running while its client is away; events go to `state.ws`.
+    const state: ActionState = { id, action, actionType, sessionId, ws };
     let feedbackCount = 0;
-    const interval = setInterval(() => {
+    state.interval = setInterval(() => {
       feedbackCount += 1;
 
       if (feedbackCount <= 2) {
-        send(ws, {
+        if (!state.ws) {
+          return;
+        }
+        send(state.ws, {
           type: "feedback",
           action,
           action_type: actionType,
@@ -174,9 +281,8 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
         return;
       }
 
-      clearInterval(interval);
-      activeActions.delete(actionKey(action, sessionId));
-      send(ws, {
+      clearInterval(state.interval);
+      const result = {
         type: "result",
         action,
         action_type: actionType,
@@ -187,17 +293,37 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
           success: true,
           output: "action-complete"
         }
-      });
+      };
+      if (!state.ws) {
+        state.interval = undefined;
+        state.result = result;
+        return;
+      }
+      activeActions.delete(actionKey(action, sessionId));
+      send(state.ws, result);
     }, DEFAULT_TICK_MS);
 
-    activeActions.set(actionKey(action, sessionId), {
-      id,
-      action,
-      actionType,
-      sessionId,
-      interval,
-      ws
-    });
+    activeActions.set(actionKey(action, sessionId), state);
+  }
+
+  /**
+   * tachybridge `query_action_goal`: re-attaches a goal started on an earlier
+   * connection to `ws`, replaying its result if it finished meanwhile.
+   */
+  function queryNativeAction(ws: WebSocket, message: OpMessage): void {
+    const action = message.action ?? "/demo/action";
+    const key = actionKey(action, message.session_id);
+    const state = activeActions.get(key);
+    const found = state !== undefined && state.id === message.id && (state.ws === undefined || state.ws === ws);
+    send(ws, { op: "action_goal_status", action, id: message.id, session_id: message.session_id, found });
+    if (!found) {
+      return;
+    }
+    state.ws = ws;
+    if (state.result) {
+      activeActions.delete(key);
+      send(ws, state.result);
+    }
   }
 
   function cancelNativeAction(ws: WebSocket, message: OpMessage): void {
@@ -258,9 +384,21 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
         return;
       }
 
+      if (message.op === "set_level" && message.level) {
+        statusLevels.set(ws, message.level);
+        return;
+      }
+
       if (message.op === "subscribe" && message.topic) {
+        if (message.type !== undefined && !/^[\w]+\/(msg\/)?[\w]+$/.test(message.type)) {
+          sendStatus(ws, "error", `Unable to load the manifest for ${message.type}`, message.id);
+          return;
+        }
+        sendStatus(ws, "info", `Subscribed to ${message.topic}`, message.id);
+        // Restart the stream so a changed throttle_rate takes effect.
+        stopTopicStream(ws, message.topic);
         subscriptionCompression.get(ws)?.set(message.topic, message.compression);
-        startTopicStream(ws, message.topic);
+        startTopicStream(ws, message.topic, message.throttle_rate);
         return;
       }
 
@@ -275,6 +413,12 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
         return;
       }
 
+      if (message.op === "unadvertise" && message.topic) {
+        advertised.delete(message.topic);
+        console.log(`[mockup] unadvertised ${message.topic}`);
+        return;
+      }
+
       if (message.op === "publish" && message.topic) {
         console.log(`[mockup] publish ${message.topic}`, message.msg ?? {});
         const compression = subscriptionCompression.get(ws)?.get(message.topic);
@@ -299,6 +443,44 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
         return;
       }
 
+      if (message.op === "advertise_service" && message.service && message.type) {
+        hostedServices.set(message.service, { ws, type: message.type });
+        console.log(`[mockup] advertised service ${message.service} (${message.type})`);
+        return;
+      }
+
+      if (message.op === "unadvertise_service" && message.service) {
+        if (hostedServices.get(message.service)?.ws === ws) {
+          hostedServices.delete(message.service);
+        }
+        return;
+      }
+
+      if (message.op === "service_response" && message.id) {
+        const call = forwardedCalls.get(message.id);
+        if (!call) {
+          return;
+        }
+        forwardedCalls.delete(message.id);
+        send(call.ws, {
+          op: "service_response",
+          service: call.service,
+          id: call.id,
+          result: message.result === true,
+          values: message.values
+        });
+        return;
+      }
+
+      const host = message.op === "call_service" && message.service ? hostedServices.get(message.service) : undefined;
+      if (host && message.service) {
+        forwardedCallCounter += 1;
+        const forwardedId = `service_request:${message.service}:${forwardedCallCounter}`;
+        forwardedCalls.set(forwardedId, { ws, host: host.ws, service: message.service, id: message.id });
+        send(host.ws, { op: "call_service", service: message.service, id: forwardedId, args: message.args ?? {} });
+        return;
+      }
+
       if (message.op === "call_service" && message.service) {
         if ((message.args as Record<string, unknown> | undefined)?.force_fail === true) {
           send(ws, {
@@ -365,6 +547,50 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
         return;
       }
 
+      if (message.op === "advertise_action" && message.action && message.type) {
+        hostedActions.set(message.action, { ws, type: message.type });
+        console.log(`[mockup] advertised action ${message.action} (${message.type})`);
+        return;
+      }
+
+      if (message.op === "unadvertise_action" && message.action) {
+        if (hostedActions.get(message.action)?.ws === ws) {
+          hostedActions.delete(message.action);
+        }
+        return;
+      }
+
+      if (message.op === "action_feedback" && message.id) {
+        const goal = forwardedGoals.get(message.id);
+        if (goal) {
+          relayActionEvent(goal, { type: "feedback", feedback: isRecord(message.values) ? message.values : {} });
+        }
+        return;
+      }
+
+      if (message.op === "action_result" && message.id) {
+        relayActionResult(message.id, message);
+        return;
+      }
+
+      const actionHost = message.op === "send_action_goal" && message.action ? hostedActions.get(message.action) : undefined;
+      if (actionHost) {
+        forwardActionGoal(ws, message, actionHost);
+        return;
+      }
+
+      if (message.op === "cancel_action_goal") {
+        const forwarded = [...forwardedGoals.entries()].find(
+          ([, goal]) => goal.ws === ws && goal.action === message.action && goal.sessionId === message.session_id
+        );
+        if (forwarded) {
+          const [forwardedId, goal] = forwarded;
+          send(goal.host, { op: "cancel_action_goal", id: forwardedId, action: goal.action });
+          send(ws, { op: "cancel_action_result", action: goal.action, session_id: goal.sessionId, result: true });
+          return;
+        }
+      }
+
       if (message.op === "send_action_goal") {
         startNativeAction(ws, message);
         return;
@@ -375,16 +601,53 @@ export function createMockupRosbridgeServer(port = 9090): Promise<MockupRosbridg
         return;
       }
 
+      if (message.op === "query_action_goal") {
+        queryNativeAction(ws, message);
+        return;
+      }
+
       send(ws, { op: "error", error: "unsupported_operation", received: message });
     });
 
     ws.on("close", () => {
       stopAllStreams(ws);
       connectionCodec.delete(ws);
-      for (const [key, state] of activeActions.entries()) {
+      statusLevels.delete(ws);
+      for (const [service, host] of hostedServices.entries()) {
+        if (host.ws === ws) {
+          hostedServices.delete(service);
+        }
+      }
+      for (const [action, host] of hostedActions.entries()) {
+        if (host.ws === ws) {
+          hostedActions.delete(action);
+        }
+      }
+      for (const [forwardedId, goal] of forwardedGoals.entries()) {
+        if (goal.host === ws && goal.ws !== ws) {
+          relayActionEvent(goal, { type: "error", message: "action_host_disconnected" });
+        }
+        if (goal.host === ws || goal.ws === ws) {
+          forwardedGoals.delete(forwardedId);
+        }
+      }
+      for (const [forwardedId, call] of forwardedCalls.entries()) {
+        if (call.host === ws && call.ws !== ws) {
+          send(call.ws, {
+            op: "service_response",
+            service: call.service,
+            id: call.id,
+            result: false,
+            values: "service_host_disconnected"
+          });
+        }
+        if (call.host === ws || call.ws === ws) {
+          forwardedCalls.delete(forwardedId);
+        }
+      }
+      for (const state of activeActions.values()) {
         if (state.ws === ws) {
-          clearInterval(state.interval);
-          activeActions.delete(key);
+          state.ws = undefined;
         }
       }
     });
@@ -45,7 +45,6 @@ type BridgeClientLike = {
 };
 
 type BridgeClientConstructor = new (options?: {
-  strictWasm?: boolean;
   timeoutMs?: number;
   reconnect?: {
     enabled?: boolean;
@@ -342,7 +341,7 @@ export function mountMockupWeb(root: HTMLElement, deps: BridgeDeps): void {
       }
     };
 
-    return new deps.BridgeClient({ strictWasm: false, timeoutMs, codec });
+    return new deps.BridgeClient({ timeoutMs, codec });
   }
 
   async function ensureConnected(): Promise<BridgeClientLike> {
@@ -7,6 +7,7 @@ type TopicSubscriptionState = {
   compression?: string;
   active: boolean;
   pending: boolean;
+  handle?: { unsubscribe(): Promise<void> };
 };
 
 export default class Ros extends TinyEmitter {
@@ -200,14 +201,17 @@ export default class Ros extends TinyEmitter {
     }
 
     try {
-      await (this._compatClient as unknown as {
+      const handle = await (this._compatClient as unknown as {
         subscribe: (
           topic: string,
           type: string,
           callback: (msg: JsonMap) => void,
           opts?: { compression?: string }
-        ) => Promise<void>;
+        ) => Promise<TopicSubscriptionState["handle"]>;
       }).subscribe(topic, state.type, handler, { compression: state.compression });
+      // The new handle takes over before the old one is released, so the topic stays subscribed.
+      await state.handle?.unsubscribe();
+      state.handle = handle;
       state.active = true;
       state.pending = false;
     } catch (error) {
@@ -7,6 +7,8 @@ export default class Topic<T = unknown> {
   private readonly name: string;
   private readonly messageType: string;
   private readonly compression?: string;
+  private readonly latch?: boolean;
+  private readonly queueSize?: number;
   private isAdvertised = false;
 
   constructor(options: TopicOptions) {
@@ -14,6 +16,8 @@ export default class Topic<T = unknown> {
     this.name = options.name;
     this.messageType = options.messageType;
     this.compression = options.compression;
+    this.latch = options.latch;
+    this.queueSize = options.queue_size;
     if (options.compression && options.compression !== "none" && !Topic.compressionWarningShown) {
       Topic.compressionWarningShown = true;
       console.warn(
@@ -45,11 +49,21 @@ export default class Topic<T = unknown> {
       return;
     }
     this.isAdvertised = true;
-    void this.ros._compatClient.advertise(this.name, this.messageType);
+    void this.ros._compatClient
+      .advertise(this.name, this.messageType, { latch: this.latch, queueSize: this.queueSize })
+      .catch((error) => {
+        this.ros.emit("error", error);
+      });
   }
 
   unadvertise(): void {
+    if (!this.isAdvertised) {
+      return;
+    }
     this.isAdvertised = false;
+    void this.ros._compatClient.unadvertise(this.name).catch((error) => {
+      this.ros.emit("error", error);
+    });
   }
 
   publish(message: T): void {
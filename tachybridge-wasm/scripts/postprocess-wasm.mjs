@@ -2,7 +2,9 @@ import { readFile, writeFile, rm, mkdir, copyFile } from "node:fs/promises";
 import path from "node:path";
 
 const root = path.resolve(process.cwd(), "dist/wasm/web");
+const nodeRoot = path.resolve(process.cwd(), "dist/wasm/node");
 const srcShimDir = path.resolve(process.cwd(), "src/wasm/web");
+const srcNodeShimDir = path.resolve(process.cwd(), "src/wasm/node");
 const wasmPath = path.join(root, "bridge_wasm_bg.wasm");
 const gluePath = path.join(root, "bridge_wasm.js");
 const glueDtsPath = path.join(root, "bridge_wasm.d.ts");
@@ -39,8 +41,10 @@ async function patchGlue() {
 async function dropStalePackageJson() {
   // wasm-pack writes its own package.json which conflicts with our exports map.
   await rm(stalePackageJson, { force: true });
-  await rm(path.resolve(process.cwd(), "dist/wasm/node/package.json"), { force: true });
-  console.log(`[postprocess-wasm] removed nested package.json files`);
+  // The nodejs glue is CommonJS, but this package is `"type": "module"`, so
+  // without a nested package.json saying otherwise `require` loads it as ESM.
+  await writeFile(path.join(nodeRoot, "package.json"), `{ "type": "commonjs" }\n`, "utf8");
+  console.log(`[postprocess-wasm] replaced nested package.json files`);
 }
 
 async function dropWasmPackGitignores() {
@@ -48,7 +52,7 @@ async function dropWasmPackGitignores() {
   // honors it and silently drops the whole wasm/ tree from the published
   // tarball — exactly what bit us once already.
   await rm(path.join(root, ".gitignore"), { force: true });
-  await rm(path.resolve(process.cwd(), "dist/wasm/node/.gitignore"), { force: true });
+  await rm(path.join(nodeRoot, ".gitignore"), { force: true });
   console.log(`[postprocess-wasm] removed wasm-pack .gitignore stubs`);
 }
 
@@ -74,9 +78,20 @@ async function copyShimsIntoSrc() {
   console.log(`[postprocess-wasm] copied shims into ${path.relative(process.cwd(), srcShimDir)} (.d.ts + .js)`);
 }
 
+async function copyNodeBuildIntoSrc() {
+  // src/node.ts loads the nodejs glue with `require`, and vitest runs the
+  // client tests against that same Rust core from src/.
+  await mkdir(srcNodeShimDir, { recursive: true });
+  for (const file of ["bridge_wasm.js", "bridge_wasm.d.ts", "bridge_wasm_bg.wasm", "package.json"]) {
+    await copyFile(path.join(nodeRoot, file), path.join(srcNodeShimDir, file));
+  }
+  console.log(`[postprocess-wasm] copied the nodejs build into ${path.relative(process.cwd(), srcNodeShimDir)}`);
+}
+
 await emitInline();
 await patchGlue();
 await copyShimsIntoSrc();
 await dropStalePackageJson();
 await dropWasmPackGitignores();
 await dropWasmBinary();
+await copyNodeBuildIntoSrc();
@@ -0,0 +1,100 @@
+//! `bridge-typegen`: emits TypeScript declarations for the ROS interfaces
+//! found under one or more directories.
+//!
+//! ```text
+//! bridge-typegen [-o out.d.ts] <dir>...
+//! ```
+//!
+//! Files are expected at `<pkg>/msg/*.msg`, `<pkg>/srv/*.srv` and
+//! `<pkg>/action/*.action`, as in a ROS 2 package share directory.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::ExitCode;
+
+const USAGE: &str = "usage: bridge-typegen [-o out.d.ts] <dir>...";
+
+fn collect(dir: &Path, files: &mut Vec<(String, String)>) -> io::Result<()> {
+    let mut entries = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.path());
+    for entry in entries {
+        let path = entry.path();
+        if entry.file_type()?.is_dir() {
+            collect(&path, files)?;
+            continue;
+        }
+        let Some(kind @ ("msg" | "srv" | "action")) = path.extension().and_then(|e| e.to_str())
+        else {
+            continue;
+        };
+        let name = |path: Option<&Path>| {
+            path.and_then(Path::file_name)
+                .and_then(|name| name.to_str())
+                .map(str::to_owned)
+        };
+        let parent = path.parent();
+        // `<pkg>/msg/Name.msg`, or `<pkg>/Name.msg` for flat layouts.
+        let package = match name(parent) {
+            Some(dir) if dir == kind => name(parent.and_then(Path::parent)),
+            other => other,
+        };
+        let (Some(package), Some(stem)) = (package, path.file_stem().and_then(|s| s.to_str()))
+        else {
+            continue;
+        };
+        files.push((
+            format!("{package}/{kind}/{stem}"),
+            fs::read_to_string(&path)?,
+        ));
+    }
+    Ok(())
+}
+
+fn run() -> Result<(), String> {
+    let mut args = std::env::args().skip(1);
+    let mut output: Option<PathBuf> = None;
+    let mut dirs = Vec::new();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-o" | "--out" => {
+                output = Some(args.next().ok_or(USAGE)?.into());
+            }
+            "-h" | "--help" => {
+                println!("{USAGE}");
+                return Ok(());
+            }
+            _ => dirs.push(PathBuf::from(arg)),
+        }
+    }
+    if dirs.is_empty() {
+        return Err(USAGE.to_owned());
+    }
+
+    let mut files = Vec::new();
+    for dir in &dirs {
+        collect(dir, &mut files).map_err(|e| format!("cannot read {}: {e}", dir.display()))?;
+    }
+    if files.is_empty() {
+        return Err("no .msg, .srv or .action files found".to_owned());
+    }
+    let declarations = bridge_wasm::typegen::generate(&files).map_err(|e| e.to_string())?;
+    match output {
+        Some(path) => fs::write(&path, declarations)
+            .map_err(|e| format!("cannot write {}: {e}", path.display())),
+        None => {
+            print!("{declarations}");
+            Ok(())
+        }
+    }
+}
+
+fn main() -> ExitCode {
+    match run() {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            eprintln!("bridge-typegen: {message}");
+            ExitCode::FAILURE
+        }
+    }
+}
@@ -1,4 +1,5 @@
 import { BridgeClientCore } from "./client-core.js";
+import { BridgeError } from "./errors.js";
 import type { BridgeClientOptions, WasmProtocol, WebSocketLike } from "./types.js";
 // Static imports — every modern bundler resolves these without extra config.
 // `bridge_wasm.js` is the wasm-pack web glue (post-processed to remove its
@@ -9,6 +10,7 @@ import init, * as wasmModule from "./wasm/web/bridge_wasm.js";
 import { wasmBase64 } from "./wasm/web/bridge_wasm_inline.js";
 
 export { autoCodec, cborCodec, jsonCodec, resolveCodec } from "./codec.js";
+export { BridgeError } from "./errors.js";
 export type {
   BridgeCodec,
   BridgeCodecName,
@@ -36,10 +38,43 @@ async function loadBrowserProtocol(): Promise<WasmProtocol> {
         build_subscribe: wasmModule.build_subscribe,
         build_unsubscribe: wasmModule.build_unsubscribe,
         build_advertise: wasmModule.build_advertise,
+        build_unadvertise: wasmModule.build_unadvertise,
         build_publish: wasmModule.build_publish,
+        build_set_level: wasmModule.build_set_level,
         build_call_service: wasmModule.build_call_service,
         build_send_action_goal: wasmModule.build_send_action_goal,
-        build_cancel_action_goal: wasmModule.build_cancel_action_goal
+        build_cancel_action_goal: wasmModule.build_cancel_action_goal,
+        build_query_action_goal: wasmModule.build_query_action_goal,
+        build_advertise_service: wasmModule.build_advertise_service,
+        build_unadvertise_service: wasmModule.build_unadvertise_service,
+        build_service_response: wasmModule.build_service_response,
+        build_advertise_action: wasmModule.build_advertise_action,
+        build_unadvertise_action: wasmModule.build_unadvertise_action,
+        build_action_feedback: wasmModule.build_action_feedback,
+        build_action_result: wasmModule.build_action_result,
+        parse_incoming: wasmModule.parse_incoming,
+        encode_cbor: wasmModule.encode_cbor,
+        decode_cbor: wasmModule.decode_cbor,
+        decode_cdr: wasmModule.decode_cdr,
+        encode_cdr: wasmModule.encode_cdr,
+        register_message_definitions: wasmModule.register_message_definitions,
+        register_message_definition: wasmModule.register_message_definition,
+        registered_message_types: wasmModule.registered_message_types,
+        unresolved_message_types: wasmModule.unresolved_message_types,
+        get_message_schema: wasmModule.get_message_schema,
+        clear_message_definitions: wasmModule.clear_message_definitions,
+        validate_message: wasmModule.validate_message,
+        default_message: wasmModule.default_message,
+        merge_with_defaults: wasmModule.merge_with_defaults,
+        build_fragments: wasmModule.build_fragments,
+        decode_png: wasmModule.decode_png,
+        FragmentAssembler: wasmModule.FragmentAssembler,
+        SubscriptionRegistry: wasmModule.SubscriptionRegistry,
+        PublisherRegistry: wasmModule.PublisherRegistry,
+        RequestTracker: wasmModule.RequestTracker,
+        ReconnectPolicy: wasmModule.ReconnectPolicy,
+        ConnectionStateMachine: wasmModule.ConnectionStateMachine,
+        OutboundQueue: wasmModule.OutboundQueue
       };
     })();
   }
@@ -48,7 +83,7 @@ async function loadBrowserProtocol(): Promise<WasmProtocol> {
 
 function browserWebSocketFactory(url: string): WebSocketLike {
   if (!globalThis.WebSocket) {
-    throw new Error("Browser WebSocket is not available");
+    throw new BridgeError("unsupported", "Browser WebSocket is not available");
   }
   return new globalThis.WebSocket(url) as unknown as WebSocketLike;
 }
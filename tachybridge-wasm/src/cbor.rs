@@ -0,0 +1,1164 @@
+//! CBOR (RFC 8949) codec used for binary bridge frames.
+//!
+//! Decoding and encoding go through [`CborValue`] so the wire format can be
+//! tested natively; the wasm exports convert between that tree and JS values.
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+use wasm_bindgen::JsCast;
+
+use crate::error::BridgeError;
+
+/// Nesting limit for arrays, maps and tags, on both decode and encode.
+const MAX_DEPTH: usize = 256;
+
+const BREAK: u8 = 0xff;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum CborValue {
+    Unsigned(u64),
+    /// Negative integer `-1 - n`.
+    Negative(u64),
+    Bytes(Vec<u8>),
+    Text(String),
+    Array(Vec<CborValue>),
+    Map(Vec<(CborValue, CborValue)>),
+    Tag(u64, Box<CborValue>),
+    Bool(bool),
+    Null,
+    Undefined,
+    Simple(u8),
+    Float(f64),
+}
+
+impl CborValue {
+    pub fn from_i64(value: i64) -> Self {
+        if value >= 0 {
+            Self::Unsigned(value as u64)
+        } else {
+            Self::Negative((-1 - value) as u64)
+        }
+    }
+
+    /// Integer value of an integer or integral float.
+    pub fn as_integer(&self) -> Option<i128> {
+        match self {
+            Self::Unsigned(n) => Some(i128::from(*n)),
+            Self::Negative(n) => Some(-1 - i128::from(*n)),
+            Self::Float(f) if f.is_finite() && f.fract() == 0.0 => Some(*f as i128),
+            _ => None,
+        }
+    }
+
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Self::Float(f) => Some(*f),
+            other => other.as_integer().map(|n| n as f64),
+        }
+    }
+
+    /// Elements of an array-like value: an array, a byte string or a
+    /// typed-array tag.
+    pub fn array_items(&self) -> Option<Vec<CborValue>> {
+        fn ints<T: Copy + Into<i64>>(values: &[T]) -> Vec<CborValue> {
+            values
+                .iter()
+                .map(|v| CborValue::from_i64((*v).into()))
+                .collect()
+        }
+        fn uints<T: Copy + Into<u64>>(values: &[T]) -> Vec<CborValue> {
+            values
+                .iter()
+                .map(|v| CborValue::Unsigned((*v).into()))
+                .collect()
+        }
+        match self {
+            Self::Array(items) => Some(items.clone()),
+            Self::Bytes(bytes) => Some(uints(bytes)),
+            Self::Tag(tag, inner) => {
+                let Self::Bytes(bytes) = inner.as_ref() else {
+                    return None;
+                };
+                Some(match TypedArray::from_tagged(*tag, bytes).ok()?? {
+                    TypedArray::Uint8(v) | TypedArray::Uint8Clamped(v) => uints(&v),
+                    TypedArray::Uint16(v) => uints(&v),
+                    TypedArray::Uint32(v) => uints(&v),
+                    TypedArray::Uint64(v) => uints(&v),
+                    TypedArray::Int8(v) => ints(&v),
+                    TypedArray::Int16(v) => ints(&v),
+                    TypedArray::Int32(v) => ints(&v),
+                    TypedArray::Int64(v) => ints(&v),
+                    TypedArray::Float32(v) => {
+                        v.iter().map(|f| CborValue::Float(f64::from(*f))).collect()
+                    }
+                    TypedArray::Float64(v) => v.iter().map(|f| CborValue::Float(*f)).collect(),
+                })
+            }
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CborError {
+    UnexpectedEof,
+    TrailingBytes,
+    InvalidUtf8,
+    InvalidAdditionalInfo(u8),
+    InvalidIndefinite(u8),
+    UnexpectedBreak,
+    DepthExceeded,
+    Unsupported(String),
+}
+
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of CBOR payload"),
+            Self::TrailingBytes => write!(f, "trailing CBOR bytes detected"),
+            Self::InvalidUtf8 => write!(f, "CBOR text string is not valid UTF-8"),
+            Self::InvalidAdditionalInfo(info) => {
+                write!(f, "unsupported CBOR additional info: {info}")
+            }
+            Self::InvalidIndefinite(major) => {
+                write!(f, "invalid indefinite length for CBOR major type {major}")
+            }
+            Self::UnexpectedBreak => write!(f, "unexpected CBOR break"),
+            Self::DepthExceeded => write!(f, "CBOR nesting exceeds {MAX_DEPTH} levels"),
+            Self::Unsupported(what) => write!(f, "unsupported CBOR value: {what}"),
+        }
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.offset).copied()
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CborError> {
+        if len > self.remaining() {
+            return Err(CborError::UnexpectedEof);
+        }
+        let out = &self.bytes[self.offset..self.offset + len];
+        self.offset += len;
+        Ok(out)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], CborError> {
+        let mut out = [0u8; N];
+        out.copy_from_slice(self.take(N)?);
+        Ok(out)
+    }
+
+    /// Reads the argument of a head byte. `None` means indefinite length.
+    fn argument(&mut self, info: u8) -> Result<Option<u64>, CborError> {
+        match info {
+            0..=23 => Ok(Some(u64::from(info))),
+            24 => Ok(Some(u64::from(self.take_array::<1>()?[0]))),
+            25 => Ok(Some(u64::from(u16::from_be_bytes(self.take_array()?)))),
+            26 => Ok(Some(u64::from(u32::from_be_bytes(self.take_array()?)))),
+            27 => Ok(Some(u64::from_be_bytes(self.take_array()?))),
+            31 => Ok(None),
+            _ => Err(CborError::InvalidAdditionalInfo(info)),
+        }
+    }
+
+    fn length(&mut self, info: u8, major: u8) -> Result<Option<usize>, CborError> {
+        match self.argument(info)? {
+            Some(len) => usize::try_from(len)
+                .map(Some)
+                .map_err(|_| CborError::UnexpectedEof),
+            None if matches!(major, 2..=5) => Ok(None),
+            None => Err(CborError::InvalidIndefinite(major)),
+        }
+    }
+
+    fn at_break(&mut self) -> Result<bool, CborError> {
+        match self.peek() {
+            Some(BREAK) => {
+                self.offset += 1;
+                Ok(true)
+            }
+            Some(_) => Ok(false),
+            None => Err(CborError::UnexpectedEof),
+        }
+    }
+
+    /// Concatenates the definite-length chunks of an indefinite byte/text string.
+    fn chunks(&mut self, major: u8) -> Result<Vec<u8>, CborError> {
+        let mut out = Vec::new();
+        while !self.at_break()? {
+            let head = self.take_array::<1>()?[0];
+            if head >> 5 != major {
+                return Err(CborError::InvalidIndefinite(major));
+            }
+            let Some(len) = self.length(head & 0x1f, major)? else {
+                return Err(CborError::InvalidIndefinite(major));
+            };
+            out.extend_from_slice(self.take(len)?);
+        }
+        Ok(out)
+    }
+
+    fn value(&mut self, depth: usize) -> Result<CborValue, CborError> {
+        if depth > MAX_DEPTH {
+            return Err(CborError::DepthExceeded);
+        }
+        let head = self.take_array::<1>()?[0];
+        let major = head >> 5;
+        let info = head & 0x1f;
+
+        match major {
+            0 | 1 => {
+                let Some(n) = self.argument(info)? else {
+                    return Err(CborError::InvalidIndefinite(major));
+                };
+                Ok(if major == 0 {
+                    CborValue::Unsigned(n)
+                } else {
+                    CborValue::Negative(n)
+                })
+            }
+            2 => match self.length(info, major)? {
+                Some(len) => Ok(CborValue::Bytes(self.take(len)?.to_vec())),
+                None => Ok(CborValue::Bytes(self.chunks(major)?)),
+            },
+            3 => {
+                let raw = match self.length(info, major)? {
+                    Some(len) => self.take(len)?.to_vec(),
+                    None => self.chunks(major)?,
+                };
+                String::from_utf8(raw)
+                    .map(CborValue::Text)
+                    .map_err(|_| CborError::InvalidUtf8)
+            }
+            4 => {
+                let mut items = Vec::new();
+                match self.length(info, major)? {
+                    Some(len) => {
+                        // Every item takes at least one byte, so never trust the
+                        // header for more capacity than the input can back.
+                        items.reserve(len.min(self.remaining()));
+                        for _ in 0..len {
+                            items.push(self.value(depth + 1)?);
+                        }
+                    }
+                    None => {
+                        while !self.at_break()? {
+                            items.push(self.value(depth + 1)?);
+                        }
+                    }
+                }
+                Ok(CborValue::Array(items))
+            }
+            5 => {
+                let mut entries = Vec::new();
+                match self.length(info, major)? {
+                    Some(len) => {
+                        entries.reserve(len.min(self.remaining() / 2));
+                        for _ in 0..len {
+                            let key = self.value(depth + 1)?;
+                            entries.push((key, self.value(depth + 1)?));
+                        }
+                    }
+                    None => {
+                        while !self.at_break()? {
+                            let key = self.value(depth + 1)?;
+                            entries.push((key, self.value(depth + 1)?));
+                        }
+                    }
+                }
+                Ok(CborValue::Map(entries))
+            }
+            6 => {
+                let Some(tag) = self.argument(info)? else {
+                    return Err(CborError::InvalidIndefinite(major));
+                };
+                Ok(CborValue::Tag(tag, Box::new(self.value(depth + 1)?)))
+            }
+            _ => match info {
+                20 => Ok(CborValue::Bool(false)),
+                21 => Ok(CborValue::Bool(true)),
+                22 => Ok(CborValue::Null),
+                23 => Ok(CborValue::Undefined),
+                0..=19 => Ok(CborValue::Simple(info)),
+                24 => Ok(CborValue::Simple(self.take_array::<1>()?[0])),
+                25 => Ok(CborValue::Float(f16_to_f64(u16::from_be_bytes(
+                    self.take_array()?,
+                )))),
+                26 => Ok(CborValue::Float(f64::from(f32::from_be_bytes(
+                    self.take_array()?,
+                )))),
+                27 => Ok(CborValue::Float(f64::from_be_bytes(self.take_array()?))),
+                31 => Err(CborError::UnexpectedBreak),
+                _ => Err(CborError::InvalidAdditionalInfo(info)),
+            },
+        }
+    }
+}
+
+/// Decodes exactly one CBOR data item.
+pub fn decode(bytes: &[u8]) -> Result<CborValue, CborError> {
+    let mut reader = Reader { bytes, offset: 0 };
+    let value = reader.value(0)?;
+    if reader.remaining() != 0 {
+        return Err(CborError::TrailingBytes);
+    }
+    Ok(value)
+}
+
+fn write_head(out: &mut Vec<u8>, major: u8, arg: u64) {
+    let major = major << 5;
+    if arg < 24 {
+        out.push(major | arg as u8);
+    } else if let Ok(arg) = u8::try_from(arg) {
+        out.extend_from_slice(&[major | 24, arg]);
+    } else if let Ok(arg) = u16::try_from(arg) {
+        out.push(major | 25);
+        out.extend_from_slice(&arg.to_be_bytes());
+    } else if let Ok(arg) = u32::try_from(arg) {
+        out.push(major | 26);
+        out.extend_from_slice(&arg.to_be_bytes());
+    } else {
+        out.push(major | 27);
+        out.extend_from_slice(&arg.to_be_bytes());
+    }
+}
+
+/// Writes a float in the shortest of half/single/double precision that
+/// preserves its value (RFC 8949 preferred serialization).
+fn write_float(out: &mut Vec<u8>, value: f64) {
+    if let Some(half) = f64_to_f16_exact(value) {
+        out.push(0xf9);
+        out.extend_from_slice(&half.to_be_bytes());
+    } else if f64::from(value as f32) == value {
+        out.push(0xfa);
+        out.extend_from_slice(&(value as f32).to_be_bytes());
+    } else {
+        out.push(0xfb);
+        out.extend_from_slice(&value.to_be_bytes());
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &CborValue) {
+    match value {
+        CborValue::Unsigned(n) => write_head(out, 0, *n),
+        CborValue::Negative(n) => write_head(out, 1, *n),
+        CborValue::Bytes(bytes) => {
+            write_head(out, 2, bytes.len() as u64);
+            out.extend_from_slice(bytes);
+        }
+        CborValue::Text(text) => {
+            write_head(out, 3, text.len() as u64);
+            out.extend_from_slice(text.as_bytes());
+        }
+        CborValue::Array(items) => {
+            write_head(out, 4, items.len() as u64);
+            for item in items {
+                write_value(out, item);
+            }
+        }
+        CborValue::Map(entries) => {
+            write_head(out, 5, entries.len() as u64);
+            for (key, item) in entries {
+                write_value(out, key);
+                write_value(out, item);
+            }
+        }
+        CborValue::Tag(tag, inner) => {
+            write_head(out, 6, *tag);
+            write_value(out, inner);
+        }
+        CborValue::Bool(flag) => out.push(if *flag { 0xf5 } else { 0xf4 }),
+        CborValue::Null => out.push(0xf6),
+        CborValue::Undefined => out.push(0xf7),
+        CborValue::Simple(n) if *n < 24 => out.push(0xe0 | n),
+        CborValue::Simple(n) => out.extend_from_slice(&[0xf8, *n]),
+        CborValue::Float(f) => write_float(out, *f),
+    }
+}
+
+/// Encodes a value using definite lengths only.
+pub fn encode(value: &CborValue) -> Vec<u8> {
+    let mut out = Vec::new();
+    write_value(&mut out, value);
+    out
+}
+
+fn f16_to_f64(bits: u16) -> f64 {
+    let sign = if bits & 0x8000 != 0 { -1.0 } else { 1.0 };
+    let exponent = i32::from((bits >> 10) & 0x1f);
+    let fraction = f64::from(bits & 0x3ff);
+    match exponent {
+        0 => sign * fraction * 2f64.powi(-24),
+        31 if fraction == 0.0 => sign * f64::INFINITY,
+        31 => f64::NAN,
+        _ => sign * (1.0 + fraction / 1024.0) * 2f64.powi(exponent - 15),
+    }
+}
+
+fn f64_to_f16_exact(value: f64) -> Option<u16> {
+    let sign: u16 = if value.is_sign_negative() { 0x8000 } else { 0 };
+    if value.is_nan() {
+        return Some(0x7e00);
+    }
+    if value.is_infinite() {
+        return Some(sign | 0x7c00);
+    }
+    if value == 0.0 {
+        return Some(sign);
+    }
+    let magnitude = value.abs();
+    // Subnormal half floats are integer multiples of 2^-24.
+    let scaled = magnitude * 2f64.powi(24);
+    if magnitude < 2f64.powi(-14) {
+        return (scaled.fract() == 0.0).then_some(sign | scaled as u16);
+    }
+    let single = magnitude as f32;
+    if f64::from(single) != magnitude {
+        return None;
+    }
+    let bits = single.to_bits();
+    let exponent = ((bits >> 23) & 0xff) as i32 - 127;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent > 15 || mantissa & 0x1fff != 0 {
+        return None;
+    }
+    Some(sign | (((exponent + 15) as u16) << 10) | (mantissa >> 13) as u16)
+}
+
+/// Element type of an RFC 8746 typed array.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TypedArrayKind {
+    Uint8,
+    Uint8Clamped,
+    Uint16,
+    Uint32,
+    Uint64,
+    Int8,
+    Int16,
+    Int32,
+    Int64,
+    Float16,
+    Float32,
+    Float64,
+}
+
+impl TypedArrayKind {
+    /// Kind and little-endian flag for tags 64..=87. Tag 76 is reserved and
+    /// float128 (83, 87) has no JS counterpart, so both yield `None`.
+    pub fn from_tag(tag: u64) -> Option<(Self, bool)> {
+        if !(64..=87).contains(&tag) {
+            return None;
+        }
+        // 0b010_f_s_e_ll: float, signed, little endian, log2 of element size.
+        let bits = tag - 64;
+        let float = bits & 0b1_0000 != 0;
+        let signed = bits & 0b1000 != 0;
+        let little = bits & 0b100 != 0;
+        let kind = match (float, signed, bits & 0b11) {
+            (false, false, 0) if little => Self::Uint8Clamped,
+            (false, false, 0) => Self::Uint8,
+            (false, false, 1) => Self::Uint16,
+            (false, false, 2) => Self::Uint32,
+            (false, false, 3) => Self::Uint64,
+            (false, true, 0) if little => return None,
+            (false, true, 0) => Self::Int8,
+            (false, true, 1) => Self::Int16,
+            (false, true, 2) => Self::Int32,
+            (false, true, 3) => Self::Int64,
+            (true, false, 0) => Self::Float16,
+            (true, false, 1) => Self::Float32,
+            (true, false, 2) => Self::Float64,
+            _ => return None,
+        };
+        Some((kind, little))
+    }
+
+    /// Little-endian tag used when encoding.
+    pub fn tag(self) -> u64 {
+        match self {
+            Self::Uint8 => 64,
+            Self::Uint8Clamped => 68,
+            Self::Uint16 => 69,
+            Self::Uint32 => 70,
+            Self::Uint64 => 71,
+            Self::Int8 => 72,
+            Self::Int16 => 77,
+            Self::Int32 => 78,
+            Self::Int64 => 79,
+            Self::Float16 => 84,
+            Self::Float32 => 85,
+            Self::Float64 => 86,
+        }
+    }
+
+    pub fn element_size(self) -> usize {
+        match self {
+            Self::Uint8 | Self::Uint8Clamped | Self::Int8 => 1,
+            Self::Uint16 | Self::Int16 | Self::Float16 => 2,
+            Self::Uint32 | Self::Int32 | Self::Float32 => 4,
+            Self::Uint64 | Self::Int64 | Self::Float64 => 8,
+        }
+    }
+}
+
+/// Decoded typed-array contents in native representation.
+///
+/// Half floats are widened to `Float32` since JS has no portable
+/// `Float16Array`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TypedArray {
+    Uint8(Vec<u8>),
+    Uint8Clamped(Vec<u8>),
+    Uint16(Vec<u16>),
+    Uint32(Vec<u32>),
+    Uint64(Vec<u64>),
+    Int8(Vec<i8>),
+    Int16(Vec<i16>),
+    Int32(Vec<i32>),
+    Int64(Vec<i64>),
+    Float32(Vec<f32>),
+    Float64(Vec<f64>),
+}
+
+fn elements<const N: usize, T>(
+    bytes: &[u8],
+    little: bool,
+    from_le: fn([u8; N]) -> T,
+    from_be: fn([u8; N]) -> T,
+) -> Vec<T> {
+    bytes
+        .chunks_exact(N)
+        .map(|chunk| {
+            let mut raw = [0u8; N];
+            raw.copy_from_slice(chunk);
+            if little {
+                from_le(raw)
+            } else {
+                from_be(raw)
+            }
+        })
+        .collect()
+}
+
+fn le_bytes<const N: usize, T: Copy>(values: &[T], to_le: fn(T) -> [u8; N]) -> Vec<u8> {
+    values.iter().flat_map(|v| to_le(*v)).collect()
+}
+
+impl TypedArray {
+    /// Decodes the byte string of a typed-array tag. Returns `Ok(None)` when the
+    /// tag is not a supported typed-array tag.
+    pub fn from_tagged(tag: u64, bytes: &[u8]) -> Result<Option<Self>, CborError> {
+        let Some((kind, little)) = TypedArrayKind::from_tag(tag) else {
+            return Ok(None);
+        };
+        if !bytes.len().is_multiple_of(kind.element_size()) {
+            return Err(CborError::Unsupported(format!(
+                "typed array tag {tag} with {} bytes",
+                bytes.len()
+            )));
+        }
+        Ok(Some(Self::from_bytes(kind, bytes, little)))
+    }
+
+    /// Reads packed elements of `kind`. Trailing bytes that do not fill a whole
+    /// element are ignored.
+    pub fn from_bytes(kind: TypedArrayKind, bytes: &[u8], little: bool) -> Self {
+        match kind {
+            TypedArrayKind::Uint8 => Self::Uint8(bytes.to_vec()),
+            TypedArrayKind::Uint8Clamped => Self::Uint8Clamped(bytes.to_vec()),
+            TypedArrayKind::Int8 => Self::Int8(bytes.iter().map(|b| *b as i8).collect()),
+            TypedArrayKind::Uint16 => Self::Uint16(elements(
+                bytes,
+                little,
+                u16::from_le_bytes,
+                u16::from_be_bytes,
+            )),
+            TypedArrayKind::Uint32 => Self::Uint32(elements(
+                bytes,
+                little,
+                u32::from_le_bytes,
+                u32::from_be_bytes,
+            )),
+            TypedArrayKind::Uint64 => Self::Uint64(elements(
+                bytes,
+                little,
+                u64::from_le_bytes,
+                u64::from_be_bytes,
+            )),
+            TypedArrayKind::Int16 => Self::Int16(elements(
+                bytes,
+                little,
+                i16::from_le_bytes,
+                i16::from_be_bytes,
+            )),
+            TypedArrayKind::Int32 => Self::Int32(elements(
+                bytes,
+                little,
+                i32::from_le_bytes,
+                i32::from_be_bytes,
+            )),
+            TypedArrayKind::Int64 => Self::Int64(elements(
+                bytes,
+                little,
+                i64::from_le_bytes,
+                i64::from_be_bytes,
+            )),
+            TypedArrayKind::Float16 => Self::Float32(
+                elements(bytes, little, u16::from_le_bytes, u16::from_be_bytes)
+                    .into_iter()
+                    .map(|bits| f16_to_f64(bits) as f32)
+                    .collect(),
+            ),
+            TypedArrayKind::Float32 => Self::Float32(elements(
+                bytes,
+                little,
+                f32::from_le_bytes,
+                f32::from_be_bytes,
+            )),
+            TypedArrayKind::Float64 => Self::Float64(elements(
+                bytes,
+                little,
+                f64::from_le_bytes,
+                f64::from_be_bytes,
+            )),
+        }
+    }
+
+    /// Encodes as a little-endian typed-array tag. Plain bytes stay a CBOR
+    /// byte string, which is how rosbridge sends `uint8[]`.
+    pub fn to_cbor(&self) -> CborValue {
+        let (kind, bytes) = match self {
+            Self::Uint8(v) => return CborValue::Bytes(v.clone()),
+            Self::Uint8Clamped(v) => (TypedArrayKind::Uint8Clamped, v.clone()),
+            Self::Int8(v) => (TypedArrayKind::Int8, v.iter().map(|b| *b as u8).collect()),
+            Self::Uint16(v) => (TypedArrayKind::Uint16, le_bytes(v, u16::to_le_bytes)),
+            Self::Uint32(v) => (TypedArrayKind::Uint32, le_bytes(v, u32::to_le_bytes)),
+            Self::Uint64(v) => (TypedArrayKind::Uint64, le_bytes(v, u64::to_le_bytes)),
+            Self::Int16(v) => (TypedArrayKind::Int16, le_bytes(v, i16::to_le_bytes)),
+            Self::Int32(v) => (TypedArrayKind::Int32, le_bytes(v, i32::to_le_bytes)),
+            Self::Int64(v) => (TypedArrayKind::Int64, le_bytes(v, i64::to_le_bytes)),
+            Self::Float32(v) => (TypedArrayKind::Float32, le_bytes(v, f32::to_le_bytes)),
+            Self::Float64(v) => (TypedArrayKind::Float64, le_bytes(v, f64::to_le_bytes)),
+        };
+        CborValue::Tag(kind.tag(), Box::new(CborValue::Bytes(bytes)))
+    }
+
+    fn to_js(&self) -> JsValue {
+        match self {
+            Self::Uint8(v) => js_sys::Uint8Array::from(v.as_slice()).into(),
+            Self::Uint8Clamped(v) => js_sys::Uint8ClampedArray::from(v.as_slice()).into(),
+            Self::Uint16(v) => js_sys::Uint16Array::from(v.as_slice()).into(),
+            Self::Uint32(v) => js_sys::Uint32Array::from(v.as_slice()).into(),
+            Self::Uint64(v) => js_sys::BigUint64Array::from(v.as_slice()).into(),
+            Self::Int8(v) => js_sys::Int8Array::from(v.as_slice()).into(),
+            Self::Int16(v) => js_sys::Int16Array::from(v.as_slice()).into(),
+            Self::Int32(v) => js_sys::Int32Array::from(v.as_slice()).into(),
+            Self::Int64(v) => js_sys::BigInt64Array::from(v.as_slice()).into(),
+            Self::Float32(v) => js_sys::Float32Array::from(v.as_slice()).into(),
+            Self::Float64(v) => js_sys::Float64Array::from(v.as_slice()).into(),
+        }
+    }
+
+    fn from_js(value: &JsValue) -> Option<Self> {
+        macro_rules! try_view {
+            ($($js:ident => $variant:ident),* $(,)?) => {
+                $(if let Some(view) = value.dyn_ref::<js_sys::$js>() {
+                    return Some(Self::$variant(view.to_vec()));
+                })*
+            };
+        }
+        try_view!(
+            Uint8Array => Uint8,
+            Uint8ClampedArray => Uint8Clamped,
+            Uint16Array => Uint16,
+            Uint32Array => Uint32,
+            BigUint64Array => Uint64,
+            Int8Array => Int8,
+            Int16Array => Int16,
+            Int32Array => Int32,
+            BigInt64Array => Int64,
+            Float32Array => Float32,
+            Float64Array => Float64,
+        );
+        None
+    }
+}
+
+const MAX_SAFE_INTEGER: u64 = (1 << 53) - 1;
+
+fn cbor_err(error: CborError) -> JsValue {
+    BridgeError::invalid_input(error.to_string()).into()
+}
+
+fn bignum_to_js(negative: bool, bytes: &[u8]) -> Result<JsValue, JsValue> {
+    let hex: String = bytes.iter().map(|b| format!("{b:02x}")).collect();
+    let magnitude = js_sys::BigInt::new(&JsValue::from_str(&format!(
+        "0x{}",
+        if hex.is_empty() { "0" } else { &hex }
+    )))?;
+    if negative {
+        // Tag 3 encodes -1 - n.
+        Ok(JsValue::from(-magnitude - js_sys::BigInt::from(1u8)))
+    } else {
+        Ok(magnitude.into())
+    }
+}
+
+fn map_key(key: &CborValue) -> Result<String, JsValue> {
+    match key {
+        CborValue::Text(text) => Ok(text.clone()),
+        CborValue::Unsigned(n) => Ok(n.to_string()),
+        CborValue::Negative(n) => Ok(format!("-{}", u128::from(*n) + 1)),
+        CborValue::Bool(flag) => Ok(flag.to_string()),
+        CborValue::Null => Ok("null".to_owned()),
+        CborValue::Float(f) => Ok(f.to_string()),
+        other => Err(cbor_err(CborError::Unsupported(format!(
+            "map key {other:?}"
+        )))),
+    }
+}
+
+pub(crate) fn value_to_js(value: &CborValue) -> Result<JsValue, JsValue> {
+    Ok(match value {
+        CborValue::Unsigned(n) if *n <= MAX_SAFE_INTEGER => JsValue::from_f64(*n as f64),
+        CborValue::Unsigned(n) => js_sys::BigInt::from(*n).into(),
+        CborValue::Negative(n) if *n < MAX_SAFE_INTEGER => JsValue::from_f64(-(*n as f64) - 1.0),
+        CborValue::Negative(n) => {
+            JsValue::from(-js_sys::BigInt::from(*n) - js_sys::BigInt::from(1u8))
+        }
+        CborValue::Bytes(bytes) => js_sys::Uint8Array::from(bytes.as_slice()).into(),
+        CborValue::Text(text) => JsValue::from_str(text),
+        CborValue::Array(items) => {
+            let out = js_sys::Array::new_with_length(items.len() as u32);
+            for (index, item) in items.iter().enumerate() {
+                out.set(index as u32, value_to_js(item)?);
+            }
+            out.into()
+        }
+        CborValue::Map(entries) => {
+            let out = js_sys::Object::new();
+            for (key, item) in entries {
+                js_sys::Reflect::set(
+                    &out,
+                    &JsValue::from_str(&map_key(key)?),
+                    &value_to_js(item)?,
+                )?;
+            }
+            out.into()
+        }
+        CborValue::Tag(tag @ (2 | 3), inner) => match inner.as_ref() {
+            CborValue::Bytes(bytes) => bignum_to_js(*tag == 3, bytes)?,
+            other => value_to_js(other)?,
+        },
+        CborValue::Tag(tag @ 64..=87, inner) => match inner.as_ref() {
+            CborValue::Bytes(bytes) => {
+                match TypedArray::from_tagged(*tag, bytes).map_err(cbor_err)? {
+                    Some(array) => array.to_js(),
+                    None => value_to_js(inner)?,
+                }
+            }
+            other => value_to_js(other)?,
+        },
+        // Other tags carry no meaning for bridge payloads; expose the content.
+        CborValue::Tag(_, inner) => value_to_js(inner)?,
+        CborValue::Bool(flag) => JsValue::from_bool(*flag),
+        CborValue::Null => JsValue::NULL,
+        CborValue::Undefined => JsValue::UNDEFINED,
+        CborValue::Simple(n) => JsValue::from_f64(f64::from(*n)),
+        CborValue::Float(f) => JsValue::from_f64(*f),
+    })
+}
+
+fn bigint_to_value(value: js_sys::BigInt) -> Result<CborValue, JsValue> {
+    let zero = js_sys::BigInt::from(0u8);
+    let negative = value < zero;
+    // -1 - n for negatives, mirroring the CBOR encoding.
+    let magnitude = if negative {
+        -value - js_sys::BigInt::from(1u8)
+    } else {
+        value
+    };
+    if let Ok(n) = u64::try_from(magnitude.clone()) {
+        return Ok(if negative {
+            CborValue::Negative(n)
+        } else {
+            CborValue::Unsigned(n)
+        });
+    }
+    let hex = magnitude.to_string(16)?.as_string().unwrap_or_default();
+    let padded = if hex.len() % 2 == 1 {
+        format!("0{hex}")
+    } else {
+        hex
+    };
+    let bytes = (0..padded.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&padded[i..i + 2], 16))
+        .collect::<Result<Vec<u8>, _>>()
+        .map_err(|e| BridgeError::invalid_input(format!("invalid bigint: {e}")))?;
+    Ok(CborValue::Tag(
+        if negative { 3 } else { 2 },
+        Box::new(CborValue::Bytes(bytes)),
+    ))
+}
+
+/// Byte window of any `ArrayBuffer` view (typed array or `DataView`).
+fn view_bytes(view: &JsValue) -> Result<js_sys::Uint8Array, JsValue> {
+    let get = |key: &str| js_sys::Reflect::get(view, &JsValue::from_str(key));
+    let buffer: js_sys::ArrayBuffer = get("buffer")?.unchecked_into();
+    let offset = get("byteOffset")?.as_f64().unwrap_or(0.0) as u32;
+    let length = get("byteLength")?.as_f64().unwrap_or(0.0) as u32;
+    Ok(js_sys::Uint8Array::new_with_byte_offset_and_length(
+        &buffer, offset, length,
+    ))
+}
+
+pub(crate) fn js_to_value(value: &JsValue, depth: usize) -> Result<CborValue, JsValue> {
+    if depth > MAX_DEPTH {
+        return Err(cbor_err(CborError::DepthExceeded));
+    }
+    if value.is_null() {
+        return Ok(CborValue::Null);
+    }
+    if value.is_undefined() {
+        return Ok(CborValue::Undefined);
+    }
+    if let Some(flag) = value.as_bool() {
+        return Ok(CborValue::Bool(flag));
+    }
+    if let Some(number) = value.as_f64() {
+        let is_safe_integer = number.fract() == 0.0 && number.abs() <= MAX_SAFE_INTEGER as f64;
+        return Ok(match is_safe_integer {
+            true if number >= 0.0 => CborValue::Unsigned(number as u64),
+            true => CborValue::Negative((-number - 1.0) as u64),
+            false => CborValue::Float(number),
+        });
+    }
+    if let Some(text) = value.as_string() {
+        return Ok(CborValue::Text(text));
+    }
+    if value.is_bigint() {
+        return bigint_to_value(value.clone().unchecked_into());
+    }
+    if let Some(array) = TypedArray::from_js(value) {
+        return Ok(array.to_cbor());
+    }
+    if let Some(buffer) = value.dyn_ref::<js_sys::ArrayBuffer>() {
+        return Ok(CborValue::Bytes(js_sys::Uint8Array::new(buffer).to_vec()));
+    }
+    if js_sys::ArrayBuffer::is_view(value) {
+        return Ok(CborValue::Bytes(view_bytes(value)?.to_vec()));
+    }
+    if let Some(items) = value.dyn_ref::<js_sys::Array>() {
+        return items
+            .iter()
+            .map(|item| js_to_value(&item, depth + 1))
+            .collect::<Result<_, _>>()
+            .map(CborValue::Array);
+    }
+    if let Some(map) = value.dyn_ref::<js_sys::Map>() {
+        let mut entries = Vec::with_capacity(map.size() as usize);
+        let mut failure = None;
+        map.for_each(&mut |item, key| {
+            if failure.is_some() {
+                return;
+            }
+            match (js_to_value(&key, depth + 1), js_to_value(&item, depth + 1)) {
+                (Ok(key), Ok(item)) => entries.push((key, item)),
+                (Err(e), _) | (_, Err(e)) => failure = Some(e),
+            }
+        });
+        return match failure {
+            Some(error) => Err(error),
+            None => Ok(CborValue::Map(entries)),
+        };
+    }
+    if value.is_object() && !value.is_function() {
+        let mut entries = Vec::new();
+        for entry in js_sys::Object::entries(value.unchecked_ref()).iter() {
+            let pair: js_sys::Array = entry.unchecked_into();
+            let item = pair.get(1);
+            if item.is_undefined() {
+                continue;
+            }
+            let key = pair.get(0).as_string().unwrap_or_default();
+            entries.push((CborValue::Text(key), js_to_value(&item, depth + 1)?));
+        }
+        return Ok(CborValue::Map(entries));
+    }
+    Err(cbor_err(CborError::Unsupported(
+        value.js_typeof().as_string().unwrap_or_default(),
+    )))
+}
+
+#[wasm_bindgen]
+pub fn encode_cbor(value: JsValue) -> Result<Vec<u8>, JsValue> {
+    Ok(encode(&js_to_value(&value, 0)?))
+}
+
+#[wasm_bindgen]
+pub fn decode_cbor(bytes: &[u8]) -> Result<JsValue, JsValue> {
+    let value = decode(bytes)
+        .map_err(|e| BridgeError::protocol_violation(e.to_string()).with_op("cbor"))?;
+    value_to_js(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hex(text: &str) -> Vec<u8> {
+        (0..text.len())
+            .step_by(2)
+            .map(|i| u8::from_str_radix(&text[i..i + 2], 16).unwrap())
+            .collect()
+    }
+
+    /// xorshift64*, enough to drive deterministic fuzz loops.
+    struct Rng(u64);
+
+    impl Rng {
+        fn next(&mut self) -> u64 {
+            self.0 ^= self.0 >> 12;
+            self.0 ^= self.0 << 25;
+            self.0 ^= self.0 >> 27;
+            self.0.wrapping_mul(0x2545_f491_4f6c_dd1d)
+        }
+
+        fn below(&mut self, n: u64) -> u64 {
+            self.next() % n
+        }
+    }
+
+    fn text(s: &str) -> CborValue {
+        CborValue::Text(s.to_owned())
+    }
+
+    #[test]
+    fn decodes_rfc8949_vectors() {
+        let cases = [
+            ("00", CborValue::Unsigned(0)),
+            ("17", CborValue::Unsigned(23)),
+            ("1818", CborValue::Unsigned(24)),
+            ("1bffffffffffffffff", CborValue::Unsigned(u64::MAX)),
+            ("3903e7", CborValue::Negative(999)),
+            ("f93c00", CborValue::Float(1.0)),
+            ("f97bff", CborValue::Float(65504.0)),
+            ("f90001", CborValue::Float(5.960464477539063e-8)),
+            ("fa47c35000", CborValue::Float(100000.0)),
+            ("fb3ff199999999999a", CborValue::Float(1.1)),
+            ("f9fc00", CborValue::Float(f64::NEG_INFINITY)),
+            ("f4", CborValue::Bool(false)),
+            ("f6", CborValue::Null),
+            ("f7", CborValue::Undefined),
+            ("f0", CborValue::Simple(16)),
+            ("f8ff", CborValue::Simple(255)),
+            (
+                "c11a514b67b0",
+                CborValue::Tag(1, Box::new(CborValue::Unsigned(1363896240))),
+            ),
+            ("4401020304", CborValue::Bytes(vec![1, 2, 3, 4])),
+            ("62225c", text("\"\\")),
+            ("5f42010243030405ff", CborValue::Bytes(vec![1, 2, 3, 4, 5])),
+            ("7f657374726561646d696e67ff", text("streaming")),
+            (
+                "9f018202039f0405ffff",
+                CborValue::Array(vec![
+                    CborValue::Unsigned(1),
+                    CborValue::Array(vec![CborValue::Unsigned(2), CborValue::Unsigned(3)]),
+                    CborValue::Array(vec![CborValue::Unsigned(4), CborValue::Unsigned(5)]),
+                ]),
+            ),
+            (
+                "bf61610161629f0203ffff",
+                CborValue::Map(vec![
+                    (text("a"), CborValue::Unsigned(1)),
+                    (
+                        text("b"),
+                        CborValue::Array(vec![CborValue::Unsigned(2), CborValue::Unsigned(3)]),
+                    ),
+                ]),
+            ),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(decode(&hex(input)).unwrap(), expected, "{input}");
+        }
+        assert!(matches!(decode(&hex("f97e00")).unwrap(), CborValue::Float(f) if f.is_nan()));
+    }
+
+    #[test]
+    fn encodes_shortest_float() {
+        assert_eq!(encode(&CborValue::Float(1.5)), hex("f93e00"));
+        assert_eq!(encode(&CborValue::Float(-0.0)), hex("f98000"));
+        assert_eq!(
+            encode(&CborValue::Float(5.960464477539063e-8)),
+            hex("f90001")
+        );
+        assert_eq!(encode(&CborValue::Float(100000.0)), hex("fa47c35000"));
+        assert_eq!(encode(&CborValue::Float(1.1)), hex("fb3ff199999999999a"));
+        assert_eq!(encode(&CborValue::Float(f64::NAN)), hex("f97e00"));
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let cases = [
+            ("", CborError::UnexpectedEof),
+            ("0001", CborError::TrailingBytes),
+            ("ff", CborError::UnexpectedBreak),
+            ("1c", CborError::InvalidAdditionalInfo(28)),
+            ("1f", CborError::InvalidIndefinite(0)),
+            ("5f6161ff", CborError::InvalidIndefinite(2)),
+            ("62c328", CborError::InvalidUtf8),
+            ("9f01", CborError::UnexpectedEof),
+            ("5bffffffffffffffff", CborError::UnexpectedEof),
+        ];
+        for (input, expected) in cases {
+            assert_eq!(decode(&hex(input)), Err(expected), "{input}");
+        }
+        let deep = vec![0x81; MAX_DEPTH + 2];
+        assert_eq!(decode(&deep), Err(CborError::DepthExceeded));
+    }
+
+    #[test]
+    fn decodes_typed_array_tags_in_both_endiannesses() {
+        let be = TypedArray::from_tagged(65, &[0x01, 0x02, 0xff, 0xfe]).unwrap();
+        assert_eq!(be, Some(TypedArray::Uint16(vec![0x0102, 0xfffe])));
+        let le = TypedArray::from_tagged(69, &[0x01, 0x02, 0xff, 0xfe]).unwrap();
+        assert_eq!(le, Some(TypedArray::Uint16(vec![0x0201, 0xfeff])));
+
+        let f32_be = TypedArray::from_tagged(81, &1.5f32.to_be_bytes()).unwrap();
+        assert_eq!(f32_be, Some(TypedArray::Float32(vec![1.5])));
+        let f64_le = TypedArray::from_tagged(86, &(-2.25f64).to_le_bytes()).unwrap();
+        assert_eq!(f64_le, Some(TypedArray::Float64(vec![-2.25])));
+        let half = TypedArray::from_tagged(84, &[0x00, 0x3c, 0x00, 0xc0]).unwrap();
+        assert_eq!(half, Some(TypedArray::Float32(vec![1.0, -2.0])));
+
+        let i16_be = TypedArray::from_tagged(73, &[0xff, 0xfe]).unwrap();
+        assert_eq!(i16_be, Some(TypedArray::Int16(vec![-2])));
+        let i64_le = TypedArray::from_tagged(79, &(-5i64).to_le_bytes()).unwrap();
+        assert_eq!(i64_le, Some(TypedArray::Int64(vec![-5])));
+        let i8s = TypedArray::from_tagged(72, &[0x80, 0x7f]).unwrap();
+        assert_eq!(i8s, Some(TypedArray::Int8(vec![-128, 127])));
+        let clamped = TypedArray::from_tagged(68, &[1, 2]).unwrap();
+        assert_eq!(clamped, Some(TypedArray::Uint8Clamped(vec![1, 2])));
+    }
+
+    #[test]
+    fn rejects_unsupported_or_misaligned_typed_arrays() {
+        assert_eq!(TypedArray::from_tagged(76, &[]), Ok(None));
+        assert_eq!(TypedArray::from_tagged(83, &[0; 16]), Ok(None));
+        assert_eq!(TypedArray::from_tagged(1, &[]), Ok(None));
+        assert!(TypedArray::from_tagged(70, &[0; 6]).is_err());
+    }
+
+    #[test]
+    fn encodes_typed_arrays_as_little_endian_tags() {
+        // Tag 85 (float32 little endian) around a 4-byte string.
+        let encoded = encode(&TypedArray::Float32(vec![1.0]).to_cbor());
+        assert_eq!(encoded, hex("d855440000803f"));
+        assert_eq!(encode(&TypedArray::Uint8(vec![7]).to_cbor()), hex("4107"));
+
+        for kind in [
+            TypedArrayKind::Uint16,
+            TypedArrayKind::Int32,
+            TypedArrayKind::Uint64,
+            TypedArrayKind::Float64,
+            TypedArrayKind::Int8,
+        ] {
+            assert_eq!(
+                TypedArrayKind::from_tag(kind.tag()),
+                Some((kind, kind != TypedArrayKind::Int8))
+            );
+        }
+
+        let original = TypedArray::Int16(vec![-1, 300, i16::MIN]);
+        let CborValue::Tag(tag, inner) = decode(&encode(&original.to_cbor())).unwrap() else {
+            panic!("expected a tag");
+        };
+        let CborValue::Bytes(bytes) = *inner else {
+            panic!("expected a byte string");
+        };
+        assert_eq!(
+            TypedArray::from_tagged(tag, &bytes).unwrap(),
+            Some(original)
+        );
+    }
+
+    fn random_value(rng: &mut Rng, depth: usize) -> CborValue {
+        let choice = if depth > 3 {
+            rng.below(8)
+        } else {
+            rng.below(11)
+        };
+        match choice {
+            0 => CborValue::Unsigned(rng.next() >> rng.below(64)),
+            1 => CborValue::Negative(rng.next() >> rng.below(64)),
+            2 => CborValue::Bytes((0..rng.below(40)).map(|_| rng.next() as u8).collect()),
+            3 => CborValue::Text(
+                (0..rng.below(20))
+                    .map(|_| (b'a' + rng.below(26) as u8) as char)
+                    .collect(),
+            ),
+            4 => CborValue::Bool(rng.below(2) == 1),
+            5 => CborValue::Null,
+            6 => {
+                let f = f64::from_bits(rng.next());
+                CborValue::Float(if f.is_nan() { 0.5 } else { f })
+            }
+            7 => CborValue::Simple(rng.below(20) as u8),
+            8 => CborValue::Array(
+                (0..rng.below(5))
+                    .map(|_| random_value(rng, depth + 1))
+                    .collect(),
+            ),
+            9 => CborValue::Map(
+                (0..rng.below(5))
+                    .map(|_| (random_value(rng, depth + 1), random_value(rng, depth + 1)))
+                    .collect(),
+            ),
+            _ => CborValue::Tag(rng.below(1000), Box::new(random_value(rng, depth + 1))),
+        }
+    }
+
+    #[test]
+    fn roundtrips_random_values() {
+        let mut rng = Rng(0x9e37_79b9_7f4a_7c15);
+        for _ in 0..2000 {
+            let value = random_value(&mut rng, 0);
+            assert_eq!(decode(&encode(&value)).unwrap(), value);
+        }
+    }
+
+    #[test]
+    fn survives_random_and_mutated_input() {
+        let mut rng = Rng(0xdead_beef_cafe_f00d);
+        for _ in 0..5000 {
+            let bytes: Vec<u8> = (0..rng.below(64)).map(|_| rng.next() as u8).collect();
+            let _ = decode(&bytes);
+        }
+        for _ in 0..2000 {
+            let mut bytes = encode(&random_value(&mut rng, 0));
+            if bytes.is_empty() {
+                continue;
+            }
+            for _ in 0..=rng.below(4) {
+                let index = rng.below(bytes.len() as u64) as usize;
+                bytes[index] = rng.next() as u8;
+            }
+            bytes.truncate(rng.below(bytes.len() as u64 + 1) as usize);
+            let _ = decode(&bytes);
+        }
+    }
+}
@@ -0,0 +1,779 @@
+//! ROS 2 CDR (XCDR1) serialization driven by parsed message definitions.
+//!
+//! Messages use [`CborValue`] as their in-memory form, the same tree the CBOR
+//! path hands to JS: structs are text-keyed maps and numeric arrays are
+//! typed-array tags, so large payloads reach JS as typed arrays.
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+use crate::cbor::{self, CborValue, TypedArray, TypedArrayKind};
+use crate::error::BridgeError;
+use crate::msgdef::{self, ArrayKind, Field, FieldType, MessageSet, Primitive};
+use crate::schema;
+
+/// Nesting limit for nested messages.
+const MAX_DEPTH: usize = 64;
+
+const HEADER_LEN: usize = 4;
+const CDR_BE: u16 = 0x0000;
+const CDR_LE: u16 = 0x0001;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CdrErrorKind {
+    UnexpectedEof,
+    UnsupportedEncapsulation(u16),
+    UnknownType(String),
+    InvalidUtf8,
+    MissingField,
+    InvalidValue(&'static str),
+    LengthMismatch { expected: usize, actual: usize },
+    TooManyElements { bound: usize, actual: usize },
+    StringTooLong { bound: usize },
+    DepthExceeded,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CdrError {
+    /// Dotted field path, e.g. `pose.position[2]`; empty for the root.
+    pub path: String,
+    pub kind: CdrErrorKind,
+}
+
+impl CdrError {
+    fn new(kind: CdrErrorKind) -> Self {
+        Self {
+            path: String::new(),
+            kind,
+        }
+    }
+
+    fn in_field(mut self, name: &str) -> Self {
+        self.path = match self.path.as_str() {
+            "" => name.to_owned(),
+            rest if rest.starts_with('[') => format!("{name}{rest}"),
+            rest => format!("{name}.{rest}"),
+        };
+        self
+    }
+
+    fn at_index(mut self, index: usize) -> Self {
+        self.path = match self.path.as_str() {
+            "" => format!("[{index}]"),
+            rest if rest.starts_with('[') => format!("[{index}]{rest}"),
+            rest => format!("[{index}].{rest}"),
+        };
+        self
+    }
+}
+
+impl fmt::Display for CdrErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnexpectedEof => write!(f, "unexpected end of CDR data"),
+            Self::UnsupportedEncapsulation(id) => {
+                write!(f, "unsupported CDR encapsulation 0x{id:04x}")
+            }
+            Self::UnknownType(name) => write!(f, "no definition for message type {name}"),
+            Self::InvalidUtf8 => write!(f, "invalid UTF-8 in string"),
+            Self::MissingField => write!(f, "missing field"),
+            Self::InvalidValue(expected) => write!(f, "expected {expected}"),
+            Self::LengthMismatch { expected, actual } => {
+                write!(f, "expected {expected} elements, found {actual}")
+            }
+            Self::TooManyElements { bound, actual } => {
+                write!(f, "at most {bound} elements allowed, found {actual}")
+            }
+            Self::StringTooLong { bound } => {
+                write!(f, "string is longer than its bound of {bound}")
+            }
+            Self::DepthExceeded => write!(f, "message nesting too deep"),
+        }
+    }
+}
+
+impl fmt::Display for CdrError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.kind)
+        } else {
+            write!(f, "{}: {}", self.path, self.kind)
+        }
+    }
+}
+
+fn typed_kind(primitive: Primitive) -> Option<TypedArrayKind> {
+    Some(match primitive {
+        Primitive::Byte | Primitive::Char | Primitive::Uint8 => TypedArrayKind::Uint8,
+        Primitive::Int8 => TypedArrayKind::Int8,
+        Primitive::Int16 => TypedArrayKind::Int16,
+        Primitive::Uint16 => TypedArrayKind::Uint16,
+        Primitive::Int32 => TypedArrayKind::Int32,
+        Primitive::Uint32 => TypedArrayKind::Uint32,
+        Primitive::Int64 => TypedArrayKind::Int64,
+        Primitive::Uint64 => TypedArrayKind::Uint64,
+        Primitive::Float32 => TypedArrayKind::Float32,
+        Primitive::Float64 => TypedArrayKind::Float64,
+        Primitive::Bool | Primitive::String | Primitive::WString => return None,
+    })
+}
+
+fn lookup<'d>(defs: &'d MessageSet, name: &str) -> Result<&'d msgdef::MessageDef, CdrError> {
+    defs.get(name)
+        .ok_or_else(|| CdrError::new(CdrErrorKind::UnknownType(name.to_owned())))
+}
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+    little: bool,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8], CdrError> {
+        let end = self
+            .pos
+            .checked_add(len)
+            .filter(|end| *end <= self.data.len())
+            .ok_or_else(|| CdrError::new(CdrErrorKind::UnexpectedEof))?;
+        let slice = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(slice)
+    }
+
+    fn align(&mut self, size: usize) -> Result<(), CdrError> {
+        let padding = (size - self.pos % size) % size;
+        self.take(padding).map(|_| ())
+    }
+
+    fn read<const N: usize>(&mut self) -> Result<[u8; N], CdrError> {
+        self.align(N)?;
+        let mut raw = [0u8; N];
+        raw.copy_from_slice(self.take(N)?);
+        if !self.little {
+            raw.reverse();
+        }
+        Ok(raw)
+    }
+
+    fn u32(&mut self) -> Result<u32, CdrError> {
+        self.read::<4>().map(u32::from_le_bytes)
+    }
+
+    fn length(&mut self) -> Result<usize, CdrError> {
+        let len = self.u32()? as usize;
+        // Every element takes at least one byte, so this bounds allocation.
+        if len > self.data.len() - self.pos {
+            return Err(CdrError::new(CdrErrorKind::UnexpectedEof));
+        }
+        Ok(len)
+    }
+
+    fn string(&mut self) -> Result<String, CdrError> {
+        let len = self.length()?;
+        let bytes = self.take(len)?;
+        let bytes = bytes.strip_suffix(&[0]).unwrap_or(bytes);
+        String::from_utf8(bytes.to_vec()).map_err(|_| CdrError::new(CdrErrorKind::InvalidUtf8))
+    }
+
+    /// Wide strings carry a code-unit count followed by one 4-byte wchar per
+    /// UTF-16 code unit, without a terminator.
+    fn wstring(&mut self) -> Result<String, CdrError> {
+        let len = self.length()?;
+        let units = (0..len)
+            .map(|_| self.u32().map(|unit| unit as u16))
+            .collect::<Result<Vec<_>, _>>()?;
+        String::from_utf16(&units).map_err(|_| CdrError::new(CdrErrorKind::InvalidUtf8))
+    }
+
+    fn primitive(&mut self, primitive: Primitive) -> Result<CborValue, CdrError> {
+        Ok(match primitive {
+            Primitive::Bool => CborValue::Bool(self.take(1)?[0] != 0),
+            Primitive::Byte | Primitive::Char | Primitive::Uint8 => {
+                CborValue::Unsigned(u64::from(self.take(1)?[0]))
+            }
+            Primitive::Int8 => CborValue::from_i64(i64::from(self.take(1)?[0] as i8)),
+            Primitive::Int16 => CborValue::from_i64(i64::from(i16::from_le_bytes(self.read()?))),
+            Primitive::Uint16 => CborValue::Unsigned(u64::from(u16::from_le_bytes(self.read()?))),
+            Primitive::Int32 => CborValue::from_i64(i64::from(i32::from_le_bytes(self.read()?))),
+            Primitive::Uint32 => CborValue::Unsigned(u64::from(self.u32()?)),
+            Primitive::Int64 => CborValue::from_i64(i64::from_le_bytes(self.read()?)),
+            Primitive::Uint64 => CborValue::Unsigned(u64::from_le_bytes(self.read()?)),
+            Primitive::Float32 => CborValue::Float(f64::from(f32::from_le_bytes(self.read()?))),
+            Primitive::Float64 => CborValue::Float(f64::from_le_bytes(self.read()?)),
+            Primitive::String => CborValue::Text(self.string()?),
+            Primitive::WString => CborValue::Text(self.wstring()?),
+        })
+    }
+
+    fn single(
+        &mut self,
+        ty: &FieldType,
+        defs: &MessageSet,
+        depth: usize,
+    ) -> Result<CborValue, CdrError> {
+        match ty {
+            FieldType::Primitive(primitive) => self.primitive(*primitive),
+            FieldType::Message(name) => self.message(lookup(defs, name)?, defs, depth + 1),
+        }
+    }
+
+    fn field(
+        &mut self,
+        field: &Field,
+        defs: &MessageSet,
+        depth: usize,
+    ) -> Result<CborValue, CdrError> {
+        let count = match field.array {
+            ArrayKind::Single => return self.single(&field.ty, defs, depth),
+            ArrayKind::Fixed(len) => len,
+            ArrayKind::Sequence | ArrayKind::Bounded(_) => self.length()?,
+        };
+        if let FieldType::Primitive(primitive) = &field.ty {
+            if let Some(kind) = typed_kind(*primitive) {
+                if count > 0 {
+                    self.align(kind.element_size())?;
+                }
+                let len = count
+                    .checked_mul(kind.element_size())
+                    .ok_or_else(|| CdrError::new(CdrErrorKind::UnexpectedEof))?;
+                let bytes = self.take(len)?;
+                return Ok(TypedArray::from_bytes(kind, bytes, self.little).to_cbor());
+            }
+        }
+        if count > self.data.len() - self.pos {
+            return Err(CdrError::new(CdrErrorKind::UnexpectedEof));
+        }
+        (0..count)
+            .map(|index| {
+                self.single(&field.ty, defs, depth)
+                    .map_err(|e| e.at_index(index))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(CborValue::Array)
+    }
+
+    fn message(
+        &mut self,
+        def: &msgdef::MessageDef,
+        defs: &MessageSet,
+        depth: usize,
+    ) -> Result<CborValue, CdrError> {
+        if depth > MAX_DEPTH {
+            return Err(CdrError::new(CdrErrorKind::DepthExceeded));
+        }
+        if def.fields.is_empty() {
+            self.take(1)?;
+            return Ok(CborValue::Map(Vec::new()));
+        }
+        def.fields
+            .iter()
+            .map(|field| {
+                let value = self
+                    .field(field, defs, depth)
+                    .map_err(|e| e.in_field(&field.name))?;
+                Ok((CborValue::Text(field.name.clone()), value))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(CborValue::Map)
+    }
+}
+
+/// Decodes an encapsulated CDR payload into a message of type `root`.
+pub fn deserialize(bytes: &[u8], root: &str, defs: &MessageSet) -> Result<CborValue, CdrError> {
+    let name = msgdef::canonical_name(root)
+        .ok_or_else(|| CdrError::new(CdrErrorKind::UnknownType(root.to_owned())))?;
+    let def = lookup(defs, &name)?;
+    let header = bytes
+        .get(..HEADER_LEN)
+        .ok_or_else(|| CdrError::new(CdrErrorKind::UnexpectedEof))?;
+    let little = match u16::from_be_bytes([header[0], header[1]]) {
+        CDR_BE => false,
+        CDR_LE => true,
+        other => return Err(CdrError::new(CdrErrorKind::UnsupportedEncapsulation(other))),
+    };
+    // Alignment is relative to the end of the encapsulation header.
+    let mut reader = Reader {
+        data: &bytes[HEADER_LEN..],
+        pos: 0,
+        little,
+    };
+    reader.message(def, defs, 0)
+}
+
+struct Writer {
+    out: Vec<u8>,
+    little: bool,
+}
+
+impl Writer {
+    fn align(&mut self, size: usize) {
+        let pos = self.out.len() - HEADER_LEN;
+        let padding = (size - pos % size) % size;
+        self.out.resize(self.out.len() + padding, 0);
+    }
+
+    fn write<const N: usize>(&mut self, le_bytes: [u8; N]) {
+        self.align(N);
+        if self.little {
+            self.out.extend_from_slice(&le_bytes);
+        } else {
+            self.out.extend(le_bytes.iter().rev());
+        }
+    }
+
+    fn length(&mut self, len: usize) -> Result<(), CdrError> {
+        let len = u32::try_from(len)
+            .map_err(|_| CdrError::new(CdrErrorKind::InvalidValue("a length below 2^32")))?;
+        self.write(len.to_le_bytes());
+        Ok(())
+    }
+
+    fn primitive(&mut self, primitive: Primitive, value: &CborValue) -> Result<(), CdrError> {
+        macro_rules! int {
+            ($ty:ty, $expected:literal) => {{
+                let n = value
+                    .as_integer()
+                    .and_then(|n| <$ty>::try_from(n).ok())
+                    .ok_or_else(|| CdrError::new(CdrErrorKind::InvalidValue($expected)))?;
+                self.write(n.to_le_bytes());
+            }};
+        }
+        match primitive {
+            Primitive::Bool => match value {
+                CborValue::Bool(flag) => self.out.push(u8::from(*flag)),
+                _ => return Err(CdrError::new(CdrErrorKind::InvalidValue("a boolean"))),
+            },
+            Primitive::Byte | Primitive::Char | Primitive::Uint8 => int!(u8, "a uint8"),
+            Primitive::Int8 => int!(i8, "an int8"),
+            Primitive::Int16 => int!(i16, "an int16"),
+            Primitive::Uint16 => int!(u16, "a uint16"),
+            Primitive::Int32 => int!(i32, "an int32"),
+            Primitive::Uint32 => int!(u32, "a uint32"),
+            Primitive::Int64 => int!(i64, "an int64"),
+            Primitive::Uint64 => int!(u64, "a uint64"),
+            Primitive::Float32 => {
+                let f = value
+                    .as_f64()
+                    .ok_or_else(|| CdrError::new(CdrErrorKind::InvalidValue("a number")))?;
+                self.write((f as f32).to_le_bytes());
+            }
+            Primitive::Float64 => {
+                let f = value
+                    .as_f64()
+                    .ok_or_else(|| CdrError::new(CdrErrorKind::InvalidValue("a number")))?;
+                self.write(f.to_le_bytes());
+            }
+            Primitive::String => {
+                let CborValue::Text(text) = value else {
+                    return Err(CdrError::new(CdrErrorKind::InvalidValue("a string")));
+                };
+                self.length(text.len() + 1)?;
+                self.out.extend_from_slice(text.as_bytes());
+                self.out.push(0);
+            }
+            Primitive::WString => {
+                let CborValue::Text(text) = value else {
+                    return Err(CdrError::new(CdrErrorKind::InvalidValue("a string")));
+                };
+                let units: Vec<u16> = text.encode_utf16().collect();
+                self.length(units.len())?;
+                for unit in units {
+                    self.write(u32::from(unit).to_le_bytes());
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn single(
+        &mut self,
+        ty: &FieldType,
+        value: &CborValue,
+        defs: &MessageSet,
+        depth: usize,
+    ) -> Result<(), CdrError> {
+        match ty {
+            FieldType::Primitive(primitive) => self.primitive(*primitive, value),
+            FieldType::Message(name) => self.message(lookup(defs, name)?, value, defs, depth + 1),
+        }
+    }
+
+    fn field(
+        &mut self,
+        field: &Field,
+        value: &CborValue,
+        defs: &MessageSet,
+        depth: usize,
+    ) -> Result<(), CdrError> {
+        if field.array == ArrayKind::Single {
+            check_string_bound(field, value)?;
+            return self.single(&field.ty, value, defs, depth);
+        }
+        let items = value
+            .array_items()
+            .ok_or_else(|| CdrError::new(CdrErrorKind::InvalidValue("an array")))?;
+        match field.array {
+            ArrayKind::Fixed(expected) if expected != items.len() => {
+                return Err(CdrError::new(CdrErrorKind::LengthMismatch {
+                    expected,
+                    actual: items.len(),
+                }));
+            }
+            ArrayKind::Bounded(bound) if items.len() > bound => {
+                return Err(CdrError::new(CdrErrorKind::TooManyElements {
+                    bound,
+                    actual: items.len(),
+                }));
+            }
+            ArrayKind::Sequence | ArrayKind::Bounded(_) => self.length(items.len())?,
+            _ => {}
+        }
+        for (index, item) in items.iter().enumerate() {
+            check_string_bound(field, item)
+                .and_then(|()| self.single(&field.ty, item, defs, depth))
+                .map_err(|e| e.at_index(index))?;
+        }
+        Ok(())
+    }
+
+    fn message(
+        &mut self,
+        def: &msgdef::MessageDef,
+        value: &CborValue,
+        defs: &MessageSet,
+        depth: usize,
+    ) -> Result<(), CdrError> {
+        if depth > MAX_DEPTH {
+            return Err(CdrError::new(CdrErrorKind::DepthExceeded));
+        }
+        let CborValue::Map(entries) = value else {
+            return Err(CdrError::new(CdrErrorKind::InvalidValue("an object")));
+        };
+        if def.fields.is_empty() {
+            // rmw gives field-less structs a single uint8 member.
+            self.out.push(0);
+            return Ok(());
+        }
+        for field in &def.fields {
+            let value = entries
+                .iter()
+                .find(|(key, _)| matches!(key, CborValue::Text(k) if *k == field.name))
+                .map(|(_, value)| value)
+                .ok_or_else(|| CdrError::new(CdrErrorKind::MissingField).in_field(&field.name))?;
+            self.field(field, value, defs, depth)
+                .map_err(|e| e.in_field(&field.name))?;
+        }
+        Ok(())
+    }
+}
+
+/// Rejects a `string<=N` value longer than its bound; see
+/// [`Primitive::bounded_len`].
+fn check_string_bound(field: &Field, value: &CborValue) -> Result<(), CdrError> {
+    let (Some(bound), CborValue::Text(text), FieldType::Primitive(primitive)) =
+        (field.string_bound, value, &field.ty)
+    else {
+        return Ok(());
+    };
+    if primitive.bounded_len(text) > bound {
+        return Err(CdrError::new(CdrErrorKind::StringTooLong { bound }));
+    }
+    Ok(())
+}
+
+/// Encodes `value` as a message of type `root`, including the encapsulation
+/// header.
+pub fn serialize(
+    value: &CborValue,
+    root: &str,
+    defs: &MessageSet,
+    little: bool,
+) -> Result<Vec<u8>, CdrError> {
+    let name = msgdef::canonical_name(root)
+        .ok_or_else(|| CdrError::new(CdrErrorKind::UnknownType(root.to_owned())))?;
+    let def = lookup(defs, &name)?;
+    let id = if little { CDR_LE } else { CDR_BE };
+    let mut header = id.to_be_bytes().to_vec();
+    header.extend_from_slice(&[0, 0]);
+    let mut writer = Writer {
+        out: header,
+        little,
+    };
+    writer.message(def, value, defs, 0)?;
+    Ok(writer.out)
+}
+
+/// Runs `f` against `definition` when given, otherwise against the schema
+/// registry.
+fn with_definitions<R>(
+    msg_type: &str,
+    definition: Option<String>,
+    f: impl FnOnce(&MessageSet) -> Result<R, JsValue>,
+) -> Result<R, JsValue> {
+    match definition {
+        Some(text) => {
+            let defs = msgdef::parse_full_text(msg_type, &text).map_err(|e| {
+                BridgeError::invalid_input(format!("invalid message definition: {e}"))
+            })?;
+            f(&defs)
+        }
+        None => schema::with_registry(f),
+    }
+}
+
+/// Decodes CDR `bytes` of `msg_type`, using its full-text `definition` or
+/// the registered schema.
+#[wasm_bindgen]
+pub fn decode_cdr(
+    bytes: &[u8],
+    msg_type: String,
+    definition: Option<String>,
+) -> Result<JsValue, JsValue> {
+    let value = with_definitions(&msg_type, definition, |defs| {
+        deserialize(bytes, &msg_type, defs)
+            .map_err(|e| BridgeError::protocol_violation(format!("cannot decode CDR: {e}")).into())
+    })?;
+    cbor::value_to_js(&value)
+}
+
+/// Encodes `msg` as CDR, little endian unless `big_endian` is set.
+#[wasm_bindgen]
+pub fn encode_cdr(
+    msg: JsValue,
+    msg_type: String,
+    definition: Option<String>,
+    big_endian: Option<bool>,
+) -> Result<Vec<u8>, JsValue> {
+    let value = cbor::js_to_value(&msg, 0)?;
+    with_definitions(&msg_type, definition, |defs| {
+        serialize(&value, &msg_type, defs, !big_endian.unwrap_or(false))
+            .map_err(|e| BridgeError::encode_failed(format!("cannot encode CDR: {e}")).into())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TWIST_LIKE: &str = "\
+std_msgs/Header header
+int8 mode
+float64[3] linear
+string label
+bool[] flags
+Vector3[] points
+================================================================================
+MSG: std_msgs/Header
+builtin_interfaces/Time stamp
+string frame_id
+================================================================================
+MSG: builtin_interfaces/Time
+int32 sec
+uint32 nanosec
+================================================================================
+MSG: demo/Vector3
+float32 x
+float32 y
+";
+
+    fn text(s: &str) -> CborValue {
+        CborValue::Text(s.to_owned())
+    }
+
+    fn map(entries: Vec<(&str, CborValue)>) -> CborValue {
+        CborValue::Map(entries.into_iter().map(|(k, v)| (text(k), v)).collect())
+    }
+
+    fn sample() -> CborValue {
+        map(vec![
+            (
+                "header",
+                map(vec![
+                    (
+                        "stamp",
+                        map(vec![
+                            ("sec", CborValue::Unsigned(7)),
+                            ("nanosec", CborValue::Unsigned(500)),
+                        ]),
+                    ),
+                    ("frame_id", text("map")),
+                ]),
+            ),
+            ("mode", CborValue::Negative(0)),
+            (
+                "linear",
+                TypedArray::Float64(vec![1.0, -2.5, 3.0]).to_cbor(),
+            ),
+            ("label", text("hi")),
+            (
+                "flags",
+                CborValue::Array(vec![CborValue::Bool(true), CborValue::Bool(false)]),
+            ),
+            (
+                "points",
+                CborValue::Array(vec![map(vec![
+                    ("x", CborValue::Float(0.5)),
+                    ("y", CborValue::Float(-1.0)),
+                ])]),
+            ),
+        ])
+    }
+
+    fn expected_le() -> Vec<u8> {
+        let mut out = vec![0x00, 0x01, 0x00, 0x00];
+        out.extend(7i32.to_le_bytes());
+        out.extend(500u32.to_le_bytes());
+        out.extend(4u32.to_le_bytes());
+        out.extend(b"map\0");
+        out.push(0xff); // mode = -1 at offset 16
+        out.extend([0; 7]); // align float64 to 24
+        for f in [1.0f64, -2.5, 3.0] {
+            out.extend(f.to_le_bytes());
+        }
+        out.extend(3u32.to_le_bytes());
+        out.extend(b"hi\0");
+        out.push(0); // align to 4 for the sequence length
+        out.extend(2u32.to_le_bytes());
+        out.extend([1, 0]);
+        out.extend([0, 0]);
+        out.extend(1u32.to_le_bytes());
+        out.extend(0.5f32.to_le_bytes());
+        out.extend((-1.0f32).to_le_bytes());
+        out
+    }
+
+    #[test]
+    fn serializes_with_xcdr1_alignment() {
+        let defs = msgdef::parse_full_text("demo/Sample", TWIST_LIKE).unwrap();
+        let bytes = serialize(&sample(), "demo/msg/Sample", &defs, true).unwrap();
+        assert_eq!(bytes, expected_le());
+        assert_eq!(deserialize(&bytes, "demo/Sample", &defs).unwrap(), sample());
+    }
+
+    #[test]
+    fn roundtrips_big_endian() {
+        let defs = msgdef::parse_full_text("demo/Sample", TWIST_LIKE).unwrap();
+        let bytes = serialize(&sample(), "demo/Sample", &defs, false).unwrap();
+        assert_eq!(&bytes[..4], &[0, 0, 0, 0]);
+        assert_eq!(&bytes[4..8], &7i32.to_be_bytes());
+        assert_eq!(deserialize(&bytes, "demo/Sample", &defs).unwrap(), sample());
+    }
+
+    #[test]
+    fn decodes_uint8_arrays_as_bytes_and_wide_strings() {
+        let defs = msgdef::parse_full_text("demo/Blob", "uint8[] data\nwstring name\n").unwrap();
+        let mut bytes = vec![0x00, 0x01, 0x00, 0x00];
+        bytes.extend(3u32.to_le_bytes());
+        bytes.extend([9, 8, 7, 0]);
+        bytes.extend(2u32.to_le_bytes());
+        bytes.extend(u32::from('é').to_le_bytes());
+        bytes.extend(u32::from('x').to_le_bytes());
+        assert_eq!(
+            deserialize(&bytes, "demo/Blob", &defs).unwrap(),
+            map(vec![
+                ("data", CborValue::Bytes(vec![9, 8, 7])),
+                ("name", text("éx")),
+            ])
+        );
+        let value = map(vec![
+            ("data", CborValue::Array(vec![CborValue::Unsigned(9)])),
+            ("name", text("éx")),
+        ]);
+        let encoded = serialize(&value, "demo/Blob", &defs, true).unwrap();
+        assert_eq!(&encoded[4..9], &[1, 0, 0, 0, 9]);
+    }
+
+    #[test]
+    fn reports_error_paths() {
+        let defs = msgdef::parse_full_text("demo/Sample", TWIST_LIKE).unwrap();
+        let with_field = |index: usize, value: CborValue| {
+            let mut message = sample();
+            if let CborValue::Map(entries) = &mut message {
+                entries[index].1 = value;
+            }
+            serialize(&message, "demo/Sample", &defs, true)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            with_field(
+                5,
+                CborValue::Array(vec![map(vec![("x", CborValue::Float(1.0))])])
+            ),
+            "points[0].y: missing field"
+        );
+        assert_eq!(
+            with_field(1, CborValue::Unsigned(300)),
+            "mode: expected an int8"
+        );
+        assert_eq!(
+            with_field(2, CborValue::Array(vec![])),
+            "linear: expected 3 elements, found 0"
+        );
+
+        let bytes = expected_le();
+        let err = deserialize(&bytes[..30], "demo/Sample", &defs).unwrap_err();
+        assert_eq!(err.to_string(), "linear: unexpected end of CDR data");
+        let err = deserialize(&[0, 7, 0, 0], "demo/Sample", &defs).unwrap_err();
+        assert_eq!(err.kind, CdrErrorKind::UnsupportedEncapsulation(7));
+        let err = deserialize(&bytes, "demo/Other", &defs).unwrap_err();
+        assert_eq!(err.kind, CdrErrorKind::UnknownType("demo/msg/Other".into()));
+    }
+
+    #[test]
+    fn rejects_oversized_sequence_lengths() {
+        let defs = msgdef::parse_full_text("demo/Seq", "string[] names\n").unwrap();
+        let mut bytes = vec![0x00, 0x01, 0x00, 0x00];
+        bytes.extend(u32::MAX.to_le_bytes());
+        let err = deserialize(&bytes, "demo/Seq", &defs).unwrap_err();
+        assert_eq!(err.kind, CdrErrorKind::UnexpectedEof);
+    }
+
+    #[test]
+    fn enforces_bounds_on_serialize() {
+        let defs = msgdef::parse_full_text(
+            "demo/Bounded",
+            "int32[<=2] ids\nstring<=3 name\nstring<=2[] tags\n",
+        )
+        .unwrap();
+        let message = |ids: usize, name: &str, tag: &str| {
+            map(vec![
+                ("ids", CborValue::Array(vec![CborValue::Unsigned(1); ids])),
+                ("name", text(name)),
+                ("tags", CborValue::Array(vec![text("ok"), text(tag)])),
+            ])
+        };
+        assert!(serialize(&message(2, "abc", "ab"), "demo/Bounded", &defs, true).is_ok());
+        let err = |ids, name, tag| {
+            serialize(&message(ids, name, tag), "demo/Bounded", &defs, true)
+                .unwrap_err()
+                .to_string()
+        };
+        assert_eq!(
+            err(3, "abc", "ab"),
+            "ids: at most 2 elements allowed, found 3"
+        );
+        assert_eq!(
+            err(2, "abcd", "ab"),
+            "name: string is longer than its bound of 3"
+        );
+        assert_eq!(
+            err(2, "abc", "abc"),
+            "tags[1]: string is longer than its bound of 2"
+        );
+    }
+
+    #[test]
+    fn pads_empty_messages_with_one_byte() {
+        let defs = msgdef::parse_full_text("demo/Wrapper", "Empty inner\nuint8 after\n================================================================================\nMSG: demo/Empty\n").unwrap();
+        let value = map(vec![
+            ("inner", map(vec![])),
+            ("after", CborValue::Unsigned(5)),
+        ]);
+        let bytes = serialize(&value, "demo/Wrapper", &defs, true).unwrap();
+        assert_eq!(bytes, [0x00, 0x01, 0x00, 0x00, 0, 5]);
+        assert_eq!(deserialize(&bytes, "demo/Wrapper", &defs).unwrap(), value);
+    }
+}
@@ -1,64 +1,145 @@
 import { resolveCodec } from "./codec.js";
-import { fallbackProtocol } from "./protocol-fallback.js";
+import { BridgeError } from "./errors.js";
+import type { BridgeErrorDetails } from "./errors.js";
 import type {
+  ActionGoalHandler,
   ActionHandle,
+  ActionServerGoal,
+  AdvertiseOptions,
   BridgeClientOptions,
   BridgeCodec,
+  BridgeConnectionEvent,
+  BridgeConnectionState,
+  BridgeConnectionStateMachine,
+  BridgeFragmentAssembler,
+  BridgeIncomingEvent,
   BridgeIncomingMessage,
+  BridgeAdvertisement,
+  BridgeOutboundQueue,
+  BridgeMessageSchema,
+  BridgeProtocolError,
+  BridgeProtocolErrorCode,
   BridgeReconnectContext,
   BridgeReconnectReason,
+  BridgePublishPlan,
+  BridgePublisherRegistry,
+  BridgeQueueAdmission,
+  BridgeReceiveTime,
   BridgeReconnectOptions,
+  BridgeReconnectPolicy,
+  BridgeRequestKind,
+  BridgeRequestRetry,
+  BridgeRequestTracker,
+  BridgeStatus,
+  BridgeStatusLevel,
+  BridgeSubscriptionRegistry,
+  BridgeTrackedRequest,
   CallServiceOptions,
   CancelActionGoalOptions,
+  EncodeCdrOptions,
   ExecuteCliOptions,
   JsonObject,
+  MessageOf,
+  PublishOptions,
+  Publisher,
   SendActionGoalOptions,
+  ServiceHandler,
+  ServiceRequestOf,
+  ServiceResponseOf,
   SubscribeOptions,
+  SubscriptionHandle,
   WasmProtocol,
   WebSocketLike
 } from "./types.js";
 
-type PendingCall = {
-  service: string;
+/** Callbacks of a request in the `RequestTracker`, keyed by its id. */
+type PendingRequest = {
+  /**
+   * Sends the request again with its id, after a reconnect; see `sendEnvelope`
+   * for `bypassQueue` and the result.
+   */
+  send: (bypassQueue: boolean) => Promise<boolean>;
   resolve: (value: JsonObject) => void;
   reject: (error: Error) => void;
-  timeout?: NodeJS.Timeout;
-};
-
-type PendingAction = {
-  id: string;
-  sessionId?: string;
-  action: string;
-  actionType: string;
-  resolve: (value: JsonObject) => void;
-  reject: (error: Error) => void;
-  timeout?: NodeJS.Timeout;
   onRequest?: (msg: JsonObject) => void;
   onFeedback?: (msg: JsonObject) => void;
   onResult?: (msg: JsonObject) => void;
 };
 
-type PendingActionCancel = {
-  key: string;
-  resolve: (value: JsonObject) => void;
-  reject: (error: Error) => void;
-  timeout?: NodeJS.Timeout;
+/** A frame held by the `OutboundQueue`, keyed by its queue id. */
+type QueuedFrame = {
+  op: string;
+  encoded: string | Uint8Array;
+  fragmentSize?: number;
 };
 
-type PendingCliCall = {
+type SubscriptionInfo = {
   id: string;
-  resolve: (value: JsonObject) => void;
-  reject: (error: Error) => void;
-  timeout?: NodeJS.Timeout;
+  type: string;
+  compression?: string;
+  throttleRate?: number;
+  queueLength?: number;
+  fragmentSize?: number;
+  messageDefinition?: string;
+  /** Keyed by handle id. */
+  callbacks: Map<string, (msg: JsonObject, receivedAt?: BridgeReceiveTime) => void>;
 };
 
-type SubscriptionInfo = {
+/**
+ * Settings sent in the `subscribe` op, shared by every handle on a topic. A
+ * change to any of them resubscribes the topic under a fresh id.
+ */
+const SUBSCRIBE_FIELDS = ["type", "compression", "throttleRate", "queueLength", "fragmentSize"] as const;
+
+function buildSubscribe(protocol: WasmProtocol, topic: string, info: SubscriptionInfo): JsonObject {
+  return protocol.build_subscribe(
+    topic,
+    info.type,
+    info.compression,
+    info.fragmentSize,
+    info.throttleRate,
+    info.queueLength,
+    info.id
+  );
+}
+
+function buildAdvertise(protocol: WasmProtocol, advertisement: BridgeAdvertisement): JsonObject {
+  return protocol.build_advertise(
+    advertisement.topic,
+    advertisement.type,
+    advertisement.latch,
+    advertisement.queue_size
+  );
+}
+
+type AdvertisedService = {
   type: string;
-  compression?: string;
-  callbacks: Set<(msg: JsonObject) => void>;
+  handler: (request: JsonObject) => unknown;
+};
+
+type AdvertisedAction = {
+  type: string;
+  handler: (goal: ActionServerGoal) => unknown;
+};
+
+type HostedGoal = {
+  action: string;
+  controller: AbortController;
 };
 
+type ActionGoalRequestEvent = Extract<BridgeIncomingEvent, { kind: "action_goal_request" }>;
+type FragmentEvent = Extract<BridgeIncomingEvent, { kind: "fragment" }>;
+type ServiceRequestEvent = Extract<BridgeIncomingEvent, { kind: "service_request" }>;
+type StatusEvent = Extract<BridgeIncomingEvent, { kind: "status" }>;
+type ErrorEvent = Extract<BridgeIncomingEvent, { kind: "error" }>;
+
 const OPEN = 1;
+const DEFAULT_FRAGMENT_TIMEOUT_MS = 10_000;
+const DEFAULT_MAX_FRAGMENT_BYTES = 64 * 1024 * 1024;
+/** `action_msgs/GoalStatus` codes a hosted goal can finish with. */
+const GOAL_SUCCEEDED = 4;
+const GOAL_CANCELED = 5;
+const GOAL_ABORTED = 6;
 function randomId(prefix: string): string {
   if (globalThis.crypto?.randomUUID) {
     return `${prefix}-${globalThis.crypto.randomUUID()}`;
@@ -66,6 +147,49 @@ function randomId(prefix: string): string {
   return `${prefix}-${Date.now()}-${Math.floor(Math.random() * 10000)}`;
 }
 
+/** Short random tag that keeps this client's request ids apart from other clients'. */
+function idNamespace(): string {
+  if (globalThis.crypto?.randomUUID) {
+    return globalThis.crypto.randomUUID().slice(0, 8);
+  }
+  return Math.floor(Math.random() * 0xffffffff).toString(16);
+}
+
+/** Cancels carry no id on the wire, so their tracker id is left out of errors. */
+function requestDetails(request: BridgeTrackedRequest): BridgeErrorDetails {
+  return {
+    op: request.kind,
+    requestId: request.kind === "cancel_action_goal" ? undefined : request.id,
+    target: request.target
+  };
+}
+
+function requestTimeoutMessage(request: BridgeTrackedRequest): string {
+  switch (request.kind) {
+    case "call_service":
+      return `Service call timeout for ${request.target} (${request.id})`;
+    case "send_action_goal":
+      return `Action goal timeout for ${request.target} (${request.id})`;
+    case "cancel_action_goal":
+      return `Action cancel timeout for ${request.target} (${request.target}::${request.session_id ?? "default"})`;
+    case "execute_cli":
+      return `CLI call timeout (${request.id})`;
+  }
+}
+
+function requestDisconnectMessage(request: BridgeTrackedRequest): string {
+  switch (request.kind) {
+    case "call_service":
+      return `Service call ${request.id} interrupted by disconnect; retry after reconnect`;
+    case "send_action_goal":
+      return `Action ${request.id} interrupted by disconnect; resend after reconnect`;
+    case "cancel_action_goal":
+      return "Action cancel interrupted by disconnect; retry after reconnect";
+    case "execute_cli":
+      return "CLI request interrupted by disconnect; retry after reconnect";
+  }
+}
+
 function asRecord(value: unknown): JsonObject {
   if (!value || typeof value !== "object" || Array.isArray(value)) {
     return {};
@@ -73,6 +197,30 @@ function asRecord(value: unknown): JsonObject {
   return value as JsonObject;
 }
 
+/** Name of a service or action component message, e.g. `pkg/srv/Name_Request`. */
+function interfaceMessageType(type: string, kind: "srv" | "action", suffix: string): string {
+  const parts = type.split("/");
+  if (parts.length === 2) {
+    return `${parts[0]}/${kind}/${parts[1]}${suffix}`;
+  }
+  return `${type}${suffix}`;
+}
+
+/** Topic, service or action a frame is about, for per-target queue policies. */
+function frameTarget(message: JsonObject): string | undefined {
+  for (const key of ["topic", "service", "action"]) {
+    const value = message[key];
+    if (typeof value === "string") {
+      return value;
+    }
+  }
+  return undefined;
+}
+
+function encodedBytes(encoded: string | Uint8Array): number {
+  return typeof encoded === "string" ? new TextEncoder().encode(encoded).length : encoded.byteLength;
+}
+
 function hasValidOpEnvelope(value: unknown): value is JsonObject & { op: string } {
   if (!value || typeof value !== "object" || Array.isArray(value)) {
     return false;
@@ -82,17 +230,32 @@ function hasValidOpEnvelope(value: unknown): value is JsonObject & { op: string
 }
 
 export class BridgeClientCore {
-  protected readonly options: Pick<BridgeClientOptions, "timeoutMs"> & Required<Pick<BridgeClientOptions, "strictWasm">> & {
-    reconnect: BridgeReconnectOptions;
-    webSocketFactory?: (url: string) => WebSocketLike;
-    onSocketOpen?: (url: string) => void;
-    onSocketClose?: () => void;
-    onSocketError?: (error: Error) => void;
-    onReconnectScheduled?: BridgeClientOptions["onReconnectScheduled"];
-  };
+  protected readonly options: Pick<BridgeClientOptions, "timeoutMs" | "fragmentSize"> &
+    Required<
+      Pick<
+        BridgeClientOptions,
+        | "validateMessages"
+        | "fillDefaults"
+        | "autoAdvertise"
+        | "resumeActionGoals"
+        | "fragmentTimeoutMs"
+        | "maxFragmentBytes"
+      >
+    > & {
+      reconnect: BridgeReconnectOptions;
+      webSocketFactory?: (url: string) => WebSocketLike;
+      onSocketOpen?: (url: string) => void;
+      onSocketClose?: () => void;
+      onSocketError?: (error: Error) => void;
+      onReconnectScheduled?: BridgeClientOptions["onReconnectScheduled"];
+      onStateChange?: BridgeClientOptions["onStateChange"];
+      onProtocolError?: BridgeClientOptions["onProtocolError"];
+      onStatus?: BridgeClientOptions["onStatus"];
+      outboundQueue?: BridgeClientOptions["outboundQueue"];
+    };
 
   private readonly protocolPromise: Promise<WasmProtocol>;
-  private readonly codec: BridgeCodec;
+  private readonly codecPromise: Promise<BridgeCodec>;
 
   private ws: WebSocketLike | undefined;
   private socketGeneration = 0;
@@ -100,48 +263,81 @@ export class BridgeClientCore {
   private wsUrl: string | undefined;
   private manualClose = false;
   private connectInFlight: Promise<void> | undefined;
-  private reconnectAttempt = 0;
+  private reconnectPolicy: BridgeReconnectPolicy | undefined;
+  private connection: BridgeConnectionStateMachine | undefined;
   private reconnectTimer: NodeJS.Timeout | undefined;
-  private pendingCalls = new Map<string, PendingCall>();
-  private pendingActions = new Map<string, PendingAction>();
-  private actionIdBySession = new Map<string, string>();
-  private pendingActionCancels = new Map<string, PendingActionCancel>();
-  private pendingCliCalls = new Map<string, PendingCliCall>();
+  private requests = new Map<string, PendingRequest>();
+  private requestTracker: BridgeRequestTracker | undefined;
+  /** Fires at the earliest request deadline. */
+  private requestDeadline: NodeJS.Timeout | undefined;
+  private fragmentExpiry: NodeJS.Timeout | undefined;
+  /** Resends waiting out their retry backoff. */
+  private resendTimers = new Set<NodeJS.Timeout>();
   private subscriptions = new Map<string, SubscriptionInfo>();
-  private advertisedTopics = new Map<string, string>();
+  private advertisedServices = new Map<string, AdvertisedService>();
+  private advertisedActions = new Map<string, AdvertisedAction>();
+  private hostedGoals = new Map<string, HostedGoal>();
+  /** Subscribes waiting out `confirmMs`, keyed by subscription id. */
+  private confirmingSubscribes = new Map<string, Set<(error: Error) => void>>();
+  private statusLevel: BridgeStatusLevel | undefined;
+  private fragmentAssembler: BridgeFragmentAssembler | undefined;
+  private subscriptionHandles: BridgeSubscriptionRegistry | undefined;
+  private publishers: BridgePublisherRegistry | undefined;
+  private outbound: BridgeOutboundQueue | undefined;
+  private queuedFrames = new Map<number, QueuedFrame>();
 
   constructor(protocolLoader: () => Promise<WasmProtocol>, options: BridgeClientOptions = {}) {
     this.options = {
       timeoutMs: options.timeoutMs,
-      strictWasm: options.strictWasm ?? false,
+      validateMessages: options.validateMessages ?? false,
+      fillDefaults: options.fillDefaults ?? false,
+      autoAdvertise: options.autoAdvertise ?? false,
+      resumeActionGoals: options.resumeActionGoals ?? false,
+      fragmentSize: options.fragmentSize,
+      fragmentTimeoutMs: options.fragmentTimeoutMs ?? DEFAULT_FRAGMENT_TIMEOUT_MS,
+      maxFragmentBytes: options.maxFragmentBytes ?? DEFAULT_MAX_FRAGMENT_BYTES,
       webSocketFactory: options.webSocketFactory,
       onSocketOpen: options.onSocketOpen,
       onSocketClose: options.onSocketClose,
       onSocketError: options.onSocketError,
       onReconnectScheduled: options.onReconnectScheduled,
+      onStateChange: options.onStateChange,
+      onProtocolError: options.onProtocolError,
+      onStatus: options.onStatus,
+      outboundQueue: options.outboundQueue,
       reconnect: {
         enabled: options.reconnect?.enabled ?? true,
+        strategy: options.reconnect?.strategy ?? "exponential",
         initialDelayMs: options.reconnect?.initialDelayMs ?? 500,
         maxDelayMs: options.reconnect?.maxDelayMs ?? 30000,
         multiplier: options.reconnect?.multiplier ?? 2,
         jitterRatio: options.reconnect?.jitterRatio ?? 0.2,
-        shouldRetry: options.reconnect?.shouldRetry
+        scheduleMs: options.reconnect?.scheduleMs,
+        maxAttempts: options.reconnect?.maxAttempts,
+        halfOpenAfterMs: options.reconnect?.halfOpenAfterMs,
+        seed: options.reconnect?.seed,
+        shouldRetry: options.reconnect?.shouldRetry,
+        retryAfterMs: options.reconnect?.retryAfterMs
       }
     };
-    this.codec = resolveCodec(options.codec);
-
     this.protocolPromise = protocolLoader().catch((error: unknown) => {
-      if (this.options.strictWasm) {
-        throw error instanceof Error ? error : new Error(String(error));
-      }
-      return fallbackProtocol;
+      const reason = error instanceof Error ? error.message : String(error);
+      throw new BridgeError("unsupported", `Failed to load the bridge_wasm module: ${reason}`);
     });
+    this.codecPromise = this.protocolPromise.then((protocol) => resolveCodec(options.codec, protocol));
+    // A load failure surfaces from the calls that need the module.
+    this.codecPromise.catch(() => undefined);
   }
 
   protected setWebSocketFactory(factory: (url: string) => WebSocketLike): void {
     this.options.webSocketFactory = factory;
   }
 
+  /** Connection state; changes are reported through `onStateChange`. */
+  get state(): BridgeConnectionState {
+    return this.connection?.state() ?? "idle";
+  }
+
   async connect(url: string): Promise<void> {
     if (this.connectInFlight) {
       return this.connectInFlight;
@@ -151,7 +347,7 @@ export class BridgeClientCore {
     this.wsUrl = url;
     this.clearReconnectTimer();
     this.ws?.close();
-    const opening = this.openSocket(url);
+    const opening = this.openSocket(url, "connect");
     this.connectInFlight = opening;
     try {
       await opening;
@@ -162,220 +358,311 @@ export class BridgeClientCore {
     }
   }
 
-  async subscribe(
+  async subscribe<T extends string>(
     topic: string,
-    type: string,
-    callback: (msg: JsonObject) => void,
+    type: T,
+    onMessage: (msg: MessageOf<T>, receivedAt?: BridgeReceiveTime) => void,
     options: SubscribeOptions = {}
-  ): Promise<void> {
+  ): Promise<SubscriptionHandle> {
+    // Payloads are only typed at the API boundary; generated declarations describe what the bridge sends.
+    const callback = onMessage as unknown as (msg: JsonObject, receivedAt?: BridgeReceiveTime) => void;
     const existing = this.subscriptions.get(topic);
-    if (existing) {
-      existing.callbacks.add(callback);
-      const nextCompression = options.compression;
-      const shouldResubscribe = existing.type !== type || existing.compression !== nextCompression;
-      if (!shouldResubscribe) {
-        return;
-      }
-
-      existing.type = type;
-      existing.compression = nextCompression;
-      await this.sendWithProtocol((protocol) => protocol.build_subscribe(topic, type, nextCompression));
-      return;
-    }
-
-    const entry: SubscriptionInfo = {
+    // Omitted options keep the topic's current settings.
+    const settings = {
       type,
-      compression: options.compression,
-      callbacks: new Set([callback])
+      compression: options.compression ?? existing?.compression,
+      throttleRate: options.throttleRate ?? existing?.throttleRate,
+      queueLength: options.queueLength ?? existing?.queueLength,
+      fragmentSize: options.fragmentSize ?? existing?.fragmentSize
     };
-    this.subscriptions.set(topic, entry);
+    const changed = existing !== undefined && SUBSCRIBE_FIELDS.some((key) => settings[key] !== existing[key]);
+    const added = (await this.subscriptionRegistry()).add(topic, options.id, changed);
+    const next: SubscriptionInfo = {
+      ...settings,
+      id: added.wire_id,
+      messageDefinition: options.messageDefinition ?? existing?.messageDefinition,
+      callbacks: existing?.callbacks ?? new Map()
+    };
+    next.callbacks.set(added.id, callback);
+    this.subscriptions.set(topic, next);
+    const handle: SubscriptionHandle = {
+      id: added.id,
+      topic,
+      unsubscribe: () => this.releaseSubscription(added.id)
+    };
+    if (existing && !changed && added.replaced === undefined) {
+      return handle;
+    }
+
+    // rosbridge keys subscriptions by id: subscribe under the new id first, so
+    // the topic stays subscribed, then drop the old one. A kept id (one the
+    // caller asked for) is simply subscribed again with the new options.
+    await this.sendWithProtocol((protocol) => buildSubscribe(protocol, topic, next));
+    if (added.replaced !== undefined) {
+      const replaced = added.replaced;
+      await this.sendWithProtocol((protocol) => protocol.build_unsubscribe(topic, replaced));
+    }
+    if (options.confirmMs !== undefined && options.confirmMs > 0) {
+      try {
+        await this.confirmSubscribe(added.wire_id, options.confirmMs);
+      } catch (error) {
+        await this.releaseSubscription(added.id);
+        throw new BridgeError("request_failed", `Subscribe to ${topic} failed: ${(error as Error).message}`, {
+          op: "subscribe",
+          requestId: added.wire_id,
+          target: topic
+        });
+      }
+    }
+    return handle;
+  }
 
-    await this.sendWithProtocol((protocol) => protocol.build_subscribe(topic, type, options.compression));
+  /**
+   * Chooses which `status` messages the bridge sends; see `onStatus`. The
+   * level is sent again after every reconnect.
+   */
+  async setLevel(level: BridgeStatusLevel): Promise<void> {
+    await this.sendWithProtocol((protocol) => protocol.build_set_level(level));
+    this.statusLevel = level;
   }
 
+  /** Removes every callback on `topic`, whichever handle registered it. */
   async unsubscribe(topic: string): Promise<void> {
+    const id = (await this.subscriptionRegistry()).remove_topic(topic);
     this.subscriptions.delete(topic);
-    await this.sendWithProtocol((protocol) => protocol.build_unsubscribe(topic));
+    await this.sendWithProtocol((protocol) => protocol.build_unsubscribe(topic, id));
   }
 
-  async advertise(topic: string, type: string): Promise<void> {
-    this.advertisedTopics.set(topic, type);
-    await this.sendWithProtocol((protocol) => protocol.build_advertise(topic, type));
+  async advertise<T extends string>(topic: string, type: T, options: AdvertiseOptions = {}): Promise<Publisher<T>> {
+    const advertisement = (await this.publisherRegistry()).advertise(topic, type, options.latch, options.queueSize);
+    await this.sendWithProtocol((protocol) => buildAdvertise(protocol, advertisement));
+    let disposed = false;
+    return {
+      topic,
+      type,
+      publish: (msg) => this.publish(topic, msg, { type }),
+      dispose: async () => {
+        if (disposed) {
+          return;
+        }
+        disposed = true;
+        // A later `advertise` of the same topic owns the registration now.
+        if ((await this.publisherRegistry()).unadvertise(topic, advertisement.token)) {
+          await this.sendWithProtocol((protocol) => protocol.build_unadvertise(topic));
+        }
+      }
+    };
   }
 
-  async publish(topic: string, msg: JsonObject): Promise<void> {
-    await this.sendWithProtocol((protocol) => protocol.build_publish(topic, msg));
+  /** Unadvertises `topic` and stops re-advertising it after reconnects. */
+  async unadvertise(topic: string): Promise<void> {
+    (await this.publisherRegistry()).unadvertise(topic);
+    await this.sendWithProtocol((protocol) => protocol.build_unadvertise(topic));
   }
 
-  async callService(
-    service: string,
-    type: string,
-    args: JsonObject,
-    options: CallServiceOptions = {}
-  ): Promise<JsonObject> {
-    const id = options.id ?? randomId("svc");
-    const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
+  /**
+   * Publishes `msg` on `topic`. With `autoAdvertise`, an unadvertised topic is
+   * advertised first as `options.type`.
+   */
+  async publish<T extends string = string>(
+    topic: string,
+    msg: MessageOf<T>,
+    options: PublishOptions<T> = {}
+  ): Promise<void> {
+    let plan: BridgePublishPlan;
+    try {
+      plan = (await this.publisherRegistry()).plan_publish(topic, options.type, this.options.autoAdvertise);
+    } catch (error) {
+      throw BridgeError.from(error, "invalid_input", { op: "publish", target: topic });
+    }
+    const advertisement = plan.advertise;
+    if (advertisement) {
+      await this.sendWithProtocol((protocol) => buildAdvertise(protocol, advertisement));
+    }
+    const payload = await this.prepareOutgoing(plan.type, msg as unknown as JsonObject);
+    await this.sendWithProtocol((protocol) => protocol.build_publish(topic, payload), this.options.fragmentSize);
+  }
 
-    return new Promise<JsonObject>((resolve, reject) => {
-      const timeout =
-        typeof timeoutMs === "number" && timeoutMs > 0
-          ? setTimeout(() => {
-              this.pendingCalls.delete(id);
-              reject(new Error(`Service call timeout for ${service} (${id})`));
-            }, timeoutMs)
-          : undefined;
+  /** Hosts `service` on this client; the bridge forwards its `call_service` requests to `handler`. */
+  async advertiseService<T extends string>(service: string, type: T, handler: ServiceHandler<T>): Promise<void> {
+    this.advertisedServices.set(service, {
+      type,
+      handler: handler as unknown as (request: JsonObject) => unknown
+    });
+    await this.sendWithProtocol((protocol) => protocol.build_advertise_service(service, type));
+  }
 
-      this.pendingCalls.set(id, { service, resolve, reject, timeout });
+  async unadvertiseService(service: string): Promise<void> {
+    this.advertisedServices.delete(service);
+    await this.sendWithProtocol((protocol) => protocol.build_unadvertise_service(service));
+  }
 
-      this.sendWithProtocol((protocol) => protocol.build_call_service(service, type, args, id)).catch((error: unknown) => {
-        if (timeout) {
-          clearTimeout(timeout);
-        }
-        this.pendingCalls.delete(id);
-        reject(error instanceof Error ? error : new Error(String(error)));
-      });
+  /** Hosts `action` on this client; the bridge forwards its goals and cancel requests to `handler`. */
+  async advertiseAction<T extends string>(action: string, actionType: T, handler: ActionGoalHandler<T>): Promise<void> {
+    this.advertisedActions.set(action, {
+      type: actionType,
+      handler: handler as unknown as (goal: ActionServerGoal) => unknown
     });
+    await this.sendWithProtocol((protocol) => protocol.build_advertise_action(action, actionType));
   }
 
-  async executeCli(command: string, options: ExecuteCliOptions = {}): Promise<JsonObject> {
-    const trimmed = command.trim();
-    if (!trimmed) {
-      throw new Error("executeCli command must not be empty");
+  /** Stops hosting `action`; goals still running are signalled to cancel. */
+  async unadvertiseAction(action: string): Promise<void> {
+    this.advertisedActions.delete(action);
+    for (const goal of this.hostedGoals.values()) {
+      if (goal.action === action) {
+        goal.controller.abort();
+      }
     }
+    await this.sendWithProtocol((protocol) => protocol.build_unadvertise_action(action));
+  }
 
-    const pendingId = options.id ?? randomId("cli");
-    const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
-
-    return new Promise<JsonObject>((resolve, reject) => {
-      const timeout =
-        typeof timeoutMs === "number" && timeoutMs > 0
-          ? setTimeout(() => {
-              this.pendingCliCalls.delete(pendingId);
-              reject(new Error(`CLI call timeout (${pendingId})`));
-            }, timeoutMs)
-          : undefined;
-
-      this.pendingCliCalls.set(pendingId, { id: pendingId, resolve, reject, timeout });
+  async registerMessageDefinitions(text: string): Promise<string[]> {
+    const register = await this.wasmFeature("register_message_definitions", "Message definition parsing");
+    return register(text);
+  }
 
-      const message: JsonObject = options.id
-        ? { op: "execute_cli", command: trimmed, id: options.id }
-        : { op: "execute_cli", command: trimmed };
+  async registerMessageDefinition(type: string, text: string): Promise<string[]> {
+    const register = await this.wasmFeature("register_message_definition", "Message definition parsing");
+    return register(type, text);
+  }
 
-      this.sendEnvelope(message).catch((error: unknown) => {
-        if (timeout) {
-          clearTimeout(timeout);
-        }
-        this.pendingCliCalls.delete(pendingId);
-        reject(error instanceof Error ? error : new Error(String(error)));
-      });
-    });
+  async getMessageSchema(type: string): Promise<BridgeMessageSchema | undefined> {
+    const getSchema = await this.wasmFeature("get_message_schema", "Message schemas");
+    return getSchema(type);
   }
 
-  async sendActionGoal(options: SendActionGoalOptions): Promise<ActionHandle> {
-    const id = options.id ?? randomId("action");
-    const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
+  async defaultMessage(type: string): Promise<JsonObject> {
+    const build = await this.wasmFeature("default_message", "Default messages");
+    return build(type);
+  }
 
-    let resolveCompletion: (value: JsonObject) => void = () => {};
-    let rejectCompletion: (error: Error) => void = () => {};
+  async mergeWithDefaults(type: string, partial: JsonObject): Promise<JsonObject> {
+    const merge = await this.wasmFeature("merge_with_defaults", "Default messages");
+    return merge(type, partial);
+  }
 
-    const completion = new Promise<JsonObject>((resolve, reject) => {
-      resolveCompletion = resolve;
-      rejectCompletion = reject;
-    });
+  async decodeCdr(type: string, bytes: Uint8Array, definition?: string): Promise<JsonObject> {
+    const decode = await this.wasmFeature("decode_cdr", "CDR decoding");
+    return decode(bytes, type, definition);
+  }
 
-    const timeout =
-      typeof timeoutMs === "number" && timeoutMs > 0
-        ? setTimeout(() => {
-            this.pendingActions.delete(id);
-            rejectCompletion(new Error(`Action goal timeout for ${options.action} (${id})`));
-          }, timeoutMs)
-        : undefined;
+  async encodeCdr(
+    type: string,
+    msg: JsonObject,
+    definition?: string,
+    options: EncodeCdrOptions = {}
+  ): Promise<Uint8Array> {
+    const encode = await this.wasmFeature("encode_cdr", "CDR encoding");
+    return encode(msg, type, definition, options.bigEndian);
+  }
 
-    const pending: PendingAction = {
-      id,
-      sessionId: options.sessionId,
-      action: options.action,
-      actionType: options.actionType,
-      resolve: resolveCompletion,
-      reject: rejectCompletion,
-      timeout,
-      onRequest: options.onRequest,
-      onFeedback: options.onFeedback,
-      onResult: options.onResult
-    };
+  async callService<T extends string>(
+    service: string,
+    type: T,
+    args: ServiceRequestOf<T>,
+    options: CallServiceOptions = {}
+  ): Promise<ServiceResponseOf<T>> {
+    const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
+    const payload = await this.prepareOutgoing(interfaceMessageType(type, "srv", "_Request"), args as unknown as JsonObject);
+
+    const { reply } = await this.startRequest(
+      "call_service",
+      { id: options.id, target: service, timeoutMs, retry: options.retry },
+      (id, bypassQueue) =>
+        this.sendWithProtocol(
+          (protocol) => protocol.build_call_service(service, type, payload, id, options.fragmentSize),
+          undefined,
+          bypassQueue
+        )
+    );
+    return (await reply) as unknown as ServiceResponseOf<T>;
+  }
 
-    this.pendingActions.set(id, pending);
-    if (options.sessionId) {
-      this.actionIdBySession.set(options.sessionId, id);
+  async executeCli(command: string, options: ExecuteCliOptions = {}): Promise<JsonObject> {
+    const trimmed = command.trim();
+    if (!trimmed) {
+      throw new BridgeError("invalid_input", "executeCli command must not be empty", { op: "execute_cli" });
     }
 
-    await this.sendWithProtocol((protocol) =>
-      protocol.build_send_action_goal(options.action, options.actionType, options.goal, id, options.sessionId)
-    ).catch((error: unknown) => {
-      if (timeout) {
-        clearTimeout(timeout);
-      }
-      this.pendingActions.delete(id);
-      if (options.sessionId) {
-        this.actionIdBySession.delete(options.sessionId);
-      }
-      throw error;
-    });
+    const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
+    const message: JsonObject = options.id
+      ? { op: "execute_cli", command: trimmed, id: options.id }
+      : { op: "execute_cli", command: trimmed };
+
+    const { reply } = await this.startRequest(
+      "execute_cli",
+      { id: options.id, target: trimmed, timeoutMs, retry: options.retry },
+      (_id, bypassQueue) => this.sendEnvelope(message, undefined, bypassQueue)
+    );
+    return reply;
+  }
+
+  async sendActionGoal<T extends string>(options: SendActionGoalOptions<T>): Promise<ActionHandle> {
+    const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
+    const goalType = interfaceMessageType(options.actionType, "action", "_Goal");
+    const goal = await this.prepareOutgoing(goalType, options.goal as unknown as JsonObject);
+
+    const { id, reply } = await this.startRequest(
+      "send_action_goal",
+      { id: options.id, target: options.action, sessionId: options.sessionId, timeoutMs, retry: options.retry },
+      (id, bypassQueue) =>
+        this.sendWithProtocol(
+          (protocol) => protocol.build_send_action_goal(options.action, options.actionType, goal, id, options.sessionId),
+          undefined,
+          bypassQueue
+        ),
+      { onRequest: options.onRequest, onFeedback: options.onFeedback, onResult: options.onResult }
+    );
 
     return {
       id,
       sessionId: options.sessionId,
-      completion
+      completion: reply
     };
   }
 
   async cancelActionGoal(options: CancelActionGoalOptions): Promise<JsonObject> {
     const timeoutMs = options.timeoutMs ?? this.options.timeoutMs;
-    const key = `${options.action}::${options.sessionId ?? "default"}`;
-
-    return new Promise<JsonObject>((resolve, reject) => {
-      const timeout =
-        typeof timeoutMs === "number" && timeoutMs > 0
-          ? setTimeout(() => {
-              this.pendingActionCancels.delete(key);
-              reject(new Error(`Action cancel timeout for ${options.action} (${key})`));
-            }, timeoutMs)
-          : undefined;
-
-      this.pendingActionCancels.set(key, { key, resolve, reject, timeout });
-
-      this.sendWithProtocol((protocol) =>
-        protocol.build_cancel_action_goal(options.action, options.actionType, options.sessionId)
-      ).catch((error: unknown) => {
-        if (timeout) {
-          clearTimeout(timeout);
-        }
-        this.pendingActionCancels.delete(key);
-        reject(error instanceof Error ? error : new Error(String(error)));
-      });
-    });
+    const { reply } = await this.startRequest(
+      "cancel_action_goal",
+      { target: options.action, sessionId: options.sessionId, timeoutMs },
+      () =>
+        this.sendWithProtocol((protocol) =>
+          protocol.build_cancel_action_goal(options.action, options.actionType, options.sessionId)
+        )
+    );
+    return reply;
   }
 
   close(): void {
     this.manualClose = true;
+    this.clearFragments();
     this.clearReconnectTimer();
     this.connectInFlight = undefined;
     this.ws?.close();
     this.ws = undefined;
     this.resetReconnectState();
+    this.outbound?.clear();
+    this.queuedFrames.clear();
+    this.clearResendTimers();
+    this.abandonRequests();
+    this.transition("close");
   }
 
-  private async openSocket(url: string): Promise<void> {
+  private async openSocket(url: string, event: "connect" | "attempt"): Promise<void> {
     const wsFactory = this.options.webSocketFactory;
     if (!wsFactory) {
-      throw new Error("No WebSocket factory configured for this runtime");
+      throw new BridgeError("unsupported", "No WebSocket factory configured for this runtime");
     }
+    await this.connectionEngines();
+    this.transition(event);
 
     let ws: WebSocketLike;
     try {
       ws = wsFactory(url);
     } catch (error) {
-      const normalized = error instanceof Error ? error : new Error(String(error));
+      const normalized = BridgeError.from(error, "disconnected");
       this.options.onSocketError?.(normalized);
       this.scheduleReconnect("open_socket_throw", normalized);
       throw normalized;
@@ -406,13 +693,17 @@ export class BridgeClientCore {
           return;
         }
         this.resetReconnectState();
+        this.transition("socket_open");
         this.rebindState()
-          .then(() => {
+          .then(() => Promise.all([this.codecPromise, this.protocolPromise]))
+          .then(([codec, protocol]) => {
+            this.flushOutbound(codec, protocol);
+            this.transition("rebound");
             this.options.onSocketOpen?.(url);
             resolveOnce();
           })
           .catch((error: unknown) => {
-            const normalized = error instanceof Error ? error : new Error(String(error));
+            const normalized = BridgeError.from(error, "disconnected");
             this.options.onSocketError?.(normalized);
             this.scheduleReconnect("connect_error", normalized);
             rejectOnce(normalized);
@@ -422,7 +713,7 @@ export class BridgeClientCore {
         if (generation !== this.activeSocketGeneration) {
           return;
         }
-        const error = new Error("WebSocket connection error");
+        const error = new BridgeError("disconnected", "WebSocket connection error");
         this.options.onSocketError?.(error);
         this.scheduleReconnect("socket_error", error);
         rejectOnce(error);
@@ -444,51 +735,113 @@ export class BridgeClientCore {
   }
 
   private scheduleReconnect(reason: BridgeReconnectReason, error?: Error): void {
-    this.rejectPendingActionsOnDisconnect();
+    this.rejectRequestsOnDisconnect(this.options.resumeActionGoals && !this.manualClose);
+    this.clearFragments();
 
-    if (this.manualClose || !this.options.reconnect.enabled || !this.wsUrl) {
+    if (this.manualClose) {
+      this.abandonRequests();
       return;
     }
-    const nextAttempt = this.reconnectAttempt + 1;
-    const context: BridgeReconnectContext = { reason, error, attempt: nextAttempt };
-    if (this.options.reconnect.shouldRetry && !this.options.reconnect.shouldRetry(context)) {
+    if (this.reconnectTimer) {
       return;
     }
-    if (this.reconnectTimer) {
+    const policy = this.reconnectPolicy;
+    if (!this.options.reconnect.enabled || !this.wsUrl || !policy) {
+      this.giveUp(error);
+      return;
+    }
+    const context: BridgeReconnectContext = { reason, error, attempt: policy.failures() + 1 };
+    if (this.options.reconnect.shouldRetry && !this.options.reconnect.shouldRetry(context)) {
+      this.giveUp(error);
       return;
     }
 
-    this.reconnectAttempt = nextAttempt;
-    const delay = this.computeReconnectDelayMs(nextAttempt);
+    const seeded = this.options.reconnect.seed !== undefined;
+    const decision = policy.on_failure(
+      this.options.reconnect.retryAfterMs?.(context),
+      seeded ? undefined : Math.random()
+    );
+    if (!decision) {
+      this.giveUp(error);
+      return;
+    }
     this.options.onReconnectScheduled?.({
-      attempt: nextAttempt,
-      nextDelayMs: delay,
+      attempt: decision.attempt,
+      nextDelayMs: decision.delay_ms,
+      probe: decision.probe,
       reason,
       error
     });
+    this.transition("schedule_reconnect", error);
 
     this.reconnectTimer = setTimeout(() => {
       this.reconnectTimer = undefined;
-      void this.openSocket(this.wsUrl as string).catch((openError: unknown) => {
-        const normalized = openError instanceof Error ? openError : new Error(String(openError));
+      policy.on_attempt();
+      void this.openSocket(this.wsUrl as string, "attempt").catch((openError: unknown) => {
+        const normalized = BridgeError.from(openError, "disconnected");
         this.scheduleReconnect("connect_error", normalized);
       });
-    }, delay);
+    }, decision.delay_ms);
+  }
+
+  /** Ends reconnecting; requests held for a resend or re-attach can no longer finish. */
+  private giveUp(error?: Error): void {
+    this.abandonRequests();
+    this.transition("give_up", error);
   }
 
-  private computeReconnectDelayMs(attempt: number): number {
-    const boundedInitial = Math.max(0, this.options.reconnect.initialDelayMs);
-    const boundedMax = Math.max(boundedInitial, this.options.reconnect.maxDelayMs);
-    const multiplier = this.options.reconnect.multiplier > 0 ? this.options.reconnect.multiplier : 1;
-    const baseDelay = Math.min(boundedInitial * Math.pow(multiplier, Math.max(0, attempt - 1)), boundedMax);
+  /**
+   * Creates the connection state machine, reconnect policy and, when
+   * configured, the outbound queue on first use.
+   */
+  private async connectionEngines(): Promise<void> {
+    if (!this.connection) {
+      const Machine = await this.wasmFeature("ConnectionStateMachine", "Connection state tracking");
+      this.connection = new Machine();
+    }
+    if (!this.reconnectPolicy) {
+      const Policy = await this.wasmFeature("ReconnectPolicy", "Reconnect handling");
+      const options = this.options.reconnect;
+      try {
+        this.reconnectPolicy = new Policy({
+          strategy: options.strategy,
+          initialDelayMs: options.initialDelayMs,
+          maxDelayMs: options.maxDelayMs,
+          multiplier: options.multiplier,
+          jitterRatio: options.jitterRatio,
+          scheduleMs: options.scheduleMs,
+          maxAttempts: options.maxAttempts,
+          halfOpenAfterMs: options.halfOpenAfterMs,
+          seed: options.seed
+        });
+      } catch (error) {
+        throw BridgeError.from(error, "invalid_input");
+      }
+    }
+    const queueConfig = this.options.outboundQueue;
+    if (queueConfig && !this.outbound) {
+      const Queue = await this.wasmFeature("OutboundQueue", "Outbound queueing");
+      try {
+        this.outbound = new Queue(queueConfig);
+      } catch (error) {
+        throw BridgeError.from(error, "invalid_input");
+      }
+    }
+  }
 
-    const jitterRatio = Math.min(Math.max(this.options.reconnect.jitterRatio, 0), 1);
-    if (jitterRatio === 0) {
-      return Math.max(0, Math.floor(baseDelay));
+  /**
+   * Moves `state` along `event`. Events the machine rejects come from a socket
+   * that was closed or replaced meanwhile and are dropped.
+   */
+  private transition(event: BridgeConnectionEvent, error?: Error): void {
+    const machine = this.connection;
+    if (!machine?.can(event)) {
+      return;
+    }
+    const change = machine.apply(event);
+    if (change) {
+      this.options.onStateChange?.(error ? { ...change, error } : change);
     }
-    const spread = (Math.random() * 2 - 1) * jitterRatio;
-    const jittered = baseDelay * (1 + spread);
-    return Math.max(0, Math.min(boundedMax, Math.floor(jittered)));
   }
 
   private clearReconnectTimer(): void {
@@ -501,155 +854,558 @@ export class BridgeClientCore {
 
   private resetReconnectState(): void {
     this.clearReconnectTimer();
-    this.reconnectAttempt = 0;
+    this.reconnectPolicy?.reset();
   }
 
   private async handleMessage(raw: unknown): Promise<void> {
     const payload = await this.normalizeIncomingPayload(raw);
 
+    const codec = await this.codecPromise;
+
     let parsed: BridgeIncomingMessage;
     try {
-      parsed = this.codec.decode(payload);
-    } catch {
+      parsed = codec.decode(payload);
+    } catch (error) {
+      this.reportProtocolError(
+        { code: "decode_failed", message: error instanceof Error ? error.message : String(error) },
+        payload
+      );
       return;
     }
 
-    if ((parsed as { op?: string }).op === "publish") {
-      const publish = parsed as { topic?: string; msg?: JsonObject };
-      const topic = publish.topic;
-      if (!topic || !publish.msg) {
-        return;
+    let protocol: WasmProtocol;
+    try {
+      protocol = await this.protocolPromise;
+    } catch (error) {
+      this.reportProtocolError(error, parsed);
+      return;
+    }
+    this.handleFrame(parsed, protocol);
+  }
+
+  private handleFrame(frame: unknown, protocol: WasmProtocol): void {
+    let event: BridgeIncomingEvent;
+    try {
+      event = protocol.parse_incoming(frame);
+    } catch (error) {
+      this.reportProtocolError(error, frame);
+      return;
+    }
+
+    this.dispatchIncoming(event, protocol);
+  }
+
+  /** Unpacks a rosbridge `png` frame, which carries a whole JSON frame as image pixels. */
+  private handleCompressedFrame(data: string, protocol: WasmProtocol): void {
+    let frame: unknown;
+    try {
+      if (!protocol.decode_png) {
+        throw new BridgeError("unsupported", "PNG decompression requires the bridge_wasm module");
       }
-      const subscription = this.subscriptions.get(topic);
-      if (!subscription) {
+      frame = JSON.parse(protocol.decode_png(data));
+    } catch (error) {
+      this.reportProtocolError(
+        { code: "decode_failed", op: "png", message: error instanceof Error ? error.message : String(error) },
+        data
+      );
+      return;
+    }
+    this.handleFrame(frame, protocol);
+  }
+
+  private async subscriptionRegistry(): Promise<BridgeSubscriptionRegistry> {
+    const Registry = await this.wasmFeature("SubscriptionRegistry", "Subscription handles");
+    if (!this.subscriptionHandles) {
+      this.subscriptionHandles = new Registry();
+    }
+    return this.subscriptionHandles;
+  }
+
+  private async publisherRegistry(): Promise<BridgePublisherRegistry> {
+    const Registry = await this.wasmFeature("PublisherRegistry", "Publisher tracking");
+    if (!this.publishers) {
+      this.publishers = new Registry();
+    }
+    return this.publishers;
+  }
+
+  /** Drops one handle's callback and unsubscribes once the topic has no handles left. */
+  private async releaseSubscription(handleId: string): Promise<void> {
+    const removed = (await this.subscriptionRegistry()).remove(handleId);
+    if (!removed) {
+      return;
+    }
+    this.subscriptions.get(removed.topic)?.callbacks.delete(handleId);
+    if (!removed.last) {
+      return;
+    }
+    this.subscriptions.delete(removed.topic);
+    await this.sendWithProtocol((protocol) => protocol.build_unsubscribe(removed.topic, removed.wire_id));
+  }
+
+  /** Buffers a rosbridge `fragment` frame and handles the message once complete. */
+  private handleFragment(event: FragmentEvent, protocol: WasmProtocol): void {
+    if (!this.fragmentAssembler) {
+      if (!protocol.FragmentAssembler) {
+        this.reportProtocolError(
+          { code: "fragment_dropped", op: "fragment", message: "Fragment reassembly requires the bridge_wasm module" },
+          event
+        );
         return;
       }
-      for (const callback of subscription.callbacks) {
-        callback(publish.msg);
-      }
+      this.fragmentAssembler = new protocol.FragmentAssembler(
+        this.options.fragmentTimeoutMs,
+        this.options.maxFragmentBytes
+      );
+    }
+    const now = Date.now();
+    this.expireFragments(now);
+
+    let text: string | undefined;
+    try {
+      text = this.fragmentAssembler.push(event.id, event.num, event.total, event.data, now);
+    } catch (error) {
+      this.reportProtocolError(
+        { code: "fragment_dropped", op: "fragment", message: error instanceof Error ? error.message : String(error) },
+        event
+      );
+      return;
+    } finally {
+      this.armFragmentExpiry();
+    }
+    if (text === undefined) {
+      return;
+    }
+
+    let frame: unknown;
+    try {
+      frame = JSON.parse(text);
+    } catch (error) {
+      this.reportProtocolError(
+        { code: "decode_failed", op: "fragment", message: error instanceof Error ? error.message : String(error) },
+        text
+      );
       return;
     }
+    this.handleFrame(frame, protocol);
+  }
 
-    if ((parsed as { op?: string }).op === "service_response") {
-      const response = parsed as { id?: string; result?: boolean; values?: JsonObject; error?: string; service?: string };
-      if (!response.id) {
+  /** Reports and drops the fragmented messages that timed out by `now`. */
+  private expireFragments(now: number): void {
+    for (const id of this.fragmentAssembler?.expire(now) ?? []) {
+      this.reportProtocolError(
+        { code: "fragment_dropped", op: "fragment", message: `fragmented message ${id} timed out` },
+        undefined
+      );
+    }
+  }
+
+  /** Points the fragment timer at the oldest partial message's deadline. */
+  private armFragmentExpiry(): void {
+    clearTimeout(this.fragmentExpiry);
+    this.fragmentExpiry = undefined;
+    const deadline = this.fragmentAssembler?.next_deadline();
+    if (deadline === undefined) {
+      return;
+    }
+    // `expire` only drops messages strictly past their deadline.
+    this.fragmentExpiry = setTimeout(() => {
+      this.fragmentExpiry = undefined;
+      this.expireFragments(Date.now());
+      this.armFragmentExpiry();
+    }, Math.max(0, deadline - Date.now()) + 1);
+  }
+
+  private clearFragments(): void {
+    this.fragmentAssembler?.clear();
+    this.armFragmentExpiry();
+  }
+
+  private dispatchIncoming(event: BridgeIncomingEvent, protocol: WasmProtocol): void {
+    switch (event.kind) {
+      case "publish": {
+        const subscription = this.subscriptions.get(event.topic);
+        if (!subscription) {
+          return;
+        }
+        const decoded = this.decodeRawMessage(protocol, subscription, event.msg);
+        if (!decoded) {
+          return;
+        }
+        for (const callback of subscription.callbacks.values()) {
+          callback(decoded.msg, decoded.receivedAt);
+        }
         return;
       }
-      const pending = this.pendingCalls.get(response.id);
-      if (!pending) {
+
+      case "service_response": {
+        const request = this.requestTracker?.resolve("call_service", event.id);
+        const pending = this.takeRequest(request);
+        if (!request || !pending) {
+          return;
+        }
+        if (!event.ok) {
+          pending.reject(
+            new BridgeError(
+              "request_failed",
+              event.error ?? `Service call failed: ${event.service ?? request.target}`,
+              requestDetails(request)
+            )
+          );
+          return;
+        }
+        pending.resolve(event.values);
         return;
       }
-      if (pending.timeout) {
-        clearTimeout(pending.timeout);
-      }
-      this.pendingCalls.delete(response.id);
 
-      if (!response.result) {
-        pending.reject(new Error(response.error ?? `Service call failed: ${response.service ?? pending.service}`));
+      case "service_request":
+        void this.answerServiceRequest(event);
         return;
-      }
-      pending.resolve(response.values ?? {});
-      return;
-    }
 
-    if ((parsed as { op?: string }).op === "cli_response") {
-      const response = parsed as { id?: string; success?: boolean; return_code?: number; error?: string };
-      const pending = this.findPendingCliCall(response.id);
-      if (!pending) {
+      case "action_goal_request":
+        void this.runHostedGoal(event);
         return;
-      }
-      if (pending.timeout) {
-        clearTimeout(pending.timeout);
-      }
-      this.pendingCliCalls.delete(pending.id);
 
-      if (response.success === false || (typeof response.return_code === "number" && response.return_code !== 0)) {
-        pending.reject(new Error(response.error ?? `CLI execution failed (code ${response.return_code ?? -1})`));
+      case "action_cancel_request": {
+        const goal = this.hostedGoals.get(event.id);
+        if (goal?.action === event.action) {
+          goal.controller.abort();
+        }
         return;
       }
-      pending.resolve(asRecord(parsed));
-      return;
-    }
 
-    if ((parsed as { op?: string }).op === "cancel_action_result") {
-      const response = parsed as { action?: string; result?: boolean; error?: string; session_id?: string };
-      const key = `${response.action ?? ""}::${response.session_id ?? "default"}`;
-      const pending = this.pendingActionCancels.get(key);
-      if (!pending) {
+      case "cli_response": {
+        const request = this.requestTracker?.resolve("execute_cli", event.id);
+        const pending = this.takeRequest(request);
+        if (!request || !pending) {
+          return;
+        }
+        if (!event.ok) {
+          pending.reject(
+            new BridgeError(
+              "request_failed",
+              event.error ?? `CLI execution failed (code ${event.return_code ?? -1})`,
+              requestDetails(request)
+            )
+          );
+          return;
+        }
+        pending.resolve(event.frame);
         return;
       }
-      if (pending.timeout) {
-        clearTimeout(pending.timeout);
-      }
-      this.pendingActionCancels.delete(key);
 
-      if (!response.result) {
-        pending.reject(new Error(response.error ?? `Action cancel failed for ${response.action ?? "unknown"}`));
+      case "cancel_action_result": {
+        const request = this.requestTracker?.resolve("cancel_action_goal", undefined, event.session_id, event.action);
+        const pending = this.takeRequest(request);
+        if (!request || !pending) {
+          return;
+        }
+        if (!event.ok) {
+          pending.reject(
+            new BridgeError(
+              "request_failed",
+              event.error ?? `Action cancel failed for ${event.action || "unknown"}`,
+              requestDetails(request)
+            )
+          );
+          return;
+        }
+        pending.resolve(event.frame);
         return;
       }
-      pending.resolve(asRecord(parsed));
-      return;
-    }
 
-    if ((parsed as { op?: string }).op === "action_result") {
-      const response = parsed as { id?: string; session_id?: string; error?: string; result?: JsonObject };
-      const pending = this.findPendingAction(response.id, response.session_id);
-      if (!pending) {
+      case "action_result": {
+        const request = this.requestTracker?.resolve("send_action_goal", event.id, event.session_id);
+        const pending = this.takeRequest(request);
+        if (!request || !pending) {
+          return;
+        }
+        if (event.error) {
+          pending.reject(new BridgeError("request_failed", event.error, requestDetails(request)));
+          return;
+        }
+        pending.resolve(event.result);
         return;
       }
-      this.finishPendingAction(pending.id);
-      if (response.error) {
-        pending.reject(new Error(response.error));
+
+      case "action_goal_status": {
+        if (event.found) {
+          return;
+        }
+        const id = this.requestTracker?.correlate("send_action_goal", event.id);
+        const request = id === undefined ? undefined : this.requestTracker?.settle(id);
+        const pending = this.takeRequest(request);
+        if (!request || !pending) {
+          return;
+        }
+        pending.reject(
+          new BridgeError(
+            "disconnected",
+            `Action ${request.id} was lost while disconnected; the bridge no longer knows the goal`,
+            requestDetails(request)
+          )
+        );
         return;
       }
-      pending.resolve(response.result ?? asRecord(parsed));
-      return;
-    }
 
-    if ((parsed as { type?: string }).type) {
-      const event = parsed as {
-        type: string;
-        id?: string;
-        session_id?: string;
-        feedback?: JsonObject;
-        result?: JsonObject;
-        message?: string;
-        status?: number;
-      };
+      case "action_event": {
+        const id = this.requestTracker?.find("send_action_goal", event.id, event.session_id);
+        const pending = id === undefined ? undefined : this.requests.get(id);
+        if (id === undefined || !pending) {
+          return;
+        }
+
+        if (event.event === "request") {
+          pending.onRequest?.(event.payload);
+          return;
+        }
 
-      const pending = this.findPendingAction(event.id, event.session_id);
-      if (!pending) {
+        if (event.event === "feedback") {
+          pending.onFeedback?.(event.payload);
+          return;
+        }
+
+        const request = this.requestTracker?.settle(id);
+        this.takeRequest(request);
+        if (!request) {
+          return;
+        }
+        if (event.event === "result") {
+          pending.onResult?.(event.payload);
+          if (typeof event.status === "number" && event.status !== 0) {
+            pending.reject(
+              new BridgeError(
+                "request_failed",
+                `Action ${id} completed with non-success status ${event.status}`,
+                requestDetails(request)
+              )
+            );
+            return;
+          }
+          pending.resolve(event.payload);
+          return;
+        }
+
+        pending.reject(new BridgeError("request_failed", event.message ?? "action_error", requestDetails(request)));
         return;
       }
 
-      if (event.type === "request") {
-        pending.onRequest?.(asRecord(parsed));
+      case "status":
+        this.handleStatus(event);
         return;
-      }
 
-      if (event.type === "feedback") {
-        pending.onFeedback?.(event.feedback ?? asRecord(parsed));
+      case "error":
+        this.handleBridgeError(event);
         return;
-      }
 
-      if (event.type === "result") {
-        pending.onResult?.(event.result ?? asRecord(parsed));
-        this.finishPendingAction(pending.id);
-        if (typeof event.status === "number" && event.status !== 0) {
-          pending.reject(new Error(`Action ${pending.id} completed with non-success status ${event.status}`));
-          return;
-        }
-        pending.resolve(event.result ?? asRecord(parsed));
+      case "png":
+        this.handleCompressedFrame(event.data, protocol);
         return;
+
+      case "fragment":
+        this.handleFragment(event, protocol);
+        return;
+
+      case "unknown":
+        return;
+    }
+  }
+
+  /** Resolves after `ms` unless an error `status` for subscription `id` arrives first. */
+  private confirmSubscribe(id: string, ms: number): Promise<void> {
+    return new Promise<void>((resolve, reject) => {
+      const waiters = this.confirmingSubscribes.get(id) ?? new Set<(error: Error) => void>();
+      this.confirmingSubscribes.set(id, waiters);
+      const settle = () => {
+        clearTimeout(timer);
+        waiters.delete(fail);
+        if (waiters.size === 0) {
+          this.confirmingSubscribes.delete(id);
+        }
+      };
+      const fail = (error: Error) => {
+        settle();
+        reject(error);
+      };
+      const timer = setTimeout(() => {
+        settle();
+        resolve();
+      }, ms);
+      waiters.add(fail);
+    });
+  }
+
+  /** Reports a `status` message and fails the pending request it is about, if any. */
+  private handleStatus(event: StatusEvent): void {
+    const status: BridgeStatus = { level: event.level, message: event.msg };
+    const id = event.id;
+    const requestId = this.requestTracker?.correlate(undefined, id);
+    const request = requestId === undefined ? undefined : this.requestTracker?.get(requestId);
+    const subscription =
+      id === undefined ? undefined : [...this.subscriptions.entries()].find(([, info]) => info.id === id);
+    if (id !== undefined) {
+      status.id = id;
+    }
+    if (request) {
+      status.op = request.kind;
+      status.target = request.target;
+    } else if (subscription) {
+      status.op = "subscribe";
+      status.target = subscription[0];
+    }
+    this.options.onStatus?.(status);
+
+    if (event.level !== "error" || id === undefined) {
+      return;
+    }
+    const error = new BridgeError("request_failed", event.msg || `bridge reported an error for ${id}`, {
+      op: status.op,
+      requestId: id,
+      target: status.target
+    });
+    if (request) {
+      this.takeRequest(this.requestTracker?.settle(request.id))?.reject(error);
+    }
+    for (const fail of this.confirmingSubscribes.get(id) ?? []) {
+      fail(error);
+    }
+  }
+
+  /**
+   * Rejects the pending request an `error` frame refers to. Errors that match
+   * no request are reported through `onProtocolError`.
+   */
+  private handleBridgeError(event: ErrorEvent): void {
+    const error = new BridgeError(event.code, event.message ? `${event.code}: ${event.message}` : event.code, {
+      op: event.request_op,
+      requestId: event.id,
+      target: event.target
+    });
+
+    const requestId = this.requestTracker?.correlate(event.request_op, event.id);
+    const pending = requestId === undefined ? undefined : this.takeRequest(this.requestTracker?.settle(requestId));
+    if (pending) {
+      pending.reject(error);
+      return;
+    }
+    this.reportProtocolError({ code: "bridge_error", op: event.request_op, message: error.message }, event);
+  }
+
+  /** Runs the handler of an advertised service and sends back its response or error. */
+  private async answerServiceRequest(event: ServiceRequestEvent): Promise<void> {
+    const advertised = this.advertisedServices.get(event.service);
+    let result = false;
+    let values: JsonObject | string;
+    try {
+      if (!advertised) {
+        throw new BridgeError("invalid_input", `Service ${event.service} is not advertised by this client`);
       }
+      const response = ((await advertised.handler(event.args)) ?? {}) as JsonObject;
+      values = await this.prepareOutgoing(interfaceMessageType(advertised.type, "srv", "_Response"), response);
+      result = true;
+    } catch (error) {
+      values = error instanceof Error ? error.message : String(error);
+    }
+    try {
+      await this.sendWithProtocol((protocol) =>
+        protocol.build_service_response(event.service, event.id, result, values)
+      );
+    } catch {
+      // The caller can no longer be answered once the socket is gone.
+    }
+  }
 
-      if (event.type === "error") {
-        this.finishPendingAction(pending.id);
-        pending.reject(new Error(event.message ?? "action_error"));
+  /** Runs the handler of an advertised action for one goal and sends its terminal status. */
+  private async runHostedGoal(event: ActionGoalRequestEvent): Promise<void> {
+    const advertised = this.advertisedActions.get(event.action);
+    const controller = new AbortController();
+    let status: number;
+    let values: JsonObject | string;
+    try {
+      if (!advertised) {
+        throw new BridgeError("invalid_input", `Action ${event.action} is not advertised by this client`);
       }
+      this.hostedGoals.set(event.id, { action: event.action, controller });
+      const feedbackType = interfaceMessageType(advertised.type, "action", "_Feedback");
+      const goal: ActionServerGoal = {
+        id: event.id,
+        action: event.action,
+        goal: event.goal,
+        signal: controller.signal,
+        publishFeedback: async (feedback) => {
+          const payload = await this.prepareOutgoing(feedbackType, feedback);
+          await this.sendWithProtocol((protocol) =>
+            protocol.build_action_feedback(event.action, event.id, payload)
+          );
+        }
+      };
+      const result = ((await advertised.handler(goal)) ?? {}) as JsonObject;
+      values = await this.prepareOutgoing(interfaceMessageType(advertised.type, "action", "_Result"), result);
+      status = controller.signal.aborted ? GOAL_CANCELED : GOAL_SUCCEEDED;
+    } catch (error) {
+      values = error instanceof Error ? error.message : String(error);
+      status = controller.signal.aborted ? GOAL_CANCELED : GOAL_ABORTED;
+    } finally {
+      this.hostedGoals.delete(event.id);
+    }
+    try {
+      await this.sendWithProtocol((protocol) =>
+        protocol.build_action_result(event.action, event.id, status, values)
+      );
+    } catch {
+      // The caller can no longer be answered once the socket is gone.
     }
   }
 
+  /**
+   * Deserializes a `cbor-raw` message `{ bytes, secs, nsecs }` with the
+   * subscription's definition, else the registered schema of its type, and
+   * keeps the bridge's receive time. Other messages pass through.
+   */
+  private decodeRawMessage(
+    protocol: WasmProtocol,
+    subscription: SubscriptionInfo,
+    msg: JsonObject
+  ): { msg: JsonObject; receivedAt?: BridgeReceiveTime } | undefined {
+    if (subscription.compression !== "cbor-raw") {
+      return { msg };
+    }
+    try {
+      if (!protocol.decode_cdr) {
+        throw new BridgeError("unsupported", "CDR decoding requires the bridge_wasm module");
+      }
+      const bytes = msg.bytes;
+      if (!(bytes instanceof Uint8Array) && !Array.isArray(bytes)) {
+        throw new BridgeError("protocol_violation", "cbor-raw message has no `bytes` field");
+      }
+      const data = bytes instanceof Uint8Array ? bytes : Uint8Array.from(bytes as number[]);
+      const { secs, nsecs } = msg;
+      return {
+        msg: protocol.decode_cdr(data, subscription.type, subscription.messageDefinition),
+        receivedAt: typeof secs === "number" && typeof nsecs === "number" ? { secs, nsecs } : undefined
+      };
+    } catch (error) {
+      this.reportProtocolError(
+        { code: "decode_failed", op: "publish", message: error instanceof Error ? error.message : String(error) },
+        msg
+      );
+      return undefined;
+    }
+  }
+
+  private reportProtocolError(error: unknown, frame: unknown): void {
+    const info = asRecord(error);
+    const message =
+      typeof info.message === "string" ? info.message : error instanceof Error ? error.message : String(error);
+    const code = (typeof info.code === "string" ? info.code : "protocol_violation") as BridgeProtocolErrorCode;
+    const normalized = new BridgeError(code, message, {
+      op: typeof info.op === "string" ? info.op : undefined,
+      path: typeof info.path === "string" ? info.path : undefined
+    }) as BridgeProtocolError;
+    normalized.frame = frame;
+    this.options.onProtocolError?.(normalized);
+  }
+
   private async normalizeIncomingPayload(raw: unknown): Promise<unknown> {
     if (typeof raw === "string" || raw instanceof Uint8Array || raw instanceof ArrayBuffer) {
       return raw;
@@ -667,104 +1423,312 @@ export class BridgeClientCore {
   }
 
   private async rebindState(): Promise<void> {
+    const level = this.statusLevel;
+    if (level !== undefined) {
+      await this.sendWithProtocol((protocol) => protocol.build_set_level(level), undefined, true);
+    }
     for (const [topic, info] of this.subscriptions.entries()) {
-      await this.sendWithProtocol((protocol) => protocol.build_subscribe(topic, info.type, info.compression));
+      await this.sendWithProtocol((protocol) => buildSubscribe(protocol, topic, info), undefined, true);
+    }
+    for (const advertisement of (await this.publisherRegistry()).advertisements()) {
+      await this.sendWithProtocol((protocol) => buildAdvertise(protocol, advertisement), undefined, true);
     }
-    for (const [topic, type] of this.advertisedTopics.entries()) {
-      await this.sendWithProtocol((protocol) => protocol.build_advertise(topic, type));
+    for (const [action, { type }] of this.advertisedActions.entries()) {
+      await this.sendWithProtocol((protocol) => protocol.build_advertise_action(action, type), undefined, true);
+    }
+    for (const [service, { type }] of this.advertisedServices.entries()) {
+      await this.sendWithProtocol((protocol) => protocol.build_advertise_service(service, type), undefined, true);
+    }
+    for (const { request, resend_after_ms: delayMs } of this.requestTracker?.reconnected() ?? []) {
+      if (delayMs === undefined) {
+        await this.sendWithProtocol(
+          (protocol) => protocol.build_query_action_goal(request.target, request.id, request.session_id),
+          undefined,
+          true
+        );
+      } else if (delayMs === 0) {
+        await this.resendRequest(request, true);
+      } else {
+        const timer = setTimeout(() => {
+          this.resendTimers.delete(timer);
+          void this.resendRequest(request);
+        }, delayMs);
+        this.resendTimers.add(timer);
+      }
     }
   }
 
-  private findPendingAction(id?: string, sessionId?: string): PendingAction | undefined {
-    if (id && this.pendingActions.has(id)) {
-      return this.pendingActions.get(id);
+  /**
+   * Sends a held request again with its id, counting the resend once it is on
+   * the socket; rejects the request if that fails.
+   */
+  private async resendRequest(request: BridgeTrackedRequest, bypassQueue = false): Promise<void> {
+    const pending = this.requests.get(request.id);
+    if (!pending || !this.requestTracker?.get(request.id)) {
+      return;
     }
-    if (sessionId) {
-      const actionId = this.actionIdBySession.get(sessionId);
-      if (actionId) {
-        return this.pendingActions.get(actionId);
+    try {
+      if (await pending.send(bypassQueue)) {
+        this.requestTracker.resent(request.id);
       }
+    } catch (error) {
+      this.takeRequest(this.requestTracker.settle(request.id))?.reject(
+        BridgeError.from(error, "disconnected", requestDetails(request))
+      );
     }
-    if (this.pendingActions.size === 1) {
-      return this.pendingActions.values().next().value;
+  }
+
+  private clearResendTimers(): void {
+    for (const timer of this.resendTimers) {
+      clearTimeout(timer);
     }
-    return undefined;
+    this.resendTimers.clear();
   }
 
-  private finishPendingAction(id: string): void {
-    const pending = this.pendingActions.get(id);
-    if (!pending) {
-      return;
+  private async tracker(): Promise<BridgeRequestTracker> {
+    const Tracker = await this.wasmFeature("RequestTracker", "Request tracking");
+    if (!this.requestTracker) {
+      this.requestTracker = new Tracker(idNamespace());
+    }
+    return this.requestTracker;
+  }
+
+  /**
+   * Tracks a request and sends it with `send`. Returns its id once sent, and
+   * the reply that settles it: a response, or a timeout or disconnect error.
+   */
+  private async startRequest(
+    kind: BridgeRequestKind,
+    request: { id?: string; target: string; sessionId?: string; timeoutMs?: number; retry?: BridgeRequestRetry },
+    send: (id: string, bypassQueue: boolean) => Promise<boolean>,
+    callbacks: Pick<PendingRequest, "onRequest" | "onFeedback" | "onResult"> = {}
+  ): Promise<{ id: string; reply: Promise<JsonObject> }> {
+    const tracker = await this.tracker();
+    let id: string;
+    try {
+      id = tracker.track(kind, request.id, request.target, request.sessionId, request.timeoutMs, Date.now());
+    } catch (error) {
+      throw BridgeError.from(error, "invalid_input", { op: kind, requestId: request.id, target: request.target });
+    }
+    if (request.retry !== undefined) {
+      try {
+        tracker.set_retry(id, request.retry);
+      } catch (error) {
+        tracker.settle(id);
+        throw BridgeError.from(error, "invalid_input", { op: kind, requestId: id, target: request.target });
+      }
     }
-    if (pending.timeout) {
-      clearTimeout(pending.timeout);
+    const reply = new Promise<JsonObject>((resolve, reject) => {
+      this.requests.set(id, { ...callbacks, send: (bypassQueue) => send(id, bypassQueue), resolve, reject });
+    });
+    this.armRequestDeadline();
+
+    try {
+      await send(id, false);
+    } catch (error) {
+      this.takeRequest(tracker.settle(id));
+      throw BridgeError.from(error, "invalid_input", requestDetails({ id, kind, target: request.target }));
     }
-    this.pendingActions.delete(id);
-    if (pending.sessionId) {
-      this.actionIdBySession.delete(pending.sessionId);
+    return { id, reply };
+  }
+
+  /** Drops the callbacks of a request the tracker has settled and returns them. */
+  private takeRequest(request: BridgeTrackedRequest | undefined): PendingRequest | undefined {
+    if (!request) {
+      return undefined;
     }
+    const pending = this.requests.get(request.id);
+    this.requests.delete(request.id);
+    this.armRequestDeadline();
+    return pending;
   }
 
-  private rejectPendingActionsOnDisconnect(): void {
-    for (const pending of this.pendingActions.values()) {
-      if (pending.timeout) {
-        clearTimeout(pending.timeout);
+  /** Points the deadline timer at the earliest pending deadline. */
+  private armRequestDeadline(): void {
+    clearTimeout(this.requestDeadline);
+    this.requestDeadline = undefined;
+    const deadline = this.requestTracker?.next_deadline();
+    if (deadline === undefined) {
+      return;
+    }
+    this.requestDeadline = setTimeout(() => {
+      this.requestDeadline = undefined;
+      for (const request of this.requestTracker?.expire(Date.now()) ?? []) {
+        this.takeRequest(request)?.reject(
+          new BridgeError("timeout", requestTimeoutMessage(request), requestDetails(request))
+        );
       }
-      pending.reject(new Error(`Action ${pending.id} interrupted by disconnect; resend after reconnect`));
+      this.armRequestDeadline();
+    }, Math.max(0, deadline - Date.now()));
+  }
+
+  /**
+   * Rejects the requests a lost connection interrupts and holds the ones a
+   * reconnect resends; `keepGoals` also holds action goals for re-attaching.
+   */
+  private rejectRequestsOnDisconnect(keepGoals: boolean): void {
+    this.clearResendTimers();
+    this.rejectRequests(this.requestTracker?.disconnect(keepGoals) ?? []);
+  }
+
+  /** Rejects the held requests once no reconnect will follow. */
+  private abandonRequests(): void {
+    this.rejectRequests(this.requestTracker?.abandon() ?? []);
+  }
+
+  private rejectRequests(requests: BridgeTrackedRequest[]): void {
+    for (const request of requests) {
+      this.takeRequest(request)?.reject(
+        new BridgeError("disconnected", requestDisconnectMessage(request), requestDetails(request))
+      );
     }
-    this.pendingActions.clear();
-    this.actionIdBySession.clear();
+  }
 
-    for (const pendingCancel of this.pendingActionCancels.values()) {
-      if (pendingCancel.timeout) {
-        clearTimeout(pendingCancel.timeout);
+  /**
+   * Applies `fillDefaults` and `validateMessages` to an outgoing payload of
+   * `type`. Throws a `BridgeValidationError` when `msg` does not match.
+   */
+  private async prepareOutgoing(type: string | undefined, msg: JsonObject): Promise<JsonObject> {
+    if (type === undefined) {
+      return msg;
+    }
+    let payload = msg;
+    try {
+      if (this.options.fillDefaults) {
+        const merge = await this.wasmFeature("merge_with_defaults", "Default message filling");
+        payload = merge(type, payload);
+      }
+      if (this.options.validateMessages) {
+        const validate = await this.wasmFeature("validate_message", "Message validation");
+        validate(type, payload);
       }
-      pendingCancel.reject(new Error("Action cancel interrupted by disconnect; retry after reconnect"));
+    } catch (error) {
+      throw BridgeError.from(error, "invalid_input", { target: type });
     }
-    this.pendingActionCancels.clear();
+    return payload;
+  }
 
-    for (const pendingCli of this.pendingCliCalls.values()) {
-      if (pendingCli.timeout) {
-        clearTimeout(pendingCli.timeout);
-      }
-      pendingCli.reject(new Error("CLI request interrupted by disconnect; retry after reconnect"));
+  private async wasmFeature<K extends keyof WasmProtocol>(
+    name: K,
+    feature: string
+  ): Promise<NonNullable<WasmProtocol[K]>> {
+    const protocol = await this.protocolPromise;
+    const member = protocol[name];
+    if (!member) {
+      throw new BridgeError("unsupported", `${feature} requires the bridge_wasm module`);
     }
-    this.pendingCliCalls.clear();
+    return member as NonNullable<WasmProtocol[K]>;
   }
 
-  private findPendingCliCall(id?: string): PendingCliCall | undefined {
-    if (id && this.pendingCliCalls.has(id)) {
-      return this.pendingCliCalls.get(id);
+  /**
+   * Sends `message`, split into `fragment` frames when its JSON text is longer
+   * than `fragmentSize` characters (Unicode code points, as the Rust core
+   * counts them). Binary (CBOR) frames are never split.
+   * Until the connection is `open` again, frames go through the outbound queue
+   * when one is configured, so they cannot overtake older queued frames.
+   * `bypassQueue` is for the frames that restore state while rebinding.
+   * Resolves to whether the frame went out now rather than being queued.
+   */
+  private async sendEnvelope(message: JsonObject, fragmentSize?: number, bypassQueue = false): Promise<boolean> {
+    const codec = await this.codecPromise;
+    const protocol = await this.protocolPromise;
+    if (!hasValidOpEnvelope(message)) {
+      throw new BridgeError("encode_failed", "Failed to build a valid protocol message");
     }
-    if (this.pendingCliCalls.size === 1) {
-      return this.pendingCliCalls.values().next().value;
+    const frame: QueuedFrame = { op: message.op, encoded: codec.encode(message), fragmentSize };
+    const queueFirst =
+      !bypassQueue && this.state !== "open" && (this.options.outboundQueue !== undefined || this.queuedFrames.size > 0);
+    if (queueFirst || !this.ws || this.ws.readyState !== OPEN) {
+      this.enqueue(frame, frameTarget(message));
+      return false;
     }
-    return undefined;
+    this.transmit(this.ws, frame, codec, protocol);
+    return true;
   }
 
-  private async sendEnvelope(message: JsonObject): Promise<void> {
-    if (!this.ws || this.ws.readyState !== OPEN) {
-      throw new Error("WebSocket is not connected");
+  private transmit(ws: WebSocketLike, frame: QueuedFrame, codec: BridgeCodec, protocol: WasmProtocol): void {
+    const { encoded, fragmentSize } = frame;
+    // A string never has more characters than UTF-16 units, so `length` only
+    // rules out splitting; the Rust core counts the characters.
+    if (!fragmentSize || fragmentSize <= 0 || typeof encoded !== "string" || encoded.length <= fragmentSize) {
+      ws.send(encoded);
+      return;
     }
-    if (!hasValidOpEnvelope(message)) {
-      throw new Error("Failed to build a valid protocol message");
+    const fragments = protocol.build_fragments(encoded, randomId(frame.op), fragmentSize);
+    if (fragments.length === 1) {
+      ws.send(encoded);
+      return;
+    }
+    for (const fragment of fragments) {
+      ws.send(codec.encode(fragment));
     }
-    this.ws.send(this.codec.encode(message));
   }
 
-  private async sendWithProtocol(build: (protocol: WasmProtocol) => JsonObject): Promise<void> {
-    const protocol = await this.protocolPromise;
-    let message = build(protocol);
+  /**
+   * Holds a frame sent while the connection is being re-established, as the
+   * `outboundQueue` policies allow. Throws when no policy covers it.
+   */
+  private enqueue(frame: QueuedFrame, target: string | undefined): void {
+    const state = this.state;
+    if (!this.outbound || (state !== "connecting" && state !== "rebinding" && state !== "reconnect_wait")) {
+      throw new BridgeError("disconnected", "WebSocket is not connected", { op: frame.op });
+    }
+    let admission: BridgeQueueAdmission;
+    try {
+      admission = this.outbound.offer(frame.op, target, encodedBytes(frame.encoded));
+    } catch (error) {
+      throw BridgeError.from(error, "disconnected", { op: frame.op, target });
+    }
+    switch (admission.action) {
+      case "refused":
+        throw new BridgeError("disconnected", "WebSocket is not connected", { op: frame.op });
+      case "dropped":
+        return;
+      case "queued":
+        if (admission.replaces !== undefined) {
+          this.queuedFrames.delete(admission.replaces);
+        }
+        this.queuedFrames.set(admission.id, frame);
+    }
+  }
 
-    // Some wasm-bindgen return paths can produce non-plain objects in browser builds.
-    // If no op envelope is detected, retry using the pure TS fallback protocol.
-    if (!hasValidOpEnvelope(message)) {
-      message = build(fallbackProtocol);
+  /**
+   * Sends the frames queued while the socket was down, oldest first. It runs
+   * synchronously, so the caller can open the connection before any newer
+   * frame gets a chance to be queued behind them.
+   */
+  private flushOutbound(codec: BridgeCodec, protocol: WasmProtocol): void {
+    const queue = this.outbound;
+    if (!queue) {
+      return;
     }
-    if (!hasValidOpEnvelope(message)) {
-      throw new Error("Failed to build a valid protocol message");
+    for (let next = queue.front(); next; next = queue.front()) {
+      const ws = this.ws;
+      if (!ws || ws.readyState !== OPEN) {
+        return;
+      }
+      // Removed before sending, so a frame is never sent twice.
+      queue.remove(next.id);
+      const frame = this.queuedFrames.get(next.id);
+      this.queuedFrames.delete(next.id);
+      if (frame) {
+        this.transmit(ws, frame, codec, protocol);
+      }
     }
+  }
 
-    await this.sendEnvelope(message);
+  private async sendWithProtocol(
+    build: (protocol: WasmProtocol) => JsonObject,
+    fragmentSize?: number,
+    bypassQueue = false
+  ): Promise<boolean> {
+    const protocol = await this.protocolPromise;
+    let message: JsonObject;
+    try {
+      message = build(protocol);
+    } catch (error) {
+      throw BridgeError.from(error, "invalid_input");
+    }
+    return this.sendEnvelope(message, fragmentSize, bypassQueue);
   }
 }
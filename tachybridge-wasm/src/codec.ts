@@ -1,5 +1,10 @@
-import { decodeCbor, encodeCbor } from "./cbor.js";
-import type { BridgeCodec, BridgeCodecOption, BridgeIncomingMessage, JsonObject } from "./types.js";
+import { BridgeError } from "./errors.js";
+import type { BridgeCodec, BridgeCodecOption, BridgeIncomingMessage, JsonObject, WasmProtocol } from "./types.js";
+
+type CborBackend = {
+  encode: (value: unknown) => Uint8Array;
+  decode: (input: Uint8Array | ArrayBuffer) => unknown;
+};
 
 const utf8Decoder = new TextDecoder();
 
@@ -13,7 +18,7 @@ function decodeJsonLike(input: unknown): BridgeIncomingMessage {
   if (input instanceof ArrayBuffer) {
     return JSON.parse(utf8Decoder.decode(new Uint8Array(input))) as BridgeIncomingMessage;
   }
-  throw new Error("Unsupported JSON payload type");
+  throw new BridgeError("protocol_violation", "Unsupported JSON payload type");
 }
 
 export const jsonCodec: BridgeCodec = {
@@ -26,52 +31,82 @@ export const jsonCodec: BridgeCodec = {
   }
 };
 
-export const cborCodec: BridgeCodec = {
-  name: "cbor",
-  encode(message) {
-    return encodeCbor(message);
-  },
-  decode(payload) {
-    if (payload instanceof Uint8Array || payload instanceof ArrayBuffer) {
-      return decodeCbor(payload) as BridgeIncomingMessage;
-    }
-    if (typeof payload === "string") {
-      return JSON.parse(payload) as BridgeIncomingMessage;
+function createCborCodec(backend: CborBackend): BridgeCodec {
+  return {
+    name: "cbor",
+    encode(message) {
+      return backend.encode(message);
+    },
+    decode(payload) {
+      if (payload instanceof Uint8Array || payload instanceof ArrayBuffer) {
+        return backend.decode(payload) as BridgeIncomingMessage;
+      }
+      if (typeof payload === "string") {
+        return JSON.parse(payload) as BridgeIncomingMessage;
+      }
+      throw new BridgeError("protocol_violation", "Unsupported CBOR payload type");
     }
-    throw new Error("Unsupported CBOR payload type");
-  }
-};
+  };
+}
 
-export const autoCodec: BridgeCodec = {
-  name: "auto",
-  encode(message) {
-    // Keep requests maximally compatible by defaulting to JSON text on transmit.
-    return jsonCodec.encode(message);
-  },
-  decode(payload) {
-    if (typeof payload === "string") {
-      return decodeJsonLike(payload);
-    }
-    if (payload instanceof Uint8Array || payload instanceof ArrayBuffer) {
-      try {
-        return decodeCbor(payload) as BridgeIncomingMessage;
-      } catch {
+function createAutoCodec(cbor: BridgeCodec): BridgeCodec {
+  return {
+    name: "auto",
+    encode(message) {
+      // Keep requests maximally compatible by defaulting to JSON text on transmit.
+      return jsonCodec.encode(message);
+    },
+    decode(payload) {
+      if (typeof payload === "string") {
         return decodeJsonLike(payload);
       }
+      if (payload instanceof Uint8Array || payload instanceof ArrayBuffer) {
+        try {
+          return cbor.decode(payload);
+        } catch {
+          return decodeJsonLike(payload);
+        }
+      }
+      return decodeJsonLike(payload);
     }
-    return decodeJsonLike(payload);
+  };
+}
+
+function unbound(): never {
+  throw new BridgeError("unsupported", "CBOR frames require the bridge_wasm module");
+}
+
+// Selects the Rust CBOR codec; `resolveCodec` binds it to a loaded module.
+export const cborCodec: BridgeCodec = createCborCodec({ encode: unbound, decode: unbound });
+
+export const autoCodec: BridgeCodec = createAutoCodec(cborCodec);
+
+function wasmCborCodec(protocol: WasmProtocol | undefined): BridgeCodec | undefined {
+  const encode = protocol?.encode_cbor;
+  const decode = protocol?.decode_cbor;
+  if (!encode || !decode) {
+    return undefined;
   }
-};
+  return createCborCodec({
+    encode,
+    decode: (input) => decode(input instanceof Uint8Array ? input : new Uint8Array(input))
+  });
+}
 
-export function resolveCodec(codecOption: BridgeCodecOption | undefined): BridgeCodec {
+/**
+ * Resolves a codec option. The built-in `cbor`/`auto` codecs are bound to the
+ * Rust CBOR codec of `protocol`; without it, CBOR frames fail as `unsupported`.
+ */
+export function resolveCodec(codecOption: BridgeCodecOption | undefined, protocol?: WasmProtocol): BridgeCodec {
   if (!codecOption || codecOption === "json") {
     return jsonCodec;
   }
-  if (codecOption === "cbor") {
-    return cborCodec;
+  if (codecOption === "cbor" || codecOption === cborCodec) {
+    return wasmCborCodec(protocol) ?? cborCodec;
   }
-  if (codecOption === "auto") {
-    return autoCodec;
+  if (codecOption === "auto" || codecOption === autoCodec) {
+    const cbor = wasmCborCodec(protocol);
+    return cbor ? createAutoCodec(cbor) : autoCodec;
   }
   return codecOption;
 }
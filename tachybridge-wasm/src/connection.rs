@@ -0,0 +1,265 @@
+//! Connection lifecycle of a client.
+//!
+//! - `connect`: any state → `connecting`
+//! - `socket_open`: `connecting` → `rebinding`
+//! - `rebound`: `rebinding` → `open`
+//! - `schedule_reconnect`: `connecting`, `rebinding` or `open` → `reconnect_wait`
+//! - `attempt`: `reconnect_wait` → `connecting`
+//! - `give_up`: any state but `idle` and `closed` → `failed`
+//! - `close`: any state → `closed`
+//!
+//! [`Machine::apply`] rejects every other event, e.g. a rebind that finishes
+//! after the application closed the client.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum State {
+    Idle,
+    Connecting,
+    /// The socket is open and subscriptions, advertisements and the status
+    /// level are being restored.
+    Rebinding,
+    Open,
+    ReconnectWait,
+    /// Closed by the application.
+    Closed,
+    /// Lost, and no reconnect will follow.
+    Failed,
+}
+
+impl State {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Idle => "idle",
+            Self::Connecting => "connecting",
+            Self::Rebinding => "rebinding",
+            Self::Open => "open",
+            Self::ReconnectWait => "reconnect_wait",
+            Self::Closed => "closed",
+            Self::Failed => "failed",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Connect,
+    SocketOpen,
+    Rebound,
+    ScheduleReconnect,
+    Attempt,
+    GiveUp,
+    Close,
+}
+
+impl Event {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "connect" => Some(Self::Connect),
+            "socket_open" => Some(Self::SocketOpen),
+            "rebound" => Some(Self::Rebound),
+            "schedule_reconnect" => Some(Self::ScheduleReconnect),
+            "attempt" => Some(Self::Attempt),
+            "give_up" => Some(Self::GiveUp),
+            "close" => Some(Self::Close),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Connect => "connect",
+            Self::SocketOpen => "socket_open",
+            Self::Rebound => "rebound",
+            Self::ScheduleReconnect => "schedule_reconnect",
+            Self::Attempt => "attempt",
+            Self::GiveUp => "give_up",
+            Self::Close => "close",
+        }
+    }
+}
+
+fn next(state: State, event: Event) -> Option<State> {
+    use State::*;
+    match (state, event) {
+        (_, Event::Connect) => Some(Connecting),
+        (_, Event::Close) => Some(Closed),
+        (Connecting, Event::SocketOpen) => Some(Rebinding),
+        (Rebinding, Event::Rebound) => Some(Open),
+        (Connecting | Rebinding | Open, Event::ScheduleReconnect) => Some(ReconnectWait),
+        (ReconnectWait, Event::Attempt) => Some(Connecting),
+        (Connecting | Rebinding | Open | ReconnectWait | Failed, Event::GiveUp) => Some(Failed),
+        _ => None,
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Transition {
+    pub from: State,
+    pub to: State,
+}
+
+#[derive(Debug)]
+pub struct Machine {
+    state: State,
+}
+
+impl Default for Machine {
+    fn default() -> Self {
+        Self { state: State::Idle }
+    }
+}
+
+impl Machine {
+    pub fn state(&self) -> State {
+        self.state
+    }
+
+    pub fn can(&self, event: Event) -> bool {
+        next(self.state, event).is_some()
+    }
+
+    /// Applies `event`; `None` if the state does not change.
+    pub fn apply(&mut self, event: Event) -> Result<Option<Transition>, BridgeError> {
+        let to = next(self.state, event).ok_or_else(|| {
+            BridgeError::invalid_input(format!(
+                "`{}` is not valid in connection state `{}`",
+                event.as_str(),
+                self.state.as_str()
+            ))
+        })?;
+        if to == self.state {
+            return Ok(None);
+        }
+        let from = std::mem::replace(&mut self.state, to);
+        Ok(Some(Transition { from, to }))
+    }
+}
+
+fn parse_event(event: &str) -> Result<Event, JsValue> {
+    Event::parse(event).ok_or_else(|| {
+        BridgeError::invalid_input(format!("unknown connection event `{event}`"))
+            .with_path("event")
+            .into()
+    })
+}
+
+/// Connection state of one client; see [`Machine`].
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct ConnectionStateMachine {
+    inner: Machine,
+}
+
+#[wasm_bindgen]
+impl ConnectionStateMachine {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> ConnectionStateMachine {
+        ConnectionStateMachine::default()
+    }
+
+    pub fn state(&self) -> String {
+        self.inner.state().as_str().to_owned()
+    }
+
+    pub fn can(&self, event: String) -> Result<bool, JsValue> {
+        Ok(self.inner.can(parse_event(&event)?))
+    }
+
+    /// Returns `{ from, to }`, or `undefined` if the state does not change.
+    /// Throws for events that are not valid in the current state.
+    pub fn apply(&mut self, event: String) -> Result<JsValue, JsValue> {
+        match self.inner.apply(parse_event(&event)?)? {
+            Some(transition) => crate::to_js_object(&transition),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(machine: &mut Machine, events: &[Event]) -> Vec<State> {
+        events
+            .iter()
+            .map(|event| {
+                machine.apply(*event).unwrap();
+                machine.state()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn connects_and_reconnects() {
+        let mut machine = Machine::default();
+        assert_eq!(machine.state(), State::Idle);
+        assert_eq!(
+            run(
+                &mut machine,
+                &[
+                    Event::Connect,
+                    Event::SocketOpen,
+                    Event::Rebound,
+                    Event::ScheduleReconnect,
+                    Event::Attempt,
+                    Event::ScheduleReconnect,
+                    Event::Attempt,
+                    Event::SocketOpen,
+                    Event::Rebound,
+                    Event::Close,
+                ]
+            ),
+            [
+                State::Connecting,
+                State::Rebinding,
+                State::Open,
+                State::ReconnectWait,
+                State::Connecting,
+                State::ReconnectWait,
+                State::Connecting,
+                State::Rebinding,
+                State::Open,
+                State::Closed,
+            ]
+        );
+    }
+
+    #[test]
+    fn gives_up_and_connects_again() {
+        let mut machine = Machine::default();
+        run(&mut machine, &[Event::Connect, Event::GiveUp]);
+        assert_eq!(machine.state(), State::Failed);
+        assert_eq!(machine.apply(Event::GiveUp).unwrap(), None);
+        assert_eq!(
+            machine.apply(Event::Connect).unwrap(),
+            Some(Transition {
+                from: State::Failed,
+                to: State::Connecting
+            })
+        );
+    }
+
+    #[test]
+    fn rejects_events_from_other_states() {
+        let mut machine = Machine::default();
+        let err = machine.apply(Event::SocketOpen).unwrap_err();
+        assert_eq!(
+            err.message,
+            "`socket_open` is not valid in connection state `idle`"
+        );
+        assert_eq!(machine.state(), State::Idle);
+
+        run(&mut machine, &[Event::Connect, Event::Close]);
+        // A rebind finishing after `close` must not reopen the connection.
+        assert!(!machine.can(Event::Rebound));
+        assert!(!machine.can(Event::ScheduleReconnect));
+        assert!(!machine.can(Event::Attempt));
+        assert!(machine.can(Event::Connect));
+    }
+}
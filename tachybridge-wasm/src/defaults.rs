@@ -0,0 +1,247 @@
+//! Default message instances built from registered schemas.
+
+use wasm_bindgen::prelude::*;
+
+use crate::cbor::{self, CborValue};
+use crate::error::BridgeError;
+use crate::msgdef::{self, ArrayKind, Field, FieldType, Literal, MessageSet, Primitive};
+use crate::schema;
+
+/// Nesting limit, which also stops self-referencing definitions.
+const MAX_DEPTH: usize = 64;
+
+fn literal(value: &Literal) -> CborValue {
+    match value {
+        Literal::Bool(flag) => CborValue::Bool(*flag),
+        Literal::Int(n) if *n >= 0 => CborValue::Unsigned(*n as u64),
+        Literal::Int(n) => CborValue::Negative((-1 - *n) as u64),
+        Literal::Float(f) => CborValue::Float(*f),
+        Literal::String(text) => CborValue::Text(text.clone()),
+        Literal::Array(items) => CborValue::Array(items.iter().map(literal).collect()),
+    }
+}
+
+fn primitive_default(primitive: Primitive) -> CborValue {
+    match primitive {
+        Primitive::Bool => CborValue::Bool(false),
+        Primitive::Float32 | Primitive::Float64 => CborValue::Float(0.0),
+        Primitive::String | Primitive::WString => CborValue::Text(String::new()),
+        _ => CborValue::Unsigned(0),
+    }
+}
+
+struct Defaults<'d> {
+    defs: &'d MessageSet,
+}
+
+impl Defaults<'_> {
+    fn lookup(&self, name: &str, depth: usize) -> Result<&msgdef::MessageDef, String> {
+        if depth > MAX_DEPTH {
+            return Err(format!("{name} is nested too deeply"));
+        }
+        self.defs
+            .get(name)
+            .ok_or_else(|| format!("no schema registered for {name}"))
+    }
+
+    fn message(&self, name: &str, depth: usize) -> Result<CborValue, String> {
+        let def = self.lookup(name, depth)?;
+        def.fields
+            .iter()
+            .map(|field| {
+                Ok((
+                    CborValue::Text(field.name.clone()),
+                    self.field(field, depth)?,
+                ))
+            })
+            .collect::<Result<Vec<_>, String>>()
+            .map(CborValue::Map)
+    }
+
+    fn field(&self, field: &Field, depth: usize) -> Result<CborValue, String> {
+        if let Some(value) = &field.default {
+            return Ok(literal(value));
+        }
+        let single = || match &field.ty {
+            FieldType::Primitive(primitive) => Ok(primitive_default(*primitive)),
+            FieldType::Message(name) => self.message(name, depth + 1),
+        };
+        match field.array {
+            ArrayKind::Single => single(),
+            ArrayKind::Fixed(n) => Ok(CborValue::Array(vec![single()?; n])),
+            ArrayKind::Sequence | ArrayKind::Bounded(_) => Ok(CborValue::Array(Vec::new())),
+        }
+    }
+
+    fn merge(&self, name: &str, partial: &CborValue, depth: usize) -> Result<CborValue, String> {
+        let def = self.lookup(name, depth)?;
+        // Non-objects are passed through for validation to report.
+        let CborValue::Map(entries) = partial else {
+            return Ok(partial.clone());
+        };
+        let given = |name: &str| {
+            entries
+                .iter()
+                .find(|(key, _)| matches!(key, CborValue::Text(k) if k == name))
+                .map(|(_, value)| value)
+        };
+        let mut out = Vec::with_capacity(def.fields.len());
+        for field in &def.fields {
+            let value = match (given(&field.name), &field.ty) {
+                (None, _) => self.field(field, depth)?,
+                (Some(value), FieldType::Message(nested)) => match (field.array, value) {
+                    (ArrayKind::Single, _) => self.merge(nested, value, depth + 1)?,
+                    (_, CborValue::Array(items)) => CborValue::Array(
+                        items
+                            .iter()
+                            .map(|item| self.merge(nested, item, depth + 1))
+                            .collect::<Result<_, _>>()?,
+                    ),
+                    _ => value.clone(),
+                },
+                (Some(value), FieldType::Primitive(_)) => value.clone(),
+            };
+            out.push((CborValue::Text(field.name.clone()), value));
+        }
+        // Keep unknown keys rather than dropping them silently.
+        out.extend(
+            entries
+                .iter()
+                .filter(|(key, _)| {
+                    !matches!(key, CborValue::Text(k) if def.fields.iter().any(|f| f.name == *k))
+                })
+                .cloned(),
+        );
+        Ok(CborValue::Map(out))
+    }
+}
+
+fn canonical(type_name: &str) -> Result<String, String> {
+    msgdef::canonical_name(type_name).ok_or_else(|| format!("invalid type name {type_name}"))
+}
+
+/// Fully populated default instance of `type_name`: zeros, empty strings,
+/// declared default values, fixed-size arrays filled and sequences empty.
+pub fn default_value(type_name: &str, defs: &MessageSet) -> Result<CborValue, String> {
+    Defaults { defs }.message(&canonical(type_name)?, 0)
+}
+
+/// Fills every field missing from `partial`, recursing into nested messages.
+pub fn merge(type_name: &str, partial: &CborValue, defs: &MessageSet) -> Result<CborValue, String> {
+    Defaults { defs }.merge(&canonical(type_name)?, partial, 0)
+}
+
+/// Default instance of a registered message type.
+#[wasm_bindgen]
+pub fn default_message(msg_type: String) -> Result<JsValue, JsValue> {
+    let value = schema::with_registry(|defs| default_value(&msg_type, defs))
+        .map_err(BridgeError::invalid_input)?;
+    cbor::value_to_js(&value)
+}
+
+/// `partial` with every missing field filled from the registered defaults.
+#[wasm_bindgen]
+pub fn merge_with_defaults(msg_type: String, partial: JsValue) -> Result<JsValue, JsValue> {
+    let partial = cbor::js_to_value(&partial, 0)?;
+    let value = schema::with_registry(|defs| merge(&msg_type, &partial, defs))
+        .map_err(BridgeError::invalid_input)?;
+    cbor::value_to_js(&value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbor::TypedArray;
+
+    fn text(s: &str) -> CborValue {
+        CborValue::Text(s.to_owned())
+    }
+
+    fn map(entries: Vec<(&str, CborValue)>) -> CborValue {
+        CborValue::Map(entries.into_iter().map(|(k, v)| (text(k), v)).collect())
+    }
+
+    fn defs() -> MessageSet {
+        msgdef::parse_full_text(
+            "demo/Goal",
+            "geometry_msgs/Point target\n\
+             geometry_msgs/Point[2] corners\n\
+             geometry_msgs/Point[] path\n\
+             string frame 'map'\n\
+             int32 retries -1\n\
+             bool strict\n\
+             float32[3] weights [1, 2, 3]\n\
+             uint8 MODE_FAST=1\n\
+             ===\n\
+             MSG: geometry_msgs/Point\n\
+             float64 x\nfloat64 y\n",
+        )
+        .unwrap()
+    }
+
+    fn point(x: f64, y: f64) -> CborValue {
+        map(vec![("x", CborValue::Float(x)), ("y", CborValue::Float(y))])
+    }
+
+    #[test]
+    fn builds_fully_populated_defaults() {
+        let floats = |values: &[f64]| {
+            CborValue::Array(values.iter().map(|f| CborValue::Float(*f)).collect())
+        };
+        assert_eq!(
+            default_value("demo/Goal", &defs()).unwrap(),
+            map(vec![
+                ("target", point(0.0, 0.0)),
+                (
+                    "corners",
+                    CborValue::Array(vec![point(0.0, 0.0), point(0.0, 0.0)])
+                ),
+                ("path", CborValue::Array(vec![])),
+                ("frame", text("map")),
+                ("retries", CborValue::Negative(0)),
+                ("strict", CborValue::Bool(false)),
+                ("weights", floats(&[1.0, 2.0, 3.0])),
+            ])
+        );
+    }
+
+    #[test]
+    fn merges_sparse_objects_recursively() {
+        let weights = TypedArray::Float32(vec![4.0, 5.0, 6.0]).to_cbor();
+        let partial = map(vec![
+            ("target", map(vec![("y", CborValue::Float(2.0))])),
+            ("path", CborValue::Array(vec![map(vec![])])),
+            ("weights", weights.clone()),
+            ("extra", CborValue::Null),
+        ]);
+        let merged = merge("demo/msg/Goal", &partial, &defs()).unwrap();
+        let CborValue::Map(entries) = merged else {
+            panic!("expected a map")
+        };
+        let get = |name: &str| {
+            entries
+                .iter()
+                .find(|(k, _)| *k == text(name))
+                .map(|(_, v)| v.clone())
+                .unwrap()
+        };
+        assert_eq!(get("target"), point(0.0, 2.0));
+        assert_eq!(get("path"), CborValue::Array(vec![point(0.0, 0.0)]));
+        assert_eq!(get("weights"), weights);
+        assert_eq!(get("frame"), text("map"));
+        assert_eq!(get("extra"), CborValue::Null);
+        assert_eq!(entries.len(), 8);
+    }
+
+    #[test]
+    fn reports_missing_and_recursive_types() {
+        assert_eq!(
+            default_value("demo/Unknown", &defs()).unwrap_err(),
+            "no schema registered for demo/msg/Unknown"
+        );
+        let recursive = msgdef::parse_full_text("demo/Node", "Node child\n").unwrap();
+        assert!(default_value("demo/Node", &recursive)
+            .unwrap_err()
+            .contains("nested too deeply"));
+    }
+}
@@ -0,0 +1,129 @@
+//! Errors thrown across the wasm boundary.
+//!
+//! Every fallible export throws a JS `Error` named `BridgeError` carrying a
+//! machine-readable `code` and, where known, the rosbridge `op`, the field
+//! `path` and the `requestId` it is about. `BridgeClientCore` rethrows them
+//! as instances of its `BridgeError` class.
+
+use std::fmt;
+
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ErrorCode {
+    /// An argument or message does not make sense, e.g. an unknown type name.
+    InvalidInput,
+    /// A value could not be converted for the wire or for JS.
+    EncodeFailed,
+    /// A message does not match its registered schema.
+    SchemaViolation,
+    /// Data received from the bridge is malformed.
+    ProtocolViolation,
+    Timeout,
+    Disconnected,
+}
+
+impl ErrorCode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::InvalidInput => "invalid_input",
+            Self::EncodeFailed => "encode_failed",
+            Self::SchemaViolation => "schema_violation",
+            Self::ProtocolViolation => "protocol_violation",
+            Self::Timeout => "timeout",
+            Self::Disconnected => "disconnected",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BridgeError {
+    pub code: ErrorCode,
+    pub message: String,
+    pub op: Option<String>,
+    pub path: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl BridgeError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            op: None,
+            path: None,
+            request_id: None,
+        }
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn encode_failed(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::EncodeFailed, message)
+    }
+
+    pub fn protocol_violation(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::ProtocolViolation, message)
+    }
+
+    pub fn with_op(mut self, op: impl Into<String>) -> Self {
+        self.op = Some(op.into());
+        self
+    }
+
+    pub fn with_path(mut self, path: impl Into<String>) -> Self {
+        self.path = Some(path.into());
+        self
+    }
+
+    pub fn with_request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+}
+
+impl fmt::Display for BridgeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+impl std::error::Error for BridgeError {}
+
+impl From<BridgeError> for JsValue {
+    fn from(error: BridgeError) -> JsValue {
+        let js_error = js_sys::Error::new(&error.message);
+        js_error.set_name("BridgeError");
+        let target: JsValue = js_error.into();
+        let set = |key: &str, value: Option<&str>| {
+            if let Some(value) = value {
+                let _ = js_sys::Reflect::set(&target, &key.into(), &value.into());
+            }
+        };
+        set("code", Some(error.code.as_str()));
+        set("op", error.op.as_deref());
+        set("path", error.path.as_deref());
+        set("requestId", error.request_id.as_deref());
+        target
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn carries_context() {
+        let error = BridgeError::invalid_input("goal status 2 is not terminal")
+            .with_op("action_result")
+            .with_path("status")
+            .with_request_id("goal-1");
+        assert_eq!(error.code.as_str(), "invalid_input");
+        assert_eq!(error.to_string(), "goal status 2 is not terminal");
+        assert_eq!(error.op.as_deref(), Some("action_result"));
+        assert_eq!(error.path.as_deref(), Some("status"));
+        assert_eq!(error.request_id.as_deref(), Some("goal-1"));
+    }
+}
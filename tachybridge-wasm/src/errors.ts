@@ -0,0 +1,84 @@
+/**
+ * Why a `BridgeError` was raised. The first six mirror `ErrorCode` in the Rust
+ * core; `error` frames from the bridge keep their own code, such as
+ * `unsupported_operation`.
+ */
+export type BridgeErrorCode =
+  | "invalid_input"
+  | "encode_failed"
+  | "schema_violation"
+  | "protocol_violation"
+  | "timeout"
+  | "disconnected"
+  /** The bridge answered the request with a failure. */
+  | "request_failed"
+  /** The bridge_wasm module failed to load or lacks the feature. */
+  | "unsupported";
+
+export type BridgeErrorDetails = {
+  /** rosbridge op of the request, e.g. `call_service`. */
+  op?: string;
+  /** Field path the error is about, e.g. `linear.x`. */
+  path?: string;
+  requestId?: string;
+  /** Topic, service or action of the request. */
+  target?: string;
+};
+
+function text(value: unknown): string | undefined {
+  return typeof value === "string" ? value : undefined;
+}
+
+/** Every error the client rejects or throws with. */
+export class BridgeError extends Error {
+  readonly code: BridgeErrorCode | (string & {});
+  op?: string;
+  path?: string;
+  requestId?: string;
+  target?: string;
+
+  constructor(code: BridgeErrorCode | (string & {}), message: string, details: BridgeErrorDetails = {}) {
+    super(message);
+    this.name = "BridgeError";
+    this.code = code;
+    for (const [key, value] of Object.entries(details)) {
+      if (value !== undefined) {
+        this[key as keyof BridgeErrorDetails] = value;
+      }
+    }
+  }
+
+  /**
+   * Normalizes anything thrown by the wasm module or a socket. The Rust core already throws `{ code, op?, path?, requestId? }`
+   * errors; other values get `code`. Missing details are filled in, and extra
+   * fields such as validation `issues` are kept.
+   */
+  static from(error: unknown, code: BridgeErrorCode, details: BridgeErrorDetails = {}): BridgeError {
+    if (error instanceof BridgeError) {
+      for (const [key, value] of Object.entries(details)) {
+        const field = key as keyof BridgeErrorDetails;
+        if (error[field] === undefined && value !== undefined) {
+          error[field] = value;
+        }
+      }
+      return error;
+    }
+    const info = error !== null && typeof error === "object" ? (error as Record<string, unknown>) : {};
+    const wrapped = new BridgeError(
+      text(info.code) ?? code,
+      text(info.message) ?? (error instanceof Error ? error.message : String(error)),
+      {
+        op: text(info.op) ?? details.op,
+        path: text(info.path) ?? details.path,
+        requestId: text(info.requestId) ?? details.requestId,
+        target: text(info.target) ?? details.target
+      }
+    );
+    for (const [key, value] of Object.entries(info)) {
+      if (!(key in wrapped)) {
+        (wrapped as unknown as Record<string, unknown>)[key] = value;
+      }
+    }
+    return wrapped;
+  }
+}
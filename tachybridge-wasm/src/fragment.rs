@@ -0,0 +1,372 @@
+//! rosbridge message fragmentation.
+//!
+//! With `fragment_size` set, rosbridge splits the JSON text of a large message
+//! into `{op: "fragment", id, data, num, total}` frames. [`Assembler`] puts them
+//! back together, in any arrival order, under a timeout and a memory cap.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::json;
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum FragmentError {
+    /// `num`/`total` do not describe a valid fragment of message `id`.
+    Invalid { id: String, message: String },
+    /// Buffering the fragment would exceed the memory cap; the message is dropped.
+    TooLarge { id: String, limit: usize },
+}
+
+impl fmt::Display for FragmentError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Invalid { id, message } => write!(f, "invalid fragment of {id}: {message}"),
+            Self::TooLarge { id, limit } => {
+                write!(f, "dropped {id}: fragments exceed the {limit} byte buffer")
+            }
+        }
+    }
+}
+
+/// Memory a slot in [`Partial::parts`] takes before its fragment arrives.
+const SLOT_BYTES: usize = std::mem::size_of::<Option<String>>();
+
+struct Partial {
+    parts: Vec<Option<String>>,
+    received: usize,
+    /// Fragment data plus the slot vector, as counted against the cap.
+    bytes: usize,
+    started_ms: f64,
+}
+
+/// Buffers fragments per message id until every part has arrived.
+pub struct Assembler {
+    timeout_ms: f64,
+    max_bytes: usize,
+    buffered: usize,
+    pending: HashMap<String, Partial>,
+}
+
+impl Assembler {
+    pub fn new(timeout_ms: f64, max_bytes: usize) -> Self {
+        Self {
+            timeout_ms,
+            max_bytes,
+            buffered: 0,
+            pending: HashMap::new(),
+        }
+    }
+
+    fn drop_message(&mut self, id: &str) {
+        if let Some(partial) = self.pending.remove(id) {
+            self.buffered -= partial.bytes;
+        }
+    }
+
+    /// Adds fragment `num` of `total`; returns the full text once complete.
+    pub fn push(
+        &mut self,
+        id: &str,
+        num: usize,
+        total: usize,
+        data: String,
+        now_ms: f64,
+    ) -> Result<Option<String>, FragmentError> {
+        let invalid = |message: String| FragmentError::Invalid {
+            id: id.to_owned(),
+            message,
+        };
+        if total == 0 || num >= total {
+            return Err(invalid(format!("fragment {num} of {total}")));
+        }
+        if total == 1 {
+            return Ok(Some(data));
+        }
+        // The slots are allocated from the wire `total`, so they count
+        // against the cap before anything is allocated.
+        let (replaced, slots) = match self.pending.get(id) {
+            Some(partial) if partial.parts.len() != total => {
+                let expected = partial.parts.len();
+                self.drop_message(id);
+                return Err(invalid(format!(
+                    "expected {expected} fragments, got {total}"
+                )));
+            }
+            Some(partial) => (partial.parts[num].as_ref().map_or(0, String::len), 0),
+            None => (0, total.saturating_mul(SLOT_BYTES)),
+        };
+        let needed = (self.buffered - replaced)
+            .saturating_add(slots)
+            .saturating_add(data.len());
+        if needed > self.max_bytes {
+            self.drop_message(id);
+            return Err(FragmentError::TooLarge {
+                id: id.to_owned(),
+                limit: self.max_bytes,
+            });
+        }
+
+        let partial = self
+            .pending
+            .entry(id.to_owned())
+            .or_insert_with(|| Partial {
+                parts: vec![None; total],
+                received: 0,
+                bytes: slots,
+                started_ms: now_ms,
+            });
+        self.buffered += slots;
+        let size = data.len();
+        // A repeated fragment replaces the earlier copy.
+        match partial.parts[num].replace(data) {
+            Some(previous) => {
+                partial.bytes -= previous.len();
+                self.buffered -= previous.len();
+            }
+            None => partial.received += 1,
+        }
+        partial.bytes += size;
+        self.buffered += size;
+        if partial.received < total {
+            return Ok(None);
+        }
+
+        let partial = self.pending.remove(id).expect("partial message present");
+        self.buffered -= partial.bytes;
+        Ok(Some(partial.parts.into_iter().flatten().collect()))
+    }
+
+    /// Drops messages whose first fragment arrived more than the timeout ago
+    /// and returns their ids.
+    pub fn expire(&mut self, now_ms: f64) -> Vec<String> {
+        let mut expired: Vec<String> = self
+            .pending
+            .iter()
+            .filter(|(_, partial)| now_ms - partial.started_ms > self.timeout_ms)
+            .map(|(id, _)| id.clone())
+            .collect();
+        expired.sort();
+        for id in &expired {
+            self.drop_message(id);
+        }
+        expired
+    }
+
+    /// Time after which the oldest partial message expires.
+    pub fn next_deadline(&self) -> Option<f64> {
+        self.pending
+            .values()
+            .map(|partial| partial.started_ms + self.timeout_ms)
+            .min_by(f64::total_cmp)
+    }
+
+    pub fn clear(&mut self) {
+        self.pending.clear();
+        self.buffered = 0;
+    }
+
+    pub fn pending(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn buffered_bytes(&self) -> usize {
+        self.buffered
+    }
+}
+
+/// Splits `text` into chunks of at most `size` characters.
+pub fn split(text: &str, size: usize) -> Vec<String> {
+    let size = size.max(1);
+    let chars: Vec<char> = text.chars().collect();
+    chars
+        .chunks(size)
+        .map(|chunk| chunk.iter().collect())
+        .collect()
+}
+
+/// Reassembles rosbridge `fragment` frames.
+#[wasm_bindgen]
+pub struct FragmentAssembler {
+    inner: Assembler,
+}
+
+#[wasm_bindgen]
+impl FragmentAssembler {
+    #[wasm_bindgen(constructor)]
+    pub fn new(timeout_ms: f64, max_bytes: f64) -> FragmentAssembler {
+        FragmentAssembler {
+            inner: Assembler::new(timeout_ms, max_bytes as usize),
+        }
+    }
+
+    /// Adds one fragment and returns the reassembled message text once all
+    /// fragments of `id` have arrived.
+    pub fn push(
+        &mut self,
+        id: String,
+        num: u32,
+        total: u32,
+        data: String,
+        now_ms: f64,
+    ) -> Result<Option<String>, JsValue> {
+        self.inner
+            .push(&id, num as usize, total as usize, data, now_ms)
+            .map_err(|e| {
+                BridgeError::protocol_violation(e.to_string())
+                    .with_op("fragment")
+                    .with_request_id(id)
+                    .into()
+            })
+    }
+
+    /// Drops timed-out messages and returns their ids.
+    pub fn expire(&mut self, now_ms: f64) -> Vec<String> {
+        self.inner.expire(now_ms)
+    }
+
+    /// Time after which the oldest partial message expires, if any.
+    pub fn next_deadline(&self) -> Option<f64> {
+        self.inner.next_deadline()
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    /// Number of partially received messages.
+    #[wasm_bindgen(getter)]
+    pub fn pending(&self) -> usize {
+        self.inner.pending()
+    }
+
+    /// Bytes held by partially received messages.
+    #[wasm_bindgen(getter)]
+    pub fn buffered_bytes(&self) -> usize {
+        self.inner.buffered_bytes()
+    }
+}
+
+/// Splits the encoded frame `text` into `fragment` frames of at most `size`
+/// characters (Unicode scalar values) of data each. A `text` that fits in one
+/// fragment yields a single frame.
+#[wasm_bindgen]
+pub fn build_fragments(text: String, id: String, size: u32) -> Result<Vec<JsValue>, JsValue> {
+    let parts = split(&text, size as usize);
+    let total = parts.len();
+    parts
+        .into_iter()
+        .enumerate()
+        .map(|(num, data)| {
+            crate::to_js_object(&json!({
+                "op": "fragment",
+                "id": id,
+                "data": data,
+                "num": num,
+                "total": total,
+            }))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reassembles_out_of_order_fragments() {
+        let mut assembler = Assembler::new(1000.0, 1024);
+        assert_eq!(
+            assembler.push("m", 2, 3, "stale".into(), 0.0).unwrap(),
+            None
+        );
+        assert_eq!(
+            assembler.push("m", 0, 3, "{\"a\":".into(), 1.0).unwrap(),
+            None
+        );
+        assert_eq!(assembler.pending(), 1);
+        assert_eq!(assembler.push("m", 2, 3, "c\"}".into(), 2.0).unwrap(), None);
+        assert_eq!(
+            assembler.push("m", 1, 3, "\"b".into(), 3.0).unwrap(),
+            Some("{\"a\":\"bc\"}".into())
+        );
+        assert_eq!(assembler.pending(), 0);
+        assert_eq!(assembler.buffered_bytes(), 0);
+        assert_eq!(
+            assembler.push("single", 0, 1, "{}".into(), 4.0).unwrap(),
+            Some("{}".into())
+        );
+    }
+
+    #[test]
+    fn rejects_inconsistent_fragments() {
+        let mut assembler = Assembler::new(1000.0, 1024);
+        assert!(matches!(
+            assembler.push("m", 3, 3, "x".into(), 0.0),
+            Err(FragmentError::Invalid { .. })
+        ));
+        assembler.push("m", 0, 3, "x".into(), 0.0).unwrap();
+        let err = assembler.push("m", 1, 4, "y".into(), 0.0).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "invalid fragment of m: expected 3 fragments, got 4"
+        );
+        assert_eq!(assembler.pending(), 0);
+    }
+
+    #[test]
+    fn enforces_timeout_and_memory_cap() {
+        let limit = 4 * SLOT_BYTES + 8;
+        let mut assembler = Assembler::new(100.0, limit);
+        assembler.push("old", 0, 2, "1234".into(), 0.0).unwrap();
+        assembler.push("new", 0, 2, "12".into(), 50.0).unwrap();
+        assert_eq!(
+            assembler.push("new", 1, 2, "12345".into(), 60.0),
+            Err(FragmentError::TooLarge {
+                id: "new".into(),
+                limit
+            })
+        );
+        assert_eq!(assembler.buffered_bytes(), 2 * SLOT_BYTES + 4);
+        assert_eq!(assembler.next_deadline(), Some(100.0));
+        assert!(assembler.expire(100.0).is_empty());
+        assert_eq!(assembler.expire(101.0), ["old"]);
+        assert_eq!(assembler.buffered_bytes(), 0);
+        assert_eq!(assembler.next_deadline(), None);
+    }
+
+    #[test]
+    fn counts_slots_before_allocating_them() {
+        let mut assembler = Assembler::new(100.0, 1 << 20);
+        assert_eq!(
+            assembler.push("huge", 0, usize::MAX, "x".into(), 0.0),
+            Err(FragmentError::TooLarge {
+                id: "huge".into(),
+                limit: 1 << 20
+            })
+        );
+        assert_eq!(assembler.pending(), 0);
+        assert_eq!(assembler.buffered_bytes(), 0);
+    }
+
+    #[test]
+    fn repeated_fragments_replace_their_bytes() {
+        let limit = 2 * SLOT_BYTES + 6;
+        let mut assembler = Assembler::new(100.0, limit);
+        assembler.push("m", 0, 2, "1234".into(), 0.0).unwrap();
+        assembler.push("m", 0, 2, "123456".into(), 1.0).unwrap();
+        assert_eq!(assembler.buffered_bytes(), limit);
+        assert_eq!(
+            assembler.push("m", 1, 2, "".into(), 2.0).unwrap(),
+            Some("123456".into())
+        );
+    }
+
+    #[test]
+    fn splits_on_character_boundaries() {
+        assert_eq!(split("añb€c", 2), ["añ", "b€", "c"]);
+        assert_eq!(split("", 4), Vec::<String>::new());
+    }
+}
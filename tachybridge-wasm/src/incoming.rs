@@ -0,0 +1,939 @@
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use wasm_bindgen::prelude::*;
+
+use crate::cbor::{self, CborValue};
+use crate::error::BridgeError;
+
+/// Normalized view of a frame received from the bridge.
+///
+/// `kind` is the discriminator on the JS side. Payload-carrying fields keep the
+/// value from the original frame untouched so subscribers see exactly what the
+/// codec produced.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum IncomingEvent {
+    Publish {
+        topic: String,
+        msg: Value,
+    },
+    ServiceResponse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        service: Option<String>,
+        ok: bool,
+        values: Value,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+    },
+    /// `call_service` sent to a service this client advertised.
+    ServiceRequest {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        service: String,
+        args: Value,
+    },
+    /// `send_action_goal` sent to an action this client advertised.
+    ActionGoalRequest {
+        id: String,
+        action: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        action_type: Option<String>,
+        goal: Value,
+    },
+    /// `cancel_action_goal` for a goal of an action this client advertised.
+    ActionCancelRequest {
+        id: String,
+        action: String,
+    },
+    CliResponse {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        return_code: Option<i64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        frame: Value,
+    },
+    CancelActionResult {
+        action: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        ok: bool,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        frame: Value,
+    },
+    ActionResult {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        action: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        error: Option<String>,
+        result: Value,
+    },
+    /// Answer to `query_action_goal`. With `found`, the goal's remaining
+    /// events follow on this connection.
+    ActionGoalStatus {
+        id: String,
+        action: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        found: bool,
+    },
+    ActionEvent {
+        event: ActionEventType,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        session_id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        status: Option<i64>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        payload: Value,
+    },
+    /// Bridge log message; `id` is the id of the op that caused it.
+    Status {
+        level: String,
+        msg: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+    },
+    /// The bridge refused a frame. `id`, `request_op` and `target` identify
+    /// the request, taken from the frame or from the echoed `received` frame.
+    Error {
+        code: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        message: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        request_op: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        target: Option<String>,
+    },
+    /// rosbridge `png` compression: the whole frame as base64 PNG pixels.
+    Png {
+        data: String,
+    },
+    Fragment {
+        id: String,
+        data: String,
+        num: u32,
+        total: u32,
+    },
+    Unknown {
+        op: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ActionEventType {
+    Request,
+    Feedback,
+    Result,
+    Error,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IncomingErrorCode {
+    NotAnObject,
+    MissingOp,
+    InvalidFrame,
+}
+
+/// Why a frame could not be classified. `parse_incoming` throws it as a
+/// `protocol_violation` [`BridgeError`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct IncomingError {
+    pub code: IncomingErrorCode,
+    pub op: Option<String>,
+    /// The offending field, when known.
+    pub path: Option<String>,
+    pub message: String,
+}
+
+impl IncomingError {
+    fn new(code: IncomingErrorCode, op: Option<&str>, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            op: op.map(str::to_owned),
+            path: None,
+            message: message.into(),
+        }
+    }
+
+    fn with_path(mut self, path: &str) -> Self {
+        self.path = Some(path.to_owned());
+        self
+    }
+}
+
+#[derive(Deserialize)]
+struct PublishFrame {
+    topic: String,
+    msg: Map<String, Value>,
+}
+
+#[derive(Deserialize)]
+struct ServiceResponseFrame {
+    id: Option<String>,
+    service: Option<String>,
+    #[serde(default)]
+    result: bool,
+    values: Option<Value>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ServiceRequestFrame {
+    id: Option<String>,
+    service: String,
+    args: Option<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct ActionGoalRequestFrame {
+    id: String,
+    action: String,
+    action_type: Option<String>,
+    /// rosbridge sends the goal as `args`; `goal` mirrors `send_action_goal`.
+    #[serde(alias = "goal")]
+    args: Option<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct ActionCancelRequestFrame {
+    id: String,
+    action: String,
+}
+
+#[derive(Deserialize)]
+struct CliResponseFrame {
+    id: Option<String>,
+    success: Option<bool>,
+    return_code: Option<i64>,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CancelActionResultFrame {
+    #[serde(default)]
+    action: String,
+    session_id: Option<String>,
+    #[serde(default)]
+    result: bool,
+    error: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ActionResultFrame {
+    id: Option<String>,
+    session_id: Option<String>,
+    action: Option<String>,
+    error: Option<String>,
+    result: Option<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct ActionGoalStatusFrame {
+    id: String,
+    action: String,
+    session_id: Option<String>,
+    #[serde(default)]
+    found: bool,
+}
+
+#[derive(Deserialize)]
+struct ActionEventFrame {
+    #[serde(rename = "type")]
+    event: ActionEventType,
+    id: Option<String>,
+    session_id: Option<String>,
+    status: Option<i64>,
+    message: Option<String>,
+    feedback: Option<Map<String, Value>>,
+    result: Option<Map<String, Value>>,
+}
+
+#[derive(Deserialize)]
+struct StatusFrame {
+    level: String,
+    #[serde(default)]
+    msg: String,
+    id: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct ErrorFrame {
+    error: String,
+    #[serde(alias = "msg")]
+    message: Option<String>,
+    id: Option<String>,
+    received: Option<ReceivedEnvelope>,
+}
+
+/// The part of an echoed request needed to find who sent it.
+#[derive(Deserialize)]
+struct ReceivedEnvelope {
+    op: Option<String>,
+    id: Option<String>,
+    service: Option<String>,
+    action: Option<String>,
+    topic: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct PngFrame {
+    data: String,
+}
+
+#[derive(Deserialize)]
+struct FragmentFrame {
+    id: String,
+    data: String,
+    num: u32,
+    total: u32,
+}
+
+fn decode_frame<T: DeserializeOwned>(frame: &Value, op: &str) -> Result<T, IncomingError> {
+    T::deserialize(frame).map_err(|e| {
+        let message = e.to_string();
+        let error = IncomingError::new(IncomingErrorCode::InvalidFrame, Some(op), &message);
+        // serde names the field only when it is missing.
+        match message
+            .strip_prefix("missing field `")
+            .and_then(|rest| rest.split('`').next())
+        {
+            Some(field) => error.with_path(field),
+            None => error,
+        }
+    })
+}
+
+/// Validates a decoded frame and turns it into an [`IncomingEvent`].
+pub fn classify(frame: &Value) -> Result<IncomingEvent, IncomingError> {
+    let Some(object) = frame.as_object() else {
+        return Err(IncomingError::new(
+            IncomingErrorCode::NotAnObject,
+            None,
+            "frame must be a JSON object",
+        ));
+    };
+
+    let op = match object.get("op") {
+        Some(Value::String(op)) if !op.is_empty() => op.as_str(),
+        Some(Value::String(_)) | None => return classify_action_event(frame, object),
+        Some(_) => {
+            return Err(IncomingError::new(
+                IncomingErrorCode::InvalidFrame,
+                None,
+                "`op` must be a non-empty string",
+            )
+            .with_path("op"))
+        }
+    };
+
+    match op {
+        "publish" => {
+            let f: PublishFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::Publish {
+                topic: f.topic,
+                msg: Value::Object(f.msg),
+            })
+        }
+        "service_response" => {
+            let f: ServiceResponseFrame = decode_frame(frame, op)?;
+            // rosbridge reports failures as `values: "<error text>"`.
+            let (values, values_error) = match f.values {
+                Some(Value::String(text)) => (Value::Object(Map::new()), Some(text)),
+                Some(value @ Value::Object(_)) => (value, None),
+                _ => (Value::Object(Map::new()), None),
+            };
+            Ok(IncomingEvent::ServiceResponse {
+                id: f.id,
+                service: f.service,
+                ok: f.result,
+                values,
+                error: f.error.or(values_error),
+            })
+        }
+        "call_service" => {
+            let f: ServiceRequestFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::ServiceRequest {
+                id: f.id,
+                service: f.service,
+                args: Value::Object(f.args.unwrap_or_default()),
+            })
+        }
+        // Only a client hosting an action receives goals and cancels.
+        "send_action_goal" => {
+            let f: ActionGoalRequestFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::ActionGoalRequest {
+                id: f.id,
+                action: f.action,
+                action_type: f.action_type,
+                goal: Value::Object(f.args.unwrap_or_default()),
+            })
+        }
+        "cancel_action_goal" => {
+            let f: ActionCancelRequestFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::ActionCancelRequest {
+                id: f.id,
+                action: f.action,
+            })
+        }
+        "cli_response" => {
+            let f: CliResponseFrame = decode_frame(frame, op)?;
+            let ok = f.success != Some(false) && f.return_code.is_none_or(|code| code == 0);
+            Ok(IncomingEvent::CliResponse {
+                id: f.id,
+                ok,
+                return_code: f.return_code,
+                error: f.error,
+                frame: frame.clone(),
+            })
+        }
+        "cancel_action_result" => {
+            let f: CancelActionResultFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::CancelActionResult {
+                action: f.action,
+                session_id: f.session_id,
+                ok: f.result,
+                error: f.error,
+                frame: frame.clone(),
+            })
+        }
+        "action_result" => {
+            let f: ActionResultFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::ActionResult {
+                id: f.id,
+                session_id: f.session_id,
+                action: f.action,
+                error: f.error,
+                result: f.result.map(Value::Object).unwrap_or_else(|| frame.clone()),
+            })
+        }
+        "action_goal_status" => {
+            let f: ActionGoalStatusFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::ActionGoalStatus {
+                id: f.id,
+                action: f.action,
+                session_id: f.session_id,
+                found: f.found,
+            })
+        }
+        "status" => {
+            let f: StatusFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::Status {
+                level: f.level,
+                msg: f.msg,
+                id: f.id,
+            })
+        }
+        "error" => {
+            let f: ErrorFrame = decode_frame(frame, op)?;
+            let (request_op, received_id, target) = match f.received {
+                Some(r) => (r.op, r.id, r.service.or(r.action).or(r.topic)),
+                None => (None, None, None),
+            };
+            Ok(IncomingEvent::Error {
+                code: f.error,
+                message: f.message,
+                id: f.id.or(received_id),
+                request_op,
+                target,
+            })
+        }
+        "png" => {
+            let f: PngFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::Png { data: f.data })
+        }
+        "fragment" => {
+            let f: FragmentFrame = decode_frame(frame, op)?;
+            Ok(IncomingEvent::Fragment {
+                id: f.id,
+                data: f.data,
+                num: f.num,
+                total: f.total,
+            })
+        }
+        other => Ok(IncomingEvent::Unknown {
+            op: other.to_owned(),
+        }),
+    }
+}
+
+fn classify_action_event(
+    frame: &Value,
+    object: &Map<String, Value>,
+) -> Result<IncomingEvent, IncomingError> {
+    if !object.contains_key("type") {
+        return Err(IncomingError::new(
+            IncomingErrorCode::MissingOp,
+            None,
+            "frame has neither `op` nor an action event `type`",
+        )
+        .with_path("op"));
+    }
+
+    let f: ActionEventFrame = decode_frame(frame, "action_event")?;
+    let payload = match f.event {
+        ActionEventType::Feedback => f.feedback.map(Value::Object),
+        ActionEventType::Result => f.result.map(Value::Object),
+        ActionEventType::Request | ActionEventType::Error => None,
+    };
+    Ok(IncomingEvent::ActionEvent {
+        event: f.event,
+        id: f.id,
+        session_id: f.session_id,
+        status: f.status,
+        message: f.message,
+        payload: payload.unwrap_or_else(|| frame.clone()),
+    })
+}
+
+/// Field of the event that carries payload, and the frame field it came from
+/// (`None` meaning the whole frame).
+fn payload_source(
+    event: &IncomingEvent,
+    frame: &Value,
+) -> Option<(&'static str, Option<&'static str>)> {
+    let has_object = |key: &str| frame.get(key).is_some_and(Value::is_object);
+    match event {
+        IncomingEvent::Publish { .. } => Some(("msg", Some("msg"))),
+        IncomingEvent::ServiceResponse { .. } if has_object("values") => {
+            Some(("values", Some("values")))
+        }
+        IncomingEvent::ServiceRequest { .. } if has_object("args") => Some(("args", Some("args"))),
+        IncomingEvent::ActionGoalRequest { .. } if has_object("args") => {
+            Some(("goal", Some("args")))
+        }
+        IncomingEvent::ActionGoalRequest { .. } if has_object("goal") => {
+            Some(("goal", Some("goal")))
+        }
+        IncomingEvent::CliResponse { .. } | IncomingEvent::CancelActionResult { .. } => {
+            Some(("frame", None))
+        }
+        IncomingEvent::ActionResult { .. } if has_object("result") => {
+            Some(("result", Some("result")))
+        }
+        IncomingEvent::ActionResult { .. } => Some(("result", None)),
+        IncomingEvent::ActionEvent { event, .. } => match event {
+            ActionEventType::Feedback if has_object("feedback") => {
+                Some(("payload", Some("feedback")))
+            }
+            ActionEventType::Result if has_object("result") => Some(("payload", Some("result"))),
+            _ => Some(("payload", None)),
+        },
+        IncomingEvent::ServiceResponse { .. }
+        | IncomingEvent::ServiceRequest { .. }
+        | IncomingEvent::ActionGoalRequest { .. }
+        | IncomingEvent::ActionCancelRequest { .. }
+        | IncomingEvent::ActionGoalStatus { .. }
+        | IncomingEvent::Status { .. }
+        | IncomingEvent::Error { .. }
+        | IncomingEvent::Png { .. }
+        | IncomingEvent::Fragment { .. }
+        | IncomingEvent::Unknown { .. } => None,
+    }
+}
+
+/// Copies the top-level scalars of a JS frame into a `Value`, including the
+/// `BigInt` integers the CBOR path produces.
+///
+/// Nested objects and arrays are replaced by empty placeholders: the classifier
+/// only needs their shape, and the real payload (which may hold typed arrays
+/// that do not survive a serde round trip) is re-attached by reference.
+fn shallow_envelope(frame: &JsValue) -> Value {
+    if !frame.is_object() || js_sys::Array::is_array(frame) {
+        return Value::Null;
+    }
+    let object = js_sys::Object::from(frame.clone());
+    let mut out = Map::new();
+    for entry in js_sys::Object::entries(&object).iter() {
+        let pair = js_sys::Array::from(&entry);
+        let Some(key) = pair.get(0).as_string() else {
+            continue;
+        };
+        let value = pair.get(1);
+        let converted = if let Some(text) = value.as_string() {
+            Value::String(text)
+        } else if let Some(flag) = value.as_bool() {
+            Value::Bool(flag)
+        } else if let Some(number) = value.as_f64() {
+            if number.fract() == 0.0 && number.abs() <= i64::MAX as f64 {
+                Value::from(number as i64)
+            } else {
+                Value::from(number)
+            }
+        } else if value.is_null() {
+            Value::Null
+        } else if value.is_undefined() {
+            continue;
+        } else if js_sys::Array::is_array(&value) || js_sys::ArrayBuffer::is_view(&value) {
+            Value::Array(Vec::new())
+        } else if value.is_bigint() {
+            cbor::js_to_value(&value, 0).map_or(Value::Null, |value| integer_value(&value))
+        } else if key == "received" {
+            // `error` frames echo the refused request; its envelope tells
+            // which pending request failed.
+            shallow_envelope(&value)
+        } else {
+            Value::Object(Map::new())
+        };
+        out.insert(key, converted);
+    }
+    Value::Object(out)
+}
+
+/// JSON number for an integer the CBOR path handed over as a `BigInt`
+/// (beyond 2^53). Bignums wider than 64 bits lose precision.
+fn integer_value(value: &CborValue) -> Value {
+    let magnitude = |bytes: &[u8]| {
+        bytes
+            .iter()
+            .fold(0.0, |n, byte| n * 256.0 + f64::from(*byte))
+    };
+    match value {
+        CborValue::Unsigned(n) => Value::from(*n),
+        CborValue::Negative(n) => i64::try_from(*n)
+            .map_or_else(|_| Value::from(-1.0 - *n as f64), |n| Value::from(-1 - n)),
+        CborValue::Tag(2, inner) => match inner.as_ref() {
+            CborValue::Bytes(bytes) => Value::from(magnitude(bytes)),
+            _ => Value::Null,
+        },
+        CborValue::Tag(3, inner) => match inner.as_ref() {
+            CborValue::Bytes(bytes) => Value::from(-1.0 - magnitude(bytes)),
+            _ => Value::Null,
+        },
+        _ => Value::Null,
+    }
+}
+
+#[wasm_bindgen]
+pub fn parse_incoming(frame: JsValue) -> Result<JsValue, JsValue> {
+    let envelope = shallow_envelope(&frame);
+    let event = classify(&envelope).map_err(|e| {
+        let mut error = BridgeError::protocol_violation(e.message);
+        error.op = e.op;
+        error.path = e.path;
+        JsValue::from(error)
+    })?;
+    let out = crate::to_js_object(&event)?;
+    if let Some((key, source)) = payload_source(&event, &envelope) {
+        let payload = match source {
+            Some(field) => js_sys::Reflect::get(&frame, &JsValue::from_str(field))?,
+            None => frame.clone(),
+        };
+        js_sys::Reflect::set(&out, &JsValue::from_str(key), &payload)?;
+    }
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn classifies_publish() {
+        let event = classify(&json!({"op": "publish", "topic": "/a", "msg": {"x": 1}})).unwrap();
+        assert_eq!(
+            event,
+            IncomingEvent::Publish {
+                topic: "/a".into(),
+                msg: json!({"x": 1})
+            }
+        );
+    }
+
+    #[test]
+    fn rejects_publish_without_msg_object() {
+        let err = classify(&json!({"op": "publish", "topic": "/a", "msg": 3})).unwrap_err();
+        assert_eq!(err.code, IncomingErrorCode::InvalidFrame);
+        assert_eq!(err.op.as_deref(), Some("publish"));
+        let err = classify(&json!({"op": "publish", "topic": "/a"})).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("msg"));
+
+        let err = classify(&json!({"op": "publish", "msg": {}})).unwrap_err();
+        assert!(err.message.contains("topic"), "{}", err.message);
+    }
+
+    #[test]
+    fn service_response_takes_error_from_string_values() {
+        let event = classify(&json!({
+            "op": "service_response",
+            "service": "/s",
+            "id": "svc-1",
+            "result": false,
+            "values": "boom"
+        }))
+        .unwrap();
+        let IncomingEvent::ServiceResponse { ok, error, id, .. } = event else {
+            panic!("unexpected event");
+        };
+        assert!(!ok);
+        assert_eq!(error.as_deref(), Some("boom"));
+        assert_eq!(id.as_deref(), Some("svc-1"));
+    }
+
+    #[test]
+    fn classifies_service_requests() {
+        let event = classify(&json!({
+            "op": "call_service",
+            "service": "/confirm",
+            "id": "service_request:1",
+            "args": {"prompt": "go?"}
+        }))
+        .unwrap();
+        assert_eq!(
+            event,
+            IncomingEvent::ServiceRequest {
+                id: Some("service_request:1".into()),
+                service: "/confirm".into(),
+                args: json!({"prompt": "go?"}),
+            }
+        );
+        let IncomingEvent::ServiceRequest { args, .. } =
+            classify(&json!({"op": "call_service", "service": "/trigger"})).unwrap()
+        else {
+            panic!("unexpected event");
+        };
+        assert_eq!(args, json!({}));
+        assert!(classify(&json!({"op": "call_service"})).is_err());
+    }
+
+    #[test]
+    fn classifies_hosted_action_requests() {
+        let goal = classify(&json!({
+            "op": "send_action_goal",
+            "id": "goal-1",
+            "action": "/teleop",
+            "action_type": "demo/action/Teleop",
+            "args": {"speed": 0.5},
+            "feedback": true
+        }))
+        .unwrap();
+        assert_eq!(
+            goal,
+            IncomingEvent::ActionGoalRequest {
+                id: "goal-1".into(),
+                action: "/teleop".into(),
+                action_type: Some("demo/action/Teleop".into()),
+                goal: json!({"speed": 0.5}),
+            }
+        );
+        let IncomingEvent::ActionGoalRequest { goal, .. } = classify(&json!({
+            "op": "send_action_goal",
+            "id": "goal-2",
+            "action": "/teleop",
+            "goal": {"speed": 1}
+        }))
+        .unwrap() else {
+            panic!("unexpected event");
+        };
+        assert_eq!(goal, json!({"speed": 1}));
+        assert_eq!(
+            classify(&json!({"op": "cancel_action_goal", "id": "goal-1", "action": "/teleop"}))
+                .unwrap(),
+            IncomingEvent::ActionCancelRequest {
+                id: "goal-1".into(),
+                action: "/teleop".into()
+            }
+        );
+    }
+
+    #[test]
+    fn cli_response_fails_on_nonzero_return_code() {
+        let ok = |frame: Value| match classify(&frame).unwrap() {
+            IncomingEvent::CliResponse { ok, .. } => ok,
+            other => panic!("unexpected event {other:?}"),
+        };
+        assert!(ok(json!({"op": "cli_response", "return_code": 0})));
+        assert!(ok(json!({"op": "cli_response"})));
+        assert!(!ok(
+            json!({"op": "cli_response", "return_code": 1, "success": true})
+        ));
+        assert!(!ok(json!({"op": "cli_response", "success": false})));
+    }
+
+    #[test]
+    fn action_result_falls_back_to_whole_frame() {
+        let frame = json!({"op": "action_result", "id": "a-1", "error": "unknown_action_type"});
+        let IncomingEvent::ActionResult { result, error, .. } = classify(&frame).unwrap() else {
+            panic!("unexpected event");
+        };
+        assert_eq!(result, frame);
+        assert_eq!(error.as_deref(), Some("unknown_action_type"));
+    }
+
+    #[test]
+    fn classifies_action_events() {
+        let event = classify(&json!({
+            "type": "feedback",
+            "id": "a-1",
+            "feedback": {"progress": 50}
+        }))
+        .unwrap();
+        let IncomingEvent::ActionEvent { event, payload, .. } = event else {
+            panic!("unexpected event");
+        };
+        assert_eq!(event, ActionEventType::Feedback);
+        assert_eq!(payload, json!({"progress": 50}));
+
+        let err = classify(&json!({"type": "bogus"})).unwrap_err();
+        assert_eq!(err.code, IncomingErrorCode::InvalidFrame);
+    }
+
+    #[test]
+    fn reports_frames_without_op() {
+        assert_eq!(
+            classify(&json!([1, 2])).unwrap_err().code,
+            IncomingErrorCode::NotAnObject
+        );
+        assert_eq!(
+            classify(&json!({"topic": "/a"})).unwrap_err().code,
+            IncomingErrorCode::MissingOp
+        );
+        let err = classify(&json!({"op": 7})).unwrap_err();
+        assert_eq!(err.code, IncomingErrorCode::InvalidFrame);
+        assert_eq!(err.path.as_deref(), Some("op"));
+    }
+
+    #[test]
+    fn classifies_fragments() {
+        let event = classify(&json!({
+            "op": "fragment",
+            "id": "m-1",
+            "data": "{\"op\"",
+            "num": 0,
+            "total": 2
+        }))
+        .unwrap();
+        assert_eq!(
+            event,
+            IncomingEvent::Fragment {
+                id: "m-1".into(),
+                data: "{\"op\"".into(),
+                num: 0,
+                total: 2
+            }
+        );
+        let err =
+            classify(&json!({"op": "fragment", "id": "m-1", "data": "x", "num": -1, "total": 2}))
+                .unwrap_err();
+        assert_eq!(err.code, IncomingErrorCode::InvalidFrame);
+    }
+
+    #[test]
+    fn classifies_action_goal_status() {
+        assert_eq!(
+            classify(&json!({
+                "op": "action_goal_status",
+                "action": "/navigate",
+                "id": "action-1",
+                "session_id": "s1",
+                "found": true
+            }))
+            .unwrap(),
+            IncomingEvent::ActionGoalStatus {
+                id: "action-1".into(),
+                action: "/navigate".into(),
+                session_id: Some("s1".into()),
+                found: true,
+            }
+        );
+        let err =
+            classify(&json!({"op": "action_goal_status", "action": "/navigate"})).unwrap_err();
+        assert_eq!(err.op.as_deref(), Some("action_goal_status"));
+    }
+
+    #[test]
+    fn classifies_status_messages() {
+        assert_eq!(
+            classify(&json!({
+                "op": "status",
+                "level": "error",
+                "msg": "Unable to load the manifest for package foo",
+                "id": "subscribe:/foo:1"
+            }))
+            .unwrap(),
+            IncomingEvent::Status {
+                level: "error".into(),
+                msg: "Unable to load the manifest for package foo".into(),
+                id: Some("subscribe:/foo:1".into()),
+            }
+        );
+        let err = classify(&json!({"op": "status", "msg": "no level"})).unwrap_err();
+        assert_eq!(err.op.as_deref(), Some("status"));
+    }
+
+    #[test]
+    fn classifies_error_frames() {
+        let event = classify(&json!({
+            "op": "error",
+            "error": "unsupported_operation",
+            "received": {"op": "call_service", "service": "/nope", "id": "svc-1", "args": {}}
+        }))
+        .unwrap();
+        assert_eq!(
+            event,
+            IncomingEvent::Error {
+                code: "unsupported_operation".into(),
+                message: None,
+                id: Some("svc-1".into()),
+                request_op: Some("call_service".into()),
+                target: Some("/nope".into()),
+            }
+        );
+        let IncomingEvent::Error { code, id, .. } =
+            classify(&json!({"op": "error", "error": "invalid_json"})).unwrap()
+        else {
+            panic!("unexpected event");
+        };
+        assert_eq!(code, "invalid_json");
+        assert_eq!(id, None);
+        assert!(classify(&json!({"op": "error"})).is_err());
+    }
+
+    #[test]
+    fn passes_unknown_ops_through() {
+        assert_eq!(
+            classify(&json!({"op": "graph", "level": "error"})).unwrap(),
+            IncomingEvent::Unknown { op: "graph".into() }
+        );
+    }
+
+    #[test]
+    fn keeps_bigint_integers_in_the_envelope() {
+        assert_eq!(
+            integer_value(&CborValue::Unsigned(u64::MAX)),
+            json!(u64::MAX)
+        );
+        let big = CborValue::Negative(1 << 62);
+        let event = classify(&json!({
+            "op": "cli_response",
+            "id": "cli-1",
+            "return_code": integer_value(&big),
+        }))
+        .unwrap();
+        assert!(matches!(
+            event,
+            IncomingEvent::CliResponse { return_code: Some(code), .. } if code == -1 - (1i64 << 62)
+        ));
+        let bignum = CborValue::Tag(
+            2,
+            Box::new(CborValue::Bytes(vec![1, 0, 0, 0, 0, 0, 0, 0, 0])),
+        );
+        assert_eq!(integer_value(&bignum), json!(2f64.powi(64)));
+    }
+}
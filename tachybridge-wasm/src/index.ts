@@ -1,19 +1,50 @@
 export * from "./browser.js";
 export { autoCodec, cborCodec, jsonCodec, resolveCodec } from "./codec.js";
+export type { BridgeErrorCode, BridgeErrorDetails } from "./errors.js";
 export type {
+  ActionFeedbackOf,
+  ActionGoalHandler,
+  ActionGoalOf,
   ActionHandle,
+  ActionResultOf,
+  ActionServerGoal,
+  AdvertiseOptions,
   BridgeClientOptions,
   BridgeCodec,
   BridgeCodecName,
   BridgeCodecOption,
+  BridgeConnectionState,
+  BridgeIncomingEvent,
+  BridgeMessageField,
+  BridgeMessageSchema,
+  BridgeOutboundQueueOptions,
+  BridgeProtocolError,
+  BridgeProtocolErrorCode,
+  BridgeQueuePolicy,
+  BridgeReceiveTime,
   BridgeReconnectContext,
   BridgeReconnectOptions,
   BridgeReconnectReason,
   BridgeReconnectScheduledEvent,
+  BridgeReconnectStrategy,
+  BridgeRequestRetry,
+  BridgeStateChange,
+  BridgeStatus,
+  BridgeStatusLevel,
+  BridgeValidationError,
+  BridgeValidationIssue,
   CallServiceOptions,
   CancelActionGoalOptions,
+  EncodeCdrOptions,
   ExecuteCliOptions,
   JsonObject,
+  MessageOf,
+  PublishOptions,
+  Publisher,
   SendActionGoalOptions,
-  SubscribeOptions
+  ServiceHandler,
+  ServiceRequestOf,
+  ServiceResponseOf,
+  SubscribeOptions,
+  SubscriptionHandle
 } from "./types.js";
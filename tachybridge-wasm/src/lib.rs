@@ -1,14 +1,42 @@
 use serde_json::{json, Value};
 use wasm_bindgen::prelude::*;
 
+use crate::error::BridgeError;
+
+mod cbor;
+mod cdr;
+mod connection;
+mod defaults;
+pub mod error;
+mod fragment;
+mod incoming;
+pub mod msgdef;
+mod outbound;
+mod png;
+mod publishers;
+mod reconnect;
+mod requests;
+mod schema;
+mod subscriptions;
+pub mod typegen;
+mod validate;
+
 fn from_js(value: JsValue) -> Result<Value, JsValue> {
     serde_wasm_bindgen::from_value(value)
-        .map_err(|e| JsValue::from_str(&format!("invalid js value: {e}")))
+        .map_err(|e| BridgeError::invalid_input(format!("invalid js value: {e}")).into())
 }
 
 fn to_js(value: Value) -> Result<JsValue, JsValue> {
     serde_wasm_bindgen::to_value(&value)
-        .map_err(|e| JsValue::from_str(&format!("cannot encode value: {e}")))
+        .map_err(|e| BridgeError::encode_failed(format!("cannot encode value: {e}")).into())
+}
+
+/// Like [`to_js`], but emits plain JS objects for maps instead of `Map`s.
+pub(crate) fn to_js_object<T: serde::Serialize + ?Sized>(value: &T) -> Result<JsValue, JsValue> {
+    let serializer = serde_wasm_bindgen::Serializer::new().serialize_maps_as_objects(true);
+    value
+        .serialize(&serializer)
+        .map_err(|e| BridgeError::encode_failed(format!("cannot encode value: {e}")).into())
 }
 
 #[wasm_bindgen]
@@ -16,29 +44,74 @@ pub fn build_subscribe(
     topic: String,
     msg_type: String,
     compression: Option<String>,
+    fragment_size: Option<u32>,
+    throttle_rate: Option<u32>,
+    queue_length: Option<u32>,
+    id: Option<String>,
 ) -> Result<JsValue, JsValue> {
     to_js(json!({
         "op": "subscribe",
         "topic": topic,
         "type": msg_type,
         "compression": compression,
+        "fragment_size": fragment_size,
+        "throttle_rate": throttle_rate,
+        "queue_length": queue_length,
+        "id": id,
     }))
 }
 
 #[wasm_bindgen]
-pub fn build_unsubscribe(topic: String) -> Result<JsValue, JsValue> {
+pub fn build_unsubscribe(topic: String, id: Option<String>) -> Result<JsValue, JsValue> {
     to_js(json!({
         "op": "unsubscribe",
         "topic": topic,
+        "id": id,
     }))
 }
 
 #[wasm_bindgen]
-pub fn build_advertise(topic: String, msg_type: String) -> Result<JsValue, JsValue> {
+pub fn build_advertise(
+    topic: String,
+    msg_type: String,
+    latch: Option<bool>,
+    queue_size: Option<u32>,
+) -> Result<JsValue, JsValue> {
     to_js(json!({
         "op": "advertise",
         "topic": topic,
         "type": msg_type,
+        "latch": latch,
+        "queue_size": queue_size,
+    }))
+}
+
+#[wasm_bindgen]
+pub fn build_unadvertise(topic: String) -> Result<JsValue, JsValue> {
+    to_js(json!({
+        "op": "unadvertise",
+        "topic": topic,
+    }))
+}
+
+/// Verbosity levels accepted by `set_level`, quietest last.
+const STATUS_LEVELS: [&str; 4] = ["info", "warning", "error", "none"];
+
+/// Chooses which `status` messages the bridge sends to this client.
+#[wasm_bindgen]
+pub fn build_set_level(level: String, id: Option<String>) -> Result<JsValue, JsValue> {
+    if !STATUS_LEVELS.contains(&level.as_str()) {
+        return Err(BridgeError::invalid_input(format!(
+            "unknown status level `{level}` (expected info, warning, error or none)"
+        ))
+        .with_op("set_level")
+        .with_path("level")
+        .into());
+    }
+    to_js(json!({
+        "op": "set_level",
+        "level": level,
+        "id": id,
     }))
 }
 
@@ -58,6 +131,7 @@ pub fn build_call_service(
     srv_type: String,
     args: JsValue,
     id: Option<String>,
+    fragment_size: Option<u32>,
 ) -> Result<JsValue, JsValue> {
     let args_value = from_js(args)?;
     to_js(json!({
@@ -66,6 +140,43 @@ pub fn build_call_service(
         "type": srv_type,
         "args": args_value,
         "id": id,
+        "fragment_size": fragment_size,
+    }))
+}
+
+#[wasm_bindgen]
+pub fn build_advertise_service(service: String, srv_type: String) -> Result<JsValue, JsValue> {
+    to_js(json!({
+        "op": "advertise_service",
+        "service": service,
+        "type": srv_type,
+    }))
+}
+
+#[wasm_bindgen]
+pub fn build_unadvertise_service(service: String) -> Result<JsValue, JsValue> {
+    to_js(json!({
+        "op": "unadvertise_service",
+        "service": service,
+    }))
+}
+
+/// Answers a `call_service` request for a service this client advertised.
+/// On failure rosbridge expects `values` to hold the error text.
+#[wasm_bindgen]
+pub fn build_service_response(
+    service: String,
+    id: Option<String>,
+    result: bool,
+    values: JsValue,
+) -> Result<JsValue, JsValue> {
+    let values_value = from_js(values)?;
+    to_js(json!({
+        "op": "service_response",
+        "service": service,
+        "id": id,
+        "result": result,
+        "values": values_value,
     }))
 }
 
@@ -101,3 +212,86 @@ pub fn build_cancel_action_goal(
         "session_id": session_id,
     }))
 }
+
+/// tachybridge extension: asks the bridge to send the events of goal `id`,
+/// started on an earlier connection, to this one. The bridge answers with
+/// `action_goal_status`.
+#[wasm_bindgen]
+pub fn build_query_action_goal(
+    action: String,
+    id: String,
+    session_id: Option<String>,
+) -> Result<JsValue, JsValue> {
+    to_js(json!({
+        "op": "query_action_goal",
+        "action": action,
+        "id": id,
+        "session_id": session_id,
+    }))
+}
+
+#[wasm_bindgen]
+pub fn build_advertise_action(action: String, action_type: String) -> Result<JsValue, JsValue> {
+    to_js(json!({
+        "op": "advertise_action",
+        "action": action,
+        "type": action_type,
+    }))
+}
+
+#[wasm_bindgen]
+pub fn build_unadvertise_action(action: String) -> Result<JsValue, JsValue> {
+    to_js(json!({
+        "op": "unadvertise_action",
+        "action": action,
+    }))
+}
+
+#[wasm_bindgen]
+pub fn build_action_feedback(
+    action: String,
+    id: String,
+    feedback: JsValue,
+) -> Result<JsValue, JsValue> {
+    let feedback_value = from_js(feedback)?;
+    to_js(json!({
+        "op": "action_feedback",
+        "action": action,
+        "id": id,
+        "values": feedback_value,
+    }))
+}
+
+/// `action_msgs/GoalStatus` codes a hosted goal can finish with.
+const GOAL_SUCCEEDED: u8 = 4;
+const GOAL_CANCELED: u8 = 5;
+const GOAL_ABORTED: u8 = 6;
+
+/// Finishes a hosted goal. `status` must be a terminal `action_msgs/GoalStatus`
+/// code; `result` is `true` only for succeeded goals.
+#[wasm_bindgen]
+pub fn build_action_result(
+    action: String,
+    id: String,
+    status: u8,
+    result: JsValue,
+) -> Result<JsValue, JsValue> {
+    if !matches!(status, GOAL_SUCCEEDED | GOAL_CANCELED | GOAL_ABORTED) {
+        return Err(BridgeError::invalid_input(format!(
+            "goal status {status} is not terminal (expected 4, 5 or 6)"
+        ))
+        .with_op("action_result")
+        .with_path("status")
+        .with_request_id(id)
+        .into());
+    }
+    let result_value = from_js(result)?;
+    to_js(json!({
+        "op": "action_result",
+        "action": action,
+        "id": id,
+        "status": status,
+        "values": result_value,
+        "result": status == GOAL_SUCCEEDED,
+    }))
+}
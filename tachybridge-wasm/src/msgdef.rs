@@ -0,0 +1,854 @@
+//! ROS 2 interface definition parsing (`.msg`, `.srv`, `.action`).
+//!
+//! Services and actions are split into their component messages, named the
+//! way rosidl names them: `pkg/srv/Name_Request`, `pkg/action/Name_Goal`, ...
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::{json, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Primitive {
+    Bool,
+    Byte,
+    Char,
+    Int8,
+    Uint8,
+    Int16,
+    Uint16,
+    Int32,
+    Uint32,
+    Int64,
+    Uint64,
+    Float32,
+    Float64,
+    String,
+    WString,
+}
+
+impl Primitive {
+    pub fn from_name(name: &str) -> Option<Self> {
+        Some(match name {
+            "bool" => Self::Bool,
+            "byte" => Self::Byte,
+            "char" => Self::Char,
+            "int8" => Self::Int8,
+            "uint8" => Self::Uint8,
+            "int16" => Self::Int16,
+            "uint16" => Self::Uint16,
+            "int32" => Self::Int32,
+            "uint32" => Self::Uint32,
+            "int64" => Self::Int64,
+            "uint64" => Self::Uint64,
+            "float32" => Self::Float32,
+            "float64" => Self::Float64,
+            "string" => Self::String,
+            "wstring" => Self::WString,
+            _ => return None,
+        })
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Bool => "bool",
+            Self::Byte => "byte",
+            Self::Char => "char",
+            Self::Int8 => "int8",
+            Self::Uint8 => "uint8",
+            Self::Int16 => "int16",
+            Self::Uint16 => "uint16",
+            Self::Int32 => "int32",
+            Self::Uint32 => "uint32",
+            Self::Int64 => "int64",
+            Self::Uint64 => "uint64",
+            Self::Float32 => "float32",
+            Self::Float64 => "float64",
+            Self::String => "string",
+            Self::WString => "wstring",
+        }
+    }
+
+    /// Length of `text` as a `string<=N` / `wstring<=N` bound counts it, in
+    /// the units CDR puts on the wire: bytes for `string`, UTF-16 code units
+    /// for `wstring`. Validation, defaults and serialization share this rule.
+    pub fn bounded_len(self, text: &str) -> usize {
+        match self {
+            Self::WString => text.encode_utf16().count(),
+            _ => text.len(),
+        }
+    }
+
+    /// Inclusive value range of integer types.
+    pub fn int_range(self) -> Option<(i128, i128)> {
+        Some(match self {
+            Self::Byte | Self::Char | Self::Uint8 => (0, u8::MAX.into()),
+            Self::Int8 => (i8::MIN.into(), i8::MAX.into()),
+            Self::Int16 => (i16::MIN.into(), i16::MAX.into()),
+            Self::Uint16 => (0, u16::MAX.into()),
+            Self::Int32 => (i32::MIN.into(), i32::MAX.into()),
+            Self::Uint32 => (0, u32::MAX.into()),
+            Self::Int64 => (i64::MIN.into(), i64::MAX.into()),
+            Self::Uint64 => (0, u64::MAX.into()),
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FieldType {
+    Primitive(Primitive),
+    /// Canonical `pkg/msg/Name` of a nested message.
+    Message(String),
+}
+
+impl FieldType {
+    pub fn name(&self) -> &str {
+        match self {
+            Self::Primitive(primitive) => primitive.name(),
+            Self::Message(name) => name,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayKind {
+    Single,
+    Fixed(usize),
+    Sequence,
+    /// Sequence of at most this many elements.
+    Bounded(usize),
+}
+
+/// Constant or default value literal.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Literal {
+    Bool(bool),
+    Int(i128),
+    Float(f64),
+    String(String),
+    Array(Vec<Literal>),
+}
+
+impl Literal {
+    pub fn to_json(&self) -> Value {
+        match self {
+            Self::Bool(flag) => json!(flag),
+            Self::Int(n) => match (i64::try_from(*n), u64::try_from(*n)) {
+                (Ok(n), _) => json!(n),
+                (_, Ok(n)) => json!(n),
+                _ => Value::Null,
+            },
+            Self::Float(f) => json!(f),
+            Self::String(text) => json!(text),
+            Self::Array(items) => Value::Array(items.iter().map(Self::to_json).collect()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Field {
+    pub name: String,
+    pub ty: FieldType,
+    pub array: ArrayKind,
+    /// Maximum length of `string<=N` / `wstring<=N` values.
+    pub string_bound: Option<usize>,
+    pub default: Option<Literal>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Constant {
+    pub name: String,
+    pub ty: Primitive,
+    pub value: Literal,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct MessageDef {
+    pub name: String,
+    pub fields: Vec<Field>,
+    pub constants: Vec<Constant>,
+}
+
+impl MessageDef {
+    /// JS-facing description, as returned by `get_message_schema`.
+    pub fn to_json(&self) -> Value {
+        let fields: Vec<Value> = self
+            .fields
+            .iter()
+            .map(|field| {
+                let mut out = json!({
+                    "name": field.name,
+                    "type": field.ty.name(),
+                    "is_message": matches!(field.ty, FieldType::Message(_)),
+                });
+                let (array, size) = match field.array {
+                    ArrayKind::Single => (None, None),
+                    ArrayKind::Fixed(n) => (Some("fixed"), Some(n)),
+                    ArrayKind::Sequence => (Some("sequence"), None),
+                    ArrayKind::Bounded(n) => (Some("bounded"), Some(n)),
+                };
+                if let Some(array) = array {
+                    out["array"] = json!(array);
+                }
+                if let Some(size) = size {
+                    out["array_size"] = json!(size);
+                }
+                if let Some(bound) = field.string_bound {
+                    out["string_bound"] = json!(bound);
+                }
+                if let Some(default) = &field.default {
+                    out["default"] = default.to_json();
+                }
+                out
+            })
+            .collect();
+        let constants: Vec<Value> = self
+            .constants
+            .iter()
+            .map(|constant| {
+                json!({
+                    "name": constant.name,
+                    "type": constant.ty.name(),
+                    "value": constant.value.to_json(),
+                })
+            })
+            .collect();
+        json!({ "name": self.name, "fields": fields, "constants": constants })
+    }
+}
+
+/// Message definitions keyed by canonical type name.
+pub type MessageSet = HashMap<String, MessageDef>;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    pub type_name: String,
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} line {}: {}", self.type_name, self.line, self.message)
+    }
+}
+
+fn canonical_in(name: &str, kind: &str) -> Option<String> {
+    let parts: Vec<&str> = name.trim().split('/').collect();
+    if parts.iter().any(|part| !is_identifier(part)) {
+        return None;
+    }
+    match parts.as_slice() {
+        [pkg, ty] => Some(format!("{pkg}/{kind}/{ty}")),
+        [pkg, kind, ty] => Some(format!("{pkg}/{kind}/{ty}")),
+        _ => None,
+    }
+}
+
+/// Normalizes `pkg/Name` and `pkg/msg/Name` to `pkg/msg/Name`.
+pub fn canonical_name(name: &str) -> Option<String> {
+    canonical_in(name, "msg")
+}
+
+fn is_identifier(text: &str) -> bool {
+    let mut chars = text.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+fn package_of(canonical: &str) -> &str {
+    canonical.split('/').next().unwrap_or_default()
+}
+
+fn resolve_type(name: &str, package: &str) -> Option<FieldType> {
+    if let Some(primitive) = Primitive::from_name(name) {
+        return Some(FieldType::Primitive(primitive));
+    }
+    if name == "Header" {
+        return Some(FieldType::Message("std_msgs/msg/Header".to_owned()));
+    }
+    if name.contains('/') {
+        return canonical_name(name).map(FieldType::Message);
+    }
+    is_identifier(name).then(|| FieldType::Message(format!("{package}/msg/{name}")))
+}
+
+/// Drops a trailing `#` comment, ignoring `#` inside quotes.
+fn strip_comment(line: &str) -> &str {
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in line.char_indices() {
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '#') => return &line[..index],
+            _ => {}
+        }
+    }
+    line
+}
+
+fn parse_bound(text: &str) -> Result<usize, String> {
+    text.trim()
+        .parse()
+        .map_err(|_| format!("invalid bound `{text}`"))
+}
+
+fn parse_array(suffix: &str) -> Result<ArrayKind, String> {
+    let inner = suffix
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| format!("malformed array suffix `{suffix}`"))?;
+    if inner.is_empty() {
+        return Ok(ArrayKind::Sequence);
+    }
+    if let Some(bound) = inner.strip_prefix("<=") {
+        return parse_bound(bound).map(ArrayKind::Bounded);
+    }
+    inner
+        .parse()
+        .map(ArrayKind::Fixed)
+        .map_err(|_| format!("invalid array size `{inner}`"))
+}
+
+fn unquote(text: &str) -> Result<String, String> {
+    let Some(quote) = text.chars().next().filter(|c| *c == '"' || *c == '\'') else {
+        return Ok(text.to_owned());
+    };
+    let inner = text[1..]
+        .strip_suffix(quote)
+        .ok_or_else(|| format!("unterminated string literal {text}"))?;
+    let mut out = String::with_capacity(inner.len());
+    let mut chars = inner.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    Ok(out)
+}
+
+/// Splits `[a, "b,c", d]` on top-level commas.
+fn split_array_literal(text: &str) -> Result<Vec<&str>, String> {
+    let inner = text
+        .trim()
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+        .ok_or_else(|| format!("expected an array literal, found `{text}`"))?;
+    if inner.trim().is_empty() {
+        return Ok(Vec::new());
+    }
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut quote = None;
+    let mut escaped = false;
+    for (index, c) in inner.char_indices() {
+        match (quote, c) {
+            _ if escaped => escaped = false,
+            (Some(_), '\\') => escaped = true,
+            (Some(q), c) if c == q => quote = None,
+            (None, '"' | '\'') => quote = Some(c),
+            (None, ',') => {
+                items.push(inner[start..index].trim());
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(inner[start..].trim());
+    Ok(items)
+}
+
+fn parse_scalar(primitive: Primitive, bound: Option<usize>, text: &str) -> Result<Literal, String> {
+    let text = text.trim();
+    if let Some((min, max)) = primitive.int_range() {
+        let value: i128 = text
+            .parse()
+            .map_err(|_| format!("invalid {} value `{text}`", primitive.name()))?;
+        if value < min || value > max {
+            return Err(format!("{value} is out of range for {}", primitive.name()));
+        }
+        return Ok(Literal::Int(value));
+    }
+    match primitive {
+        Primitive::Bool => match text {
+            "true" | "True" | "1" => Ok(Literal::Bool(true)),
+            "false" | "False" | "0" => Ok(Literal::Bool(false)),
+            _ => Err(format!("invalid bool value `{text}`")),
+        },
+        Primitive::Float32 | Primitive::Float64 => text
+            .parse()
+            .map(Literal::Float)
+            .map_err(|_| format!("invalid {} value `{text}`", primitive.name())),
+        _ => {
+            let value = unquote(text)?;
+            match bound {
+                Some(bound) if primitive.bounded_len(&value) > bound => {
+                    Err(format!("string value exceeds bound of {bound}"))
+                }
+                _ => Ok(Literal::String(value)),
+            }
+        }
+    }
+}
+
+fn parse_default(field: &Field, text: &str) -> Result<Literal, String> {
+    let FieldType::Primitive(primitive) = field.ty else {
+        return Err(format!(
+            "field `{}` of message type cannot have a default value",
+            field.name
+        ));
+    };
+    if field.array == ArrayKind::Single {
+        return parse_scalar(primitive, field.string_bound, text);
+    }
+    let items = split_array_literal(text)?
+        .into_iter()
+        .map(|item| parse_scalar(primitive, field.string_bound, item))
+        .collect::<Result<Vec<_>, _>>()?;
+    match field.array {
+        ArrayKind::Fixed(n) if items.len() != n => Err(format!(
+            "default for `{}` has {} elements, expected {n}",
+            field.name,
+            items.len()
+        )),
+        ArrayKind::Bounded(n) if items.len() > n => Err(format!(
+            "default for `{}` has {} elements, bound is {n}",
+            field.name,
+            items.len()
+        )),
+        _ => Ok(Literal::Array(items)),
+    }
+}
+
+enum Line {
+    Field(Field),
+    Constant(Constant),
+}
+
+fn parse_line(line: &str, package: &str) -> Result<Line, String> {
+    let (type_token, rest) = line
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("expected `<type> <name>`, found `{line}`"))?;
+    let rest = rest.trim_start();
+    let name_end = rest
+        .find(|c: char| c.is_whitespace() || c == '=')
+        .unwrap_or(rest.len());
+    let (name, value) = (&rest[..name_end], rest[name_end..].trim());
+    if !is_identifier(name) {
+        return Err(format!("invalid name `{name}`"));
+    }
+
+    let (base, array) = match type_token.find('[') {
+        Some(index) => (&type_token[..index], parse_array(&type_token[index..])?),
+        None => (type_token, ArrayKind::Single),
+    };
+    let (base, string_bound) = match base.split_once("<=") {
+        Some((base, bound)) if base == "string" || base == "wstring" => {
+            (base, Some(parse_bound(bound)?))
+        }
+        Some(_) => return Err(format!("only strings can be bounded, found `{base}`")),
+        None => (base, None),
+    };
+    let ty = resolve_type(base, package).ok_or_else(|| format!("invalid type `{base}`"))?;
+
+    if let Some(value) = value.strip_prefix('=') {
+        let FieldType::Primitive(primitive) = ty else {
+            return Err(format!("constant `{name}` must have a primitive type"));
+        };
+        if array != ArrayKind::Single {
+            return Err(format!("constant `{name}` cannot be an array"));
+        }
+        return Ok(Line::Constant(Constant {
+            name: name.to_owned(),
+            ty: primitive,
+            value: parse_scalar(primitive, string_bound, value)?,
+        }));
+    }
+
+    let mut field = Field {
+        name: name.to_owned(),
+        ty,
+        array,
+        string_bound,
+        default: None,
+    };
+    if !value.is_empty() {
+        field.default = Some(parse_default(&field, value)?);
+    }
+    Ok(Line::Field(field))
+}
+
+/// Parses message lines; `first_line` is the 1-based line number of `text`
+/// within the file, for error reporting.
+fn parse_body(name: String, text: &str, first_line: usize) -> Result<MessageDef, ParseError> {
+    let package = package_of(&name).to_owned();
+    let mut def = MessageDef {
+        name,
+        fields: Vec::new(),
+        constants: Vec::new(),
+    };
+    for (index, raw) in text.lines().enumerate() {
+        let line = strip_comment(raw).trim();
+        if line.is_empty() {
+            continue;
+        }
+        let error = |message| ParseError {
+            type_name: def.name.clone(),
+            line: first_line + index,
+            message,
+        };
+        match parse_line(line, &package).map_err(error)? {
+            Line::Field(field) => {
+                if def.fields.iter().any(|f| f.name == field.name) {
+                    return Err(error(format!("duplicate field `{}`", field.name)));
+                }
+                def.fields.push(field);
+            }
+            Line::Constant(constant) => def.constants.push(constant),
+        }
+    }
+    Ok(def)
+}
+
+fn invalid_name(type_name: &str) -> ParseError {
+    ParseError {
+        type_name: type_name.to_owned(),
+        line: 0,
+        message: "type name must look like `pkg/msg/Name`".to_owned(),
+    }
+}
+
+/// Parses the body of a single `.msg` file.
+pub fn parse_message(type_name: &str, text: &str) -> Result<MessageDef, ParseError> {
+    let name = canonical_name(type_name).ok_or_else(|| invalid_name(type_name))?;
+    parse_body(name, text, 1)
+}
+
+fn parse_parts(
+    type_name: &str,
+    kind: &str,
+    suffixes: &[&str],
+    text: &str,
+) -> Result<Vec<MessageDef>, ParseError> {
+    let name = canonical_in(type_name, kind).ok_or_else(|| invalid_name(type_name))?;
+    let mut parts: Vec<(usize, String)> = vec![(1, String::new())];
+    for (index, line) in text.lines().enumerate() {
+        if line.trim() == "---" {
+            parts.push((index + 2, String::new()));
+            continue;
+        }
+        let (_, body) = parts.last_mut().expect("parts is never empty");
+        body.push_str(line);
+        body.push('\n');
+    }
+    if parts.len() != suffixes.len() {
+        return Err(ParseError {
+            type_name: name,
+            line: 0,
+            message: format!(
+                "expected {} sections separated by `---`, found {}",
+                suffixes.len(),
+                parts.len()
+            ),
+        });
+    }
+    parts
+        .into_iter()
+        .zip(suffixes)
+        .map(|((first_line, body), suffix)| {
+            parse_body(format!("{name}{suffix}"), &body, first_line)
+        })
+        .collect()
+}
+
+/// Parses a `.srv` file into its `_Request` and `_Response` messages.
+pub fn parse_service(type_name: &str, text: &str) -> Result<Vec<MessageDef>, ParseError> {
+    parse_parts(type_name, "srv", &["_Request", "_Response"], text)
+}
+
+/// Parses a `.action` file into its `_Goal`, `_Result` and `_Feedback`
+/// messages.
+pub fn parse_action(type_name: &str, text: &str) -> Result<Vec<MessageDef>, ParseError> {
+    parse_parts(
+        type_name,
+        "action",
+        &["_Goal", "_Result", "_Feedback"],
+        text,
+    )
+}
+
+/// Parses one interface file, picking the format from the `msg`, `srv` or
+/// `action` segment of `type_name`.
+pub fn parse_interface(type_name: &str, text: &str) -> Result<Vec<MessageDef>, ParseError> {
+    match type_name.split('/').nth(1) {
+        Some("srv") if type_name.split('/').count() == 3 => parse_service(type_name, text),
+        Some("action") if type_name.split('/').count() == 3 => parse_action(type_name, text),
+        _ => parse_message(type_name, text).map(|def| vec![def]),
+    }
+}
+
+fn parse_sections(root_type: Option<&str>, text: &str) -> Result<Vec<MessageDef>, ParseError> {
+    let mut defs = Vec::new();
+    let mut current = root_type.map(|name| (name.to_owned(), "msg"));
+    let mut body = String::new();
+
+    let mut flush = |current: &Option<(String, &str)>, body: &str| -> Result<(), ParseError> {
+        match current {
+            Some((name, "srv")) => defs.extend(parse_service(name, body)?),
+            Some((name, "action")) => defs.extend(parse_action(name, body)?),
+            Some((name, _)) => defs.push(parse_message(name, body)?),
+            None if body.trim().is_empty() => {}
+            None => {
+                return Err(ParseError {
+                    type_name: String::new(),
+                    line: 0,
+                    message: "definition section has no `MSG:`, `SRV:` or `ACTION:` header"
+                        .to_owned(),
+                })
+            }
+        }
+        Ok(())
+    };
+
+    for line in text.lines() {
+        let trimmed = line.trim();
+        if !trimmed.is_empty() && trimmed.chars().all(|c| c == '=') {
+            flush(&current, &body)?;
+            body.clear();
+            current = None;
+            continue;
+        }
+        let header = [("MSG:", "msg"), ("SRV:", "srv"), ("ACTION:", "action")]
+            .into_iter()
+            .find_map(|(prefix, kind)| trimmed.strip_prefix(prefix).map(|rest| (rest, kind)));
+        if let Some((name, kind)) = header.filter(|_| body.trim().is_empty()) {
+            let name = canonical_in(name, kind).ok_or_else(|| invalid_name(name.trim()))?;
+            current = Some((name, kind));
+            continue;
+        }
+        body.push_str(line);
+        body.push('\n');
+    }
+    flush(&current, &body)?;
+    Ok(defs)
+}
+
+/// Parses a concatenated definition as published by ROS 2 tooling: the root
+/// message first, then each dependency after a `====` separator line and a
+/// `MSG: pkg/msg/Name` header.
+pub fn parse_full_text(root_type: &str, text: &str) -> Result<MessageSet, ParseError> {
+    Ok(parse_sections(Some(root_type), text)?
+        .into_iter()
+        .map(|def| (def.name.clone(), def))
+        .collect())
+}
+
+/// Parses a bundle of `====`-separated sections, each starting with a
+/// `MSG:`, `SRV:` or `ACTION:` header naming the interface.
+pub fn parse_bundle(text: &str) -> Result<Vec<MessageDef>, ParseError> {
+    parse_sections(None, text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn field<'d>(def: &'d MessageDef, name: &str) -> &'d Field {
+        def.fields.iter().find(|f| f.name == name).unwrap()
+    }
+
+    #[test]
+    fn parses_fields_arrays_and_nested_types() {
+        let def = parse_message(
+            "demo/Sample",
+            "# comment\nHeader header\nfloat64[3] position  # trailing\nPoint[] points\ngeometry_msgs/Twist cmd\nint32 LIMIT=5\n",
+        )
+        .unwrap();
+        assert_eq!(def.name, "demo/msg/Sample");
+        let summary: Vec<(&str, &FieldType, ArrayKind)> = def
+            .fields
+            .iter()
+            .map(|f| (f.name.as_str(), &f.ty, f.array))
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (
+                    "header",
+                    &FieldType::Message("std_msgs/msg/Header".into()),
+                    ArrayKind::Single
+                ),
+                (
+                    "position",
+                    &FieldType::Primitive(Primitive::Float64),
+                    ArrayKind::Fixed(3)
+                ),
+                (
+                    "points",
+                    &FieldType::Message("demo/msg/Point".into()),
+                    ArrayKind::Sequence
+                ),
+                (
+                    "cmd",
+                    &FieldType::Message("geometry_msgs/msg/Twist".into()),
+                    ArrayKind::Single
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_constants_defaults_and_bounds() {
+        let def = parse_message(
+            "demo/msg/Config",
+            "uint8 MODE_AUTO=1\n\
+             string GREETING = \"hi # there\"  # comment\n\
+             int16 offset -3\n\
+             bool enabled true\n\
+             string<=8 name 'robot'\n\
+             float32[<=4] gains [1.5, 2, -0.25]\n\
+             string[2] tags [\"a,b\", c]\n\
+             int32[] empty []\n",
+        )
+        .unwrap();
+        assert_eq!(
+            def.constants,
+            vec![
+                Constant {
+                    name: "MODE_AUTO".into(),
+                    ty: Primitive::Uint8,
+                    value: Literal::Int(1),
+                },
+                Constant {
+                    name: "GREETING".into(),
+                    ty: Primitive::String,
+                    value: Literal::String("hi # there".into()),
+                },
+            ]
+        );
+        assert_eq!(field(&def, "offset").default, Some(Literal::Int(-3)));
+        assert_eq!(field(&def, "enabled").default, Some(Literal::Bool(true)));
+        let name = field(&def, "name");
+        assert_eq!(name.string_bound, Some(8));
+        assert_eq!(name.default, Some(Literal::String("robot".into())));
+        let gains = field(&def, "gains");
+        assert_eq!(gains.array, ArrayKind::Bounded(4));
+        assert_eq!(
+            gains.default,
+            Some(Literal::Array(vec![
+                Literal::Float(1.5),
+                Literal::Float(2.0),
+                Literal::Float(-0.25)
+            ]))
+        );
+        assert_eq!(
+            field(&def, "tags").default,
+            Some(Literal::Array(vec![
+                Literal::String("a,b".into()),
+                Literal::String("c".into())
+            ]))
+        );
+        assert_eq!(field(&def, "empty").default, Some(Literal::Array(vec![])));
+    }
+
+    #[test]
+    fn rejects_invalid_literals() {
+        let cases = [
+            ("uint8 X=256", "out of range"),
+            ("int8 x 1.5", "invalid int8 value"),
+            ("string<=2 s \"abc\"", "exceeds bound"),
+            ("float64[2] v [1.0]", "expected 2"),
+            ("int32[<=1] v [1, 2]", "bound is 1"),
+            ("geometry_msgs/Point p 1", "cannot have a default"),
+            ("int32[] ARR=1", "cannot be an array"),
+            ("int32<=3 x", "only strings can be bounded"),
+            ("int32 x\nint32 x", "duplicate field"),
+        ];
+        for (text, expected) in cases {
+            let err = parse_message("demo/Bad", text).unwrap_err();
+            assert!(err.message.contains(expected), "{text}: {}", err.message);
+        }
+    }
+
+    #[test]
+    fn splits_services_and_actions() {
+        let srv = parse_interface("demo/srv/AddTwo", "int64 a\nint64 b\n---\nint64 sum\n").unwrap();
+        let names: Vec<&str> = srv.iter().map(|d| d.name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["demo/srv/AddTwo_Request", "demo/srv/AddTwo_Response"]
+        );
+        assert_eq!(srv[1].fields[0].name, "sum");
+
+        let action = parse_interface(
+            "demo/action/Move",
+            "Pose target\n---\nbool ok\n---\nfloat32 progress\nbad line here!\n",
+        )
+        .unwrap_err();
+        assert_eq!(action.type_name, "demo/action/Move_Feedback");
+        assert_eq!(action.line, 6);
+
+        let action = parse_action("demo/Move", "Pose target\n---\n---\n").unwrap();
+        assert_eq!(
+            action[0].fields[0].ty,
+            FieldType::Message("demo/msg/Pose".into())
+        );
+        assert!(parse_service("demo/Only", "int32 a\n").is_err());
+    }
+
+    #[test]
+    fn splits_full_text_sections() {
+        let text = "Vector3 linear\n\
+            ================================================================================\n\
+            MSG: geometry_msgs/Vector3\n\
+            float64 x\nfloat64 y\nfloat64 z\n";
+        let set = parse_full_text("geometry_msgs/msg/Twist", text).unwrap();
+        assert_eq!(set["geometry_msgs/msg/Twist"].fields.len(), 1);
+        assert_eq!(set["geometry_msgs/msg/Vector3"].fields.len(), 3);
+    }
+
+    #[test]
+    fn parses_bundles_with_headers() {
+        let text = "MSG: demo/Point\nfloat64 x\n\
+            ===\n\
+            SRV: demo/Reset\nbool hard\n---\nbool ok\n\
+            ===\n\
+            ACTION: demo/action/Go\nPoint goal\n---\n---\nfloat32 progress\n";
+        let mut names: Vec<String> = parse_bundle(text)
+            .unwrap()
+            .into_iter()
+            .map(|d| d.name)
+            .collect();
+        names.sort();
+        assert_eq!(
+            names,
+            [
+                "demo/action/Go_Feedback",
+                "demo/action/Go_Goal",
+                "demo/action/Go_Result",
+                "demo/msg/Point",
+                "demo/srv/Reset_Request",
+                "demo/srv/Reset_Response",
+            ]
+        );
+        assert!(parse_bundle("float64 x\n").is_err());
+    }
+
+    #[test]
+    fn reports_line_of_bad_field() {
+        let err = parse_message("demo/Bad", "int32 ok\nint32[x] broken\n").unwrap_err();
+        assert_eq!(err.line, 2);
+        assert!(err.message.contains("array size"), "{}", err.message);
+    }
+}
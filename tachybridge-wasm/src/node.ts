@@ -1,11 +1,17 @@
 import { createRequire } from "node:module";
 import { BridgeClientCore } from "./client-core.js";
-import type { BridgeClientOptions, WasmProtocol, WebSocketLike } from "./types.js";
+import { resolveCodec } from "./codec.js";
+import type { BridgeClientOptions, JsonObject, WasmProtocol, WebSocketLike } from "./types.js";
 export { autoCodec, cborCodec, jsonCodec, resolveCodec } from "./codec.js";
+export { BridgeError } from "./errors.js";
+export type { BridgeErrorCode, BridgeErrorDetails } from "./errors.js";
 export type {
   BridgeCodec,
   BridgeCodecName,
   BridgeCodecOption,
+  BridgeIncomingEvent,
+  BridgeProtocolError,
+  BridgeProtocolErrorCode,
   BridgeReconnectContext,
   BridgeReconnectOptions,
   BridgeReconnectReason,
@@ -14,9 +20,21 @@ export type {
 
 const require = createRequire(import.meta.url);
 
+function nodeProtocol(): WasmProtocol {
+  return require("./wasm/node/bridge_wasm.js") as WasmProtocol;
+}
+
 async function loadNodeProtocol(): Promise<WasmProtocol> {
-  const wasmModule = require("./wasm/node/bridge_wasm.js") as WasmProtocol;
-  return wasmModule;
+  return nodeProtocol();
+}
+
+/** Encodes `value` with the Rust CBOR codec the client uses, e.g. for test servers. */
+export function encodeCbor(value: unknown): Uint8Array {
+  return resolveCodec("cbor", nodeProtocol()).encode(value as JsonObject) as Uint8Array;
+}
+
+export function decodeCbor(bytes: Uint8Array): unknown {
+  return resolveCodec("cbor", nodeProtocol()).decode(bytes);
 }
 
 async function loadNodeWebSocketCtor(): Promise<new (url: string) => WebSocketLike> {
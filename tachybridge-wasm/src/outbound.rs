@@ -0,0 +1,339 @@
+//! Frames sent while the connection is down.
+//!
+//! Without an open socket the client asks [`Queue::offer`] what to do with a
+//! frame. The [`Policy`] of its topic, service or action, or else of its op,
+//! decides: `drop` discards it, `keep_latest` keeps only the newest frame per
+//! op and target, and `keep_all` keeps every frame. Queued frames together
+//! stay under `max_bytes`. Once the client has rebound, it sends the queue
+//! oldest first and removes each frame before sending it, so no frame goes
+//! out twice.
+//!
+//! The queue only knows ids, ops and sizes; the client keeps the frames.
+
+use std::collections::{BTreeMap, VecDeque};
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::error::{BridgeError, ErrorCode};
+
+pub const DEFAULT_MAX_BYTES: f64 = 1024.0 * 1024.0;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Policy {
+    /// Discard, e.g. teleop commands that are stale once the link is back.
+    Drop,
+    /// Replace the queued frame with the same op and target.
+    KeepLatest,
+    /// Deliver every frame, e.g. configuration publishes.
+    KeepAll,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct QueueConfig {
+    /// Policy per rosbridge op, e.g. `publish`.
+    pub ops: Option<BTreeMap<String, Policy>>,
+    /// Policy per topic, service or action; takes precedence over `ops`.
+    pub targets: Option<BTreeMap<String, Policy>>,
+    /// Defaults to [`DEFAULT_MAX_BYTES`].
+    pub max_bytes: Option<f64>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Entry {
+    pub id: u32,
+    pub op: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+    pub bytes: f64,
+}
+
+/// What [`Queue::offer`] did with a frame.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
+pub enum Admission {
+    /// Queued as `id`; `replaces` is the older frame it evicted.
+    Queued {
+        id: u32,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        replaces: Option<u32>,
+    },
+    Dropped,
+    /// No policy covers the frame; sending it fails as before.
+    Refused,
+}
+
+#[derive(Debug)]
+pub struct Queue {
+    ops: BTreeMap<String, Policy>,
+    targets: BTreeMap<String, Policy>,
+    max_bytes: f64,
+    next_id: u32,
+    entries: VecDeque<Entry>,
+    bytes: f64,
+}
+
+impl Queue {
+    pub fn new(config: QueueConfig) -> Result<Self, BridgeError> {
+        let max_bytes = config.max_bytes.unwrap_or(DEFAULT_MAX_BYTES);
+        if max_bytes.is_nan() || max_bytes < 0.0 {
+            return Err(
+                BridgeError::invalid_input("maxBytes must be a non-negative number")
+                    .with_path("maxBytes"),
+            );
+        }
+        Ok(Self {
+            ops: config.ops.unwrap_or_default(),
+            targets: config.targets.unwrap_or_default(),
+            max_bytes,
+            next_id: 0,
+            entries: VecDeque::new(),
+            bytes: 0.0,
+        })
+    }
+
+    pub fn policy(&self, op: &str, target: Option<&str>) -> Option<Policy> {
+        target
+            .and_then(|target| self.targets.get(target))
+            .or_else(|| self.ops.get(op))
+            .copied()
+    }
+
+    /// Decides what happens to a frame of `bytes` encoded bytes. Fails when
+    /// it would not fit in `max_bytes`.
+    pub fn offer(
+        &mut self,
+        op: &str,
+        target: Option<&str>,
+        bytes: f64,
+    ) -> Result<Admission, BridgeError> {
+        let replaced = match self.policy(op, target) {
+            None => return Ok(Admission::Refused),
+            Some(Policy::Drop) => return Ok(Admission::Dropped),
+            Some(Policy::KeepLatest) => self
+                .entries
+                .iter()
+                .position(|entry| entry.op == op && entry.target.as_deref() == target),
+            Some(Policy::KeepAll) => None,
+        };
+        let freed = replaced.map_or(0.0, |index| self.entries[index].bytes);
+        if self.bytes - freed + bytes > self.max_bytes {
+            let what =
+                target.map_or_else(|| format!("`{op}`"), |target| format!("`{op}` on {target}"));
+            return Err(BridgeError::new(
+                ErrorCode::Disconnected,
+                format!(
+                    "outbound queue is full: {what} ({bytes} bytes) does not fit in {} of {} bytes",
+                    self.max_bytes - self.bytes + freed,
+                    self.max_bytes
+                ),
+            )
+            .with_op(op));
+        }
+
+        let replaces = replaced
+            .and_then(|index| self.entries.remove(index))
+            .map(|entry| entry.id);
+        self.next_id += 1;
+        self.entries.push_back(Entry {
+            id: self.next_id,
+            op: op.to_owned(),
+            target: target.map(str::to_owned),
+            bytes,
+        });
+        self.bytes += bytes - freed;
+        Ok(Admission::Queued {
+            id: self.next_id,
+            replaces,
+        })
+    }
+
+    /// The oldest queued frame.
+    pub fn front(&self) -> Option<&Entry> {
+        self.entries.front()
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        let Some(index) = self.entries.iter().position(|entry| entry.id == id) else {
+            return false;
+        };
+        if let Some(entry) = self.entries.remove(index) {
+            self.bytes -= entry.bytes;
+        }
+        true
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.bytes = 0.0;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn bytes(&self) -> f64 {
+        self.bytes
+    }
+}
+
+/// Outbound queue of one client; see [`Queue`]. The config takes the
+/// camelCase fields of `BridgeOutboundQueueOptions`.
+#[wasm_bindgen]
+pub struct OutboundQueue {
+    inner: Queue,
+}
+
+#[wasm_bindgen]
+impl OutboundQueue {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue) -> Result<OutboundQueue, JsValue> {
+        let config: QueueConfig = serde_wasm_bindgen::from_value(config).map_err(|e| {
+            BridgeError::invalid_input(format!("invalid outbound queue options: {e}"))
+        })?;
+        Ok(OutboundQueue {
+            inner: Queue::new(config)?,
+        })
+    }
+
+    /// Returns `{ action: "queued", id, replaces? }`, `{ action: "dropped" }`
+    /// or `{ action: "refused" }`.
+    pub fn offer(
+        &mut self,
+        op: String,
+        target: Option<String>,
+        bytes: f64,
+    ) -> Result<JsValue, JsValue> {
+        let admission = self.inner.offer(&op, target.as_deref(), bytes)?;
+        crate::to_js_object(&admission)
+    }
+
+    /// `{ id, op, target?, bytes }` of the oldest frame, or `undefined`.
+    pub fn front(&self) -> Result<JsValue, JsValue> {
+        match self.inner.front() {
+            Some(entry) => crate::to_js_object(entry),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    pub fn remove(&mut self, id: u32) -> bool {
+        self.inner.remove(id)
+    }
+
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.inner.len()
+    }
+
+    pub fn bytes(&self) -> f64 {
+        self.inner.bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn queue(ops: &[(&str, Policy)], targets: &[(&str, Policy)], max_bytes: f64) -> Queue {
+        let map = |pairs: &[(&str, Policy)]| {
+            Some(
+                pairs
+                    .iter()
+                    .map(|(name, policy)| (name.to_string(), *policy))
+                    .collect(),
+            )
+        };
+        Queue::new(QueueConfig {
+            ops: map(ops),
+            targets: map(targets),
+            max_bytes: Some(max_bytes),
+        })
+        .unwrap()
+    }
+
+    fn queued_ids(queue: &mut Queue) -> Vec<u32> {
+        let mut ids = Vec::new();
+        while let Some(id) = queue.front().map(|entry| entry.id) {
+            queue.remove(id);
+            ids.push(id);
+        }
+        ids
+    }
+
+    #[test]
+    fn targets_override_ops() {
+        let mut queue = queue(
+            &[("publish", Policy::KeepAll)],
+            &[("/cmd_vel", Policy::Drop)],
+            1000.0,
+        );
+        assert_eq!(
+            queue.offer("publish", Some("/cmd_vel"), 10.0).unwrap(),
+            Admission::Dropped
+        );
+        assert_eq!(
+            queue.offer("call_service", Some("/reset"), 10.0).unwrap(),
+            Admission::Refused
+        );
+        assert_eq!(
+            queue.offer("publish", Some("/config"), 10.0).unwrap(),
+            Admission::Queued {
+                id: 1,
+                replaces: None
+            }
+        );
+        assert_eq!(queue.len(), 1);
+    }
+
+    #[test]
+    fn keep_latest_replaces_per_op_and_target() {
+        let mut queue = queue(&[("publish", Policy::KeepLatest)], &[], 1000.0);
+        queue.offer("publish", Some("/a"), 10.0).unwrap();
+        queue.offer("publish", Some("/b"), 20.0).unwrap();
+        assert_eq!(
+            queue.offer("publish", Some("/a"), 30.0).unwrap(),
+            Admission::Queued {
+                id: 3,
+                replaces: Some(1)
+            }
+        );
+        assert_eq!(queue.bytes(), 50.0);
+        assert_eq!(queued_ids(&mut queue), [2, 3]);
+        assert_eq!(queue.len(), 0);
+        assert_eq!(queue.bytes(), 0.0);
+    }
+
+    #[test]
+    fn keep_all_is_capped_by_max_bytes() {
+        let mut queue = queue(&[("publish", Policy::KeepAll)], &[], 100.0);
+        queue.offer("publish", Some("/config"), 60.0).unwrap();
+        let err = queue.offer("publish", Some("/config"), 60.0).unwrap_err();
+        assert_eq!(err.code, ErrorCode::Disconnected);
+        assert_eq!(err.op.as_deref(), Some("publish"));
+        assert_eq!(
+            err.message,
+            "outbound queue is full: `publish` on /config (60 bytes) does not fit in 40 of 100 bytes"
+        );
+        queue.offer("publish", Some("/config"), 40.0).unwrap();
+        assert_eq!(queue.len(), 2);
+
+        queue.clear();
+        assert!(queue.front().is_none());
+        assert_eq!(queue.bytes(), 0.0);
+    }
+
+    #[test]
+    fn rejects_negative_max_bytes() {
+        let err = Queue::new(QueueConfig {
+            max_bytes: Some(-1.0),
+            ..QueueConfig::default()
+        })
+        .unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("maxBytes"));
+    }
+}
@@ -0,0 +1,326 @@
+//! Decoding of rosbridge `png` compressed frames.
+//!
+//! rosbridge serializes the outgoing frame to JSON, pads the UTF-8 bytes with
+//! `\n` to fill a near-square RGB image and sends that image as base64 PNG in
+//! `{op: "png", data}`. Decoding reverses each step: base64, PNG chunks, zlib
+//! inflate and scanline filters, after which the pixel bytes are the JSON text.
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+
+const SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', 0x0d, 0x0a, 0x1a, 0x0a];
+
+fn base64_value(byte: u8) -> Option<u8> {
+    match byte {
+        b'A'..=b'Z' => Some(byte - b'A'),
+        b'a'..=b'z' => Some(byte - b'a' + 26),
+        b'0'..=b'9' => Some(byte - b'0' + 52),
+        b'+' | b'-' => Some(62),
+        b'/' | b'_' => Some(63),
+        _ => None,
+    }
+}
+
+/// Decodes standard or URL-safe base64, ignoring whitespace and padding.
+pub fn decode_base64(text: &str) -> Result<Vec<u8>, String> {
+    let mut out = Vec::with_capacity(text.len() / 4 * 3);
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for byte in text.bytes() {
+        if byte.is_ascii_whitespace() || byte == b'=' {
+            continue;
+        }
+        let value = base64_value(byte)
+            .ok_or_else(|| format!("invalid base64 character {:?}", byte as char))?;
+        buffer = (buffer << 6) | u32::from(value);
+        bits += 6;
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+    Ok(out)
+}
+
+fn crc32(parts: &[&[u8]]) -> u32 {
+    let mut crc = !0u32;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        crc ^= u32::from(*byte);
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb8_8320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+struct Header {
+    width: usize,
+    height: usize,
+    /// Bytes per pixel at bit depth 8.
+    channels: usize,
+}
+
+fn parse_header(data: &[u8]) -> Result<Header, String> {
+    if data.len() != 13 {
+        return Err("IHDR chunk must be 13 bytes".to_owned());
+    }
+    let read = |at: usize| u32::from_be_bytes([data[at], data[at + 1], data[at + 2], data[at + 3]]);
+    let (bit_depth, color_type, interlace) = (data[8], data[9], data[12]);
+    if bit_depth != 8 {
+        return Err(format!("unsupported bit depth {bit_depth}"));
+    }
+    let channels = match color_type {
+        0 => 1,
+        2 => 3,
+        4 => 2,
+        6 => 4,
+        other => return Err(format!("unsupported color type {other}")),
+    };
+    if interlace != 0 {
+        return Err("interlaced images are not supported".to_owned());
+    }
+    Ok(Header {
+        width: read(0) as usize,
+        height: read(4) as usize,
+        channels,
+    })
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let (a, b, c) = (i16::from(left), i16::from(up), i16::from(up_left));
+    let p = a + b - c;
+    let (pa, pb, pc) = ((p - a).abs(), (p - b).abs(), (p - c).abs());
+    if pa <= pb && pa <= pc {
+        left
+    } else if pb <= pc {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Reverses the per-scanline filters in place and returns the raw pixels.
+fn unfilter(data: &[u8], header: &Header) -> Result<Vec<u8>, String> {
+    let bpp = header.channels;
+    let stride = header.width * bpp;
+    if data.len() != header.height * (stride + 1) {
+        return Err(format!(
+            "expected {} bytes of image data, found {}",
+            header.height * (stride + 1),
+            data.len()
+        ));
+    }
+    let mut pixels = vec![0u8; header.height * stride];
+    for row in 0..header.height {
+        let line = &data[row * (stride + 1)..(row + 1) * (stride + 1)];
+        let (filter, line) = (line[0], &line[1..]);
+        let (done, current) = pixels.split_at_mut(row * stride);
+        let current = &mut current[..stride];
+        let previous = (row > 0).then(|| &done[(row - 1) * stride..]);
+        for i in 0..stride {
+            let left = if i >= bpp { current[i - bpp] } else { 0 };
+            let up = previous.map_or(0, |prev| prev[i]);
+            let up_left = match previous {
+                Some(prev) if i >= bpp => prev[i - bpp],
+                _ => 0,
+            };
+            let predictor = match filter {
+                0 => 0,
+                1 => left,
+                2 => up,
+                3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                4 => paeth(left, up, up_left),
+                other => return Err(format!("invalid filter type {other} in row {row}")),
+            };
+            current[i] = line[i].wrapping_add(predictor);
+        }
+    }
+    Ok(pixels)
+}
+
+/// Decodes an 8-bit PNG and returns the color bytes of its pixels, row by
+/// row, with alpha dropped.
+pub fn decode_pixels(png: &[u8]) -> Result<Vec<u8>, String> {
+    let body = png
+        .strip_prefix(&SIGNATURE)
+        .ok_or("missing PNG signature")?;
+    let mut header = None;
+    let mut compressed = Vec::new();
+    let mut rest = body;
+    loop {
+        if rest.len() < 12 {
+            return Err("truncated chunk".to_owned());
+        }
+        let length = u32::from_be_bytes([rest[0], rest[1], rest[2], rest[3]]) as usize;
+        let kind = &rest[4..8];
+        if rest.len() < 12 + length {
+            return Err(format!("truncated {} chunk", String::from_utf8_lossy(kind)));
+        }
+        let data = &rest[8..8 + length];
+        let crc = &rest[8 + length..12 + length];
+        if crc32(&[kind, data]).to_be_bytes() != crc {
+            return Err(format!(
+                "CRC mismatch in {} chunk",
+                String::from_utf8_lossy(kind)
+            ));
+        }
+        match kind {
+            b"IHDR" => header = Some(parse_header(data)?),
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = &rest[12 + length..];
+    }
+    let header = header.ok_or("missing IHDR chunk")?;
+    let expected = header
+        .width
+        .checked_mul(header.channels)
+        .and_then(|stride| stride.checked_add(1)?.checked_mul(header.height))
+        .ok_or("image dimensions are too large")?;
+    let data = miniz_oxide::inflate::decompress_to_vec_zlib_with_limit(&compressed, expected)
+        .map_err(|e| format!("cannot inflate image data: {:?}", e.status))?;
+    let pixels = unfilter(&data, &header)?;
+    Ok(match header.channels {
+        // Gray + alpha and RGBA: keep the color channels.
+        2 => pixels.chunks(2).map(|px| px[0]).collect(),
+        4 => pixels.chunks(4).flat_map(|px| &px[..3]).copied().collect(),
+        _ => pixels,
+    })
+}
+
+/// Recovers the JSON text of a rosbridge `png` frame from its base64 `data`.
+pub fn decode_frame_text(data: &str) -> Result<String, String> {
+    let mut bytes = decode_pixels(&decode_base64(data)?)?;
+    // rosbridge pads the text with newlines to fill the last row.
+    while bytes.last() == Some(&b'\n') {
+        bytes.pop();
+    }
+    String::from_utf8(bytes).map_err(|e| format!("image data is not UTF-8 text: {e}"))
+}
+
+/// Decodes the base64 `data` of a rosbridge `png` frame to the JSON text of
+/// the original frame.
+#[wasm_bindgen]
+pub fn decode_png(data: String) -> Result<String, JsValue> {
+    decode_frame_text(&data).map_err(|e| {
+        BridgeError::protocol_violation(format!("invalid png frame: {e}"))
+            .with_op("png")
+            .into()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn encode_base64(bytes: &[u8]) -> String {
+        const ALPHABET: &[u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+        let mut out = String::new();
+        for chunk in bytes.chunks(3) {
+            let n = chunk
+                .iter()
+                .enumerate()
+                .fold(0u32, |n, (i, b)| n | u32::from(*b) << (16 - 8 * i));
+            for i in 0..=chunk.len() {
+                out.push(ALPHABET[(n >> (18 - 6 * i)) as usize & 63] as char);
+            }
+        }
+        while !out.len().is_multiple_of(4) {
+            out.push('=');
+        }
+        out
+    }
+
+    fn chunk(out: &mut Vec<u8>, kind: &[u8], data: &[u8]) {
+        out.extend_from_slice(&(data.len() as u32).to_be_bytes());
+        out.extend_from_slice(kind);
+        out.extend_from_slice(data);
+        out.extend_from_slice(&crc32(&[kind, data]).to_be_bytes());
+    }
+
+    /// Encodes RGB pixels, cycling through every filter type by row.
+    fn encode_png(pixels: &[u8], width: usize, height: usize) -> Vec<u8> {
+        let stride = width * 3;
+        let mut raw = Vec::new();
+        for row in 0..height {
+            let filter = (row % 5) as u8;
+            raw.push(filter);
+            let line = &pixels[row * stride..(row + 1) * stride];
+            for i in 0..stride {
+                let left = if i >= 3 { line[i - 3] } else { 0 };
+                let up = if row > 0 {
+                    pixels[(row - 1) * stride + i]
+                } else {
+                    0
+                };
+                let up_left = if row > 0 && i >= 3 {
+                    pixels[(row - 1) * stride + i - 3]
+                } else {
+                    0
+                };
+                let predictor = match filter {
+                    0 => 0,
+                    1 => left,
+                    2 => up,
+                    3 => ((u16::from(left) + u16::from(up)) / 2) as u8,
+                    _ => paeth(left, up, up_left),
+                };
+                raw.push(line[i].wrapping_sub(predictor));
+            }
+        }
+        let mut header = Vec::new();
+        header.extend_from_slice(&(width as u32).to_be_bytes());
+        header.extend_from_slice(&(height as u32).to_be_bytes());
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+        let mut png = SIGNATURE.to_vec();
+        chunk(&mut png, b"IHDR", &header);
+        chunk(
+            &mut png,
+            b"IDAT",
+            &miniz_oxide::deflate::compress_to_vec_zlib(&raw, 6),
+        );
+        chunk(&mut png, b"IEND", &[]);
+        png
+    }
+
+    /// Mirrors rosbridge's `pngcompression.encode`.
+    fn rosbridge_encode(text: &str) -> String {
+        let bytes = text.as_bytes();
+        let width = ((bytes.len() as f64 / 3.0).sqrt().floor() as usize).max(1);
+        let height = (bytes.len() as f64 / 3.0 / width as f64).ceil() as usize;
+        let mut padded = bytes.to_vec();
+        padded.resize(width * height * 3, b'\n');
+        encode_base64(&encode_png(&padded, width, height))
+    }
+
+    #[test]
+    fn recovers_rosbridge_frames() {
+        let frame = r#"{"op": "publish", "topic": "/map", "msg": {"data": [0, 100, -1, 42], "name": "größe"}}"#;
+        assert_eq!(decode_frame_text(&rosbridge_encode(frame)).unwrap(), frame);
+    }
+
+    #[test]
+    fn decodes_base64_variants() {
+        assert_eq!(decode_base64("aGk=\n").unwrap(), b"hi");
+        assert_eq!(decode_base64("-_8").unwrap(), [0xfb, 0xff]);
+        assert!(decode_base64("a*b").is_err());
+    }
+
+    #[test]
+    fn rejects_corrupt_images() {
+        let mut png = decode_base64(&rosbridge_encode(r#"{"op": "publish"}"#)).unwrap();
+        assert_eq!(
+            decode_pixels(&png[1..]).unwrap_err(),
+            "missing PNG signature"
+        );
+        let last = png.len() - 20;
+        png[last] ^= 0xff;
+        assert!(decode_pixels(&png).unwrap_err().contains("CRC mismatch"));
+    }
+}
@@ -0,0 +1,284 @@
+//! Advertised topics and the advertise-before-publish decision.
+//!
+//! rosbridge drops `publish` ops for topics the client never advertised.
+//! [`Registry`] remembers every advertisement (for re-advertising after a
+//! reconnect) and, with auto-advertise enabled, tells the client to advertise a
+//! topic right before its first publish.
+
+use std::collections::BTreeMap;
+use std::fmt;
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+use crate::msgdef;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Advertisement {
+    pub topic: String,
+    #[serde(rename = "type")]
+    pub msg_type: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub latch: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub queue_size: Option<u32>,
+    /// Changes with every advertisement, so a stale publisher handle cannot
+    /// unadvertise a newer one.
+    pub token: u32,
+}
+
+/// What the client has to do for one `publish`.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PublishPlan {
+    /// Message type of the topic, if known, for validation and defaults.
+    #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+    pub msg_type: Option<String>,
+    /// Advertisement to send before the publish.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub advertise: Option<Advertisement>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublishError {
+    /// The publish names a different type than the topic was advertised with.
+    TypeMismatch {
+        topic: String,
+        advertised: String,
+        requested: String,
+    },
+    /// Auto-advertise needs a type for a topic that was never advertised.
+    MissingType { topic: String },
+}
+
+impl fmt::Display for PublishError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::TypeMismatch {
+                topic,
+                advertised,
+                requested,
+            } => write!(
+                f,
+                "cannot publish {requested} on {topic}: it is advertised as {advertised}"
+            ),
+            Self::MissingType { topic } => write!(
+                f,
+                "cannot auto-advertise {topic}: pass the message type to publish or advertise the topic first"
+            ),
+        }
+    }
+}
+
+/// `pkg/Name` and `pkg/msg/Name` name the same message type.
+fn same_type(a: &str, b: &str) -> bool {
+    let canonical = |name: &str| msgdef::canonical_name(name).unwrap_or_else(|| name.to_owned());
+    canonical(a) == canonical(b)
+}
+
+#[derive(Default)]
+pub struct Registry {
+    next_token: u32,
+    topics: BTreeMap<String, Advertisement>,
+}
+
+impl Registry {
+    /// Records an explicit advertisement, replacing any earlier one.
+    pub fn advertise(
+        &mut self,
+        topic: &str,
+        msg_type: &str,
+        latch: Option<bool>,
+        queue_size: Option<u32>,
+    ) -> Advertisement {
+        self.next_token += 1;
+        let advertisement = Advertisement {
+            topic: topic.to_owned(),
+            msg_type: msg_type.to_owned(),
+            latch,
+            queue_size,
+            token: self.next_token,
+        };
+        self.topics.insert(topic.to_owned(), advertisement.clone());
+        advertisement
+    }
+
+    /// Forgets `topic`; with a `token`, only if it still belongs to that
+    /// advertisement. Returns whether `unadvertise` should be sent.
+    pub fn unadvertise(&mut self, topic: &str, token: Option<u32>) -> bool {
+        match self.topics.get(topic) {
+            Some(current) if token.is_none_or(|token| token == current.token) => {
+                self.topics.remove(topic);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    /// Decides how to publish on `topic`, advertising it first when
+    /// `auto_advertise` is on and it has not been advertised yet.
+    pub fn plan_publish(
+        &mut self,
+        topic: &str,
+        msg_type: Option<&str>,
+        auto_advertise: bool,
+    ) -> Result<PublishPlan, PublishError> {
+        if let Some(current) = self.topics.get(topic) {
+            if let Some(requested) = msg_type.filter(|t| !same_type(t, &current.msg_type)) {
+                return Err(PublishError::TypeMismatch {
+                    topic: topic.to_owned(),
+                    advertised: current.msg_type.clone(),
+                    requested: requested.to_owned(),
+                });
+            }
+            return Ok(PublishPlan {
+                msg_type: Some(current.msg_type.clone()),
+                advertise: None,
+            });
+        }
+        if !auto_advertise {
+            return Ok(PublishPlan {
+                msg_type: msg_type.map(str::to_owned),
+                advertise: None,
+            });
+        }
+        let Some(msg_type) = msg_type else {
+            return Err(PublishError::MissingType {
+                topic: topic.to_owned(),
+            });
+        };
+        let advertisement = self.advertise(topic, msg_type, None, None);
+        Ok(PublishPlan {
+            msg_type: Some(advertisement.msg_type.clone()),
+            advertise: Some(advertisement),
+        })
+    }
+
+    pub fn type_of(&self, topic: &str) -> Option<&str> {
+        self.topics.get(topic).map(|ad| ad.msg_type.as_str())
+    }
+
+    /// Every current advertisement, ordered by topic.
+    pub fn advertisements(&self) -> Vec<Advertisement> {
+        self.topics.values().cloned().collect()
+    }
+}
+
+/// Advertised topics of one client; see [`Registry`].
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct PublisherRegistry {
+    inner: Registry,
+}
+
+#[wasm_bindgen]
+impl PublisherRegistry {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> PublisherRegistry {
+        PublisherRegistry::default()
+    }
+
+    /// Records an advertisement; returns `{ topic, type, latch?, queue_size?, token }`.
+    pub fn advertise(
+        &mut self,
+        topic: String,
+        msg_type: String,
+        latch: Option<bool>,
+        queue_size: Option<u32>,
+    ) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.advertise(&topic, &msg_type, latch, queue_size))
+    }
+
+    pub fn unadvertise(&mut self, topic: String, token: Option<u32>) -> bool {
+        self.inner.unadvertise(&topic, token)
+    }
+
+    /// Returns `{ type?, advertise? }`, or throws when the publish cannot go out.
+    pub fn plan_publish(
+        &mut self,
+        topic: String,
+        msg_type: Option<String>,
+        auto_advertise: bool,
+    ) -> Result<JsValue, JsValue> {
+        let plan = self
+            .inner
+            .plan_publish(&topic, msg_type.as_deref(), auto_advertise)
+            .map_err(|e| {
+                BridgeError::invalid_input(e.to_string())
+                    .with_op("publish")
+                    .with_path(topic.as_str())
+            })?;
+        crate::to_js_object(&plan)
+    }
+
+    pub fn type_of(&self, topic: String) -> Option<String> {
+        self.inner.type_of(&topic).map(str::to_owned)
+    }
+
+    pub fn advertisements(&self) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.advertisements())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn auto_advertises_once_per_topic() {
+        let mut registry = Registry::default();
+        let plan = registry
+            .plan_publish("/cmd_vel", Some("geometry_msgs/Twist"), true)
+            .unwrap();
+        let advertised = plan.advertise.expect("first publish advertises");
+        assert_eq!(advertised.msg_type, "geometry_msgs/Twist");
+
+        let plan = registry.plan_publish("/cmd_vel", None, true).unwrap();
+        assert_eq!(plan.advertise, None);
+        assert_eq!(plan.msg_type.as_deref(), Some("geometry_msgs/Twist"));
+        // `pkg/Name` and `pkg/msg/Name` are interchangeable.
+        assert!(registry
+            .plan_publish("/cmd_vel", Some("geometry_msgs/msg/Twist"), true)
+            .is_ok());
+    }
+
+    #[test]
+    fn rejects_missing_and_conflicting_types() {
+        let mut registry = Registry::default();
+        assert_eq!(
+            registry.plan_publish("/odom", None, true),
+            Err(PublishError::MissingType {
+                topic: "/odom".into()
+            })
+        );
+        registry.advertise("/odom", "nav_msgs/msg/Odometry", None, None);
+        let err = registry
+            .plan_publish("/odom", Some("std_msgs/msg/String"), false)
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "cannot publish std_msgs/msg/String on /odom: it is advertised as nav_msgs/msg/Odometry"
+        );
+    }
+
+    #[test]
+    fn leaves_unadvertised_topics_alone_without_auto_advertise() {
+        let mut registry = Registry::default();
+        let plan = registry
+            .plan_publish("/raw", Some("std_msgs/msg/String"), false)
+            .unwrap();
+        assert_eq!(plan.advertise, None);
+        assert_eq!(registry.type_of("/raw"), None);
+    }
+
+    #[test]
+    fn stale_tokens_do_not_unadvertise() {
+        let mut registry = Registry::default();
+        let first = registry.advertise("/map", "nav_msgs/msg/OccupancyGrid", None, None);
+        let second = registry.advertise("/map", "nav_msgs/msg/OccupancyGrid", Some(true), None);
+        assert!(!registry.unadvertise("/map", Some(first.token)));
+        assert_eq!(registry.advertisements(), std::slice::from_ref(&second));
+        assert!(registry.unadvertise("/map", Some(second.token)));
+        assert!(!registry.unadvertise("/map", None));
+    }
+}
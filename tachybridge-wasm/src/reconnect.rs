@@ -0,0 +1,430 @@
+//! Reconnect timing and the circuit breaker around it.
+//!
+//! [`Policy`] decides after each failed connection whether and when to try
+//! again. Consecutive failures grow the delay along a [`Strategy`]. Once more
+//! than `max_attempts` have failed the circuit opens: the policy either gives
+//! up or, with `half_open_after_ms`, lets a single probe through after that
+//! cooldown. A successful connection closes the circuit again.
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Strategy {
+    /// `initial_delay_ms * multiplier^(n-1)`, spread by `jitter_ratio`.
+    #[default]
+    Exponential,
+    /// "Decorrelated jitter": a random delay between `initial_delay_ms` and
+    /// three times the previous one.
+    Decorrelated,
+    /// The delays of `schedule_ms` in order; the last one repeats.
+    Fixed,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PolicyConfig {
+    pub strategy: Strategy,
+    pub initial_delay_ms: f64,
+    pub max_delay_ms: f64,
+    pub multiplier: f64,
+    pub jitter_ratio: f64,
+    pub schedule_ms: Option<Vec<f64>>,
+    pub max_attempts: Option<u32>,
+    pub half_open_after_ms: Option<f64>,
+    /// Makes jitter reproducible; without it callers pass their own samples.
+    pub seed: Option<f64>,
+}
+
+impl Default for PolicyConfig {
+    fn default() -> Self {
+        Self {
+            strategy: Strategy::Exponential,
+            initial_delay_ms: 500.0,
+            max_delay_ms: 30_000.0,
+            multiplier: 2.0,
+            jitter_ratio: 0.2,
+            schedule_ms: None,
+            max_attempts: None,
+            half_open_after_ms: None,
+            seed: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    /// Waiting out the cooldown, or given up.
+    Open,
+    /// The probe attempt is in flight.
+    HalfOpen,
+}
+
+impl CircuitState {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Closed => "closed",
+            Self::Open => "open",
+            Self::HalfOpen => "half_open",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Decision {
+    /// Number of consecutive failures so far.
+    pub attempt: u32,
+    pub delay_ms: f64,
+    /// The next attempt is a half-open probe.
+    pub probe: bool,
+}
+
+/// SplitMix64; small, fast and good enough for jitter.
+struct Rng(u64);
+
+impl Rng {
+    /// A sample in `[0, 1)`.
+    fn next_f64(&mut self) -> f64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^= z >> 31;
+        (z >> 11) as f64 / (1u64 << 53) as f64
+    }
+}
+
+pub struct Policy {
+    config: PolicyConfig,
+    state: CircuitState,
+    failures: u32,
+    previous_delay_ms: f64,
+    rng: Option<Rng>,
+}
+
+impl Policy {
+    pub fn new(config: PolicyConfig) -> Result<Self, BridgeError> {
+        let schedule = config.schedule_ms.as_deref().unwrap_or_default();
+        if config.strategy == Strategy::Fixed && schedule.is_empty() {
+            return Err(BridgeError::invalid_input(
+                "the fixed strategy needs a non-empty scheduleMs",
+            )
+            .with_path("scheduleMs"));
+        }
+        if let Some(index) = schedule
+            .iter()
+            .position(|delay| !delay.is_finite() || *delay < 0.0)
+        {
+            return Err(BridgeError::invalid_input(format!(
+                "scheduleMs[{index}] must be a non-negative number of milliseconds"
+            ))
+            .with_path(format!("scheduleMs.{index}")));
+        }
+        let rng = config.seed.map(|seed| Rng(seed.to_bits()));
+        Ok(Self {
+            config,
+            state: CircuitState::Closed,
+            failures: 0,
+            previous_delay_ms: 0.0,
+            rng,
+        })
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.failures
+    }
+
+    /// Records a failed connection and decides when to try again; `None`
+    /// means give up. `retry_after_ms` is a lower bound the server asked for.
+    /// `random` in `[0, 1)` is the jitter sample when no seed is configured.
+    pub fn on_failure(
+        &mut self,
+        retry_after_ms: Option<f64>,
+        random: Option<f64>,
+    ) -> Option<Decision> {
+        self.failures += 1;
+        let attempt = self.failures;
+        let sample = match &mut self.rng {
+            Some(rng) => rng.next_f64(),
+            None => random.unwrap_or(0.5).clamp(0.0, 1.0),
+        };
+        let floor = retry_after_ms.filter(|ms| ms.is_finite()).unwrap_or(0.0);
+
+        let exhausted = self.config.max_attempts.is_some_and(|max| attempt > max);
+        if self.state != CircuitState::Closed || exhausted {
+            self.state = CircuitState::Open;
+            let cooldown = self.config.half_open_after_ms?;
+            return Some(Decision {
+                attempt,
+                delay_ms: cooldown.max(floor).max(0.0),
+                probe: true,
+            });
+        }
+
+        let delay_ms = self.strategy_delay(attempt, sample);
+        self.previous_delay_ms = delay_ms;
+        Some(Decision {
+            attempt,
+            delay_ms: delay_ms.max(floor),
+            probe: false,
+        })
+    }
+
+    /// The scheduled attempt starts; an open circuit lets it through as a probe.
+    pub fn on_attempt(&mut self) {
+        if self.state == CircuitState::Open && self.config.half_open_after_ms.is_some() {
+            self.state = CircuitState::HalfOpen;
+        }
+    }
+
+    /// Closes the circuit after a successful connection or a manual close.
+    pub fn reset(&mut self) {
+        self.state = CircuitState::Closed;
+        self.failures = 0;
+        self.previous_delay_ms = 0.0;
+    }
+
+    fn strategy_delay(&self, attempt: u32, sample: f64) -> f64 {
+        let initial = self.config.initial_delay_ms.max(0.0);
+        let max = self.config.max_delay_ms.max(initial);
+        match self.config.strategy {
+            Strategy::Exponential => {
+                let multiplier = if self.config.multiplier > 0.0 {
+                    self.config.multiplier
+                } else {
+                    1.0
+                };
+                let exponent = attempt.saturating_sub(1).min(i32::MAX as u32) as i32;
+                let base = (initial * multiplier.powi(exponent)).min(max);
+                let jitter = self.config.jitter_ratio.clamp(0.0, 1.0);
+                if jitter == 0.0 {
+                    return base.floor().max(0.0);
+                }
+                let spread = (sample * 2.0 - 1.0) * jitter;
+                (base * (1.0 + spread)).floor().clamp(0.0, max)
+            }
+            Strategy::Decorrelated => {
+                let previous = if self.previous_delay_ms > 0.0 {
+                    self.previous_delay_ms
+                } else {
+                    initial
+                };
+                let high = (previous * 3.0).max(initial);
+                (initial + sample * (high - initial)).floor().min(max)
+            }
+            Strategy::Fixed => {
+                let schedule = self.config.schedule_ms.as_deref().unwrap_or_default();
+                let index = (attempt as usize - 1).min(schedule.len() - 1);
+                schedule[index]
+            }
+        }
+    }
+}
+
+/// Reconnect policy of one client; see [`Policy`]. The config takes the
+/// camelCase fields of `BridgeReconnectOptions`.
+#[wasm_bindgen]
+pub struct ReconnectPolicy {
+    inner: Policy,
+}
+
+#[wasm_bindgen]
+impl ReconnectPolicy {
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue) -> Result<ReconnectPolicy, JsValue> {
+        let config: PolicyConfig = serde_wasm_bindgen::from_value(config)
+            .map_err(|e| BridgeError::invalid_input(format!("invalid reconnect options: {e}")))?;
+        Ok(ReconnectPolicy {
+            inner: Policy::new(config)?,
+        })
+    }
+
+    /// `closed`, `open` or `half_open`.
+    pub fn state(&self) -> String {
+        self.inner.state().as_str().to_owned()
+    }
+
+    pub fn failures(&self) -> u32 {
+        self.inner.failures()
+    }
+
+    /// Returns `{ attempt, delay_ms, probe }`, or `undefined` to give up.
+    pub fn on_failure(
+        &mut self,
+        retry_after_ms: Option<f64>,
+        random: Option<f64>,
+    ) -> Result<JsValue, JsValue> {
+        match self.inner.on_failure(retry_after_ms, random) {
+            Some(decision) => crate::to_js_object(&decision),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    pub fn on_attempt(&mut self) {
+        self.inner.on_attempt();
+    }
+
+    pub fn reset(&mut self) {
+        self.inner.reset();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(config: PolicyConfig) -> Policy {
+        Policy::new(config).unwrap()
+    }
+
+    fn delays(policy: &mut Policy, random: &[f64]) -> Vec<f64> {
+        random
+            .iter()
+            .map(|sample| policy.on_failure(None, Some(*sample)).unwrap().delay_ms)
+            .collect()
+    }
+
+    #[test]
+    fn exponential_backoff_is_capped_and_jittered() {
+        let mut plain = policy(PolicyConfig {
+            initial_delay_ms: 100.0,
+            max_delay_ms: 250.0,
+            jitter_ratio: 0.0,
+            ..PolicyConfig::default()
+        });
+        assert_eq!(delays(&mut plain, &[0.5; 4]), [100.0, 200.0, 250.0, 250.0]);
+
+        let mut jittered = policy(PolicyConfig {
+            initial_delay_ms: 100.0,
+            max_delay_ms: 1000.0,
+            ..PolicyConfig::default()
+        });
+        assert_eq!(delays(&mut jittered, &[1.0, 0.0]), [120.0, 160.0]);
+    }
+
+    #[test]
+    fn decorrelated_jitter_stays_between_base_and_three_times_previous() {
+        let mut policy = policy(PolicyConfig {
+            strategy: Strategy::Decorrelated,
+            initial_delay_ms: 100.0,
+            max_delay_ms: 1000.0,
+            ..PolicyConfig::default()
+        });
+        assert_eq!(
+            delays(&mut policy, &[1.0, 1.0, 0.0, 1.0]),
+            [300.0, 900.0, 100.0, 300.0]
+        );
+    }
+
+    #[test]
+    fn fixed_schedule_repeats_its_last_delay() {
+        let mut policy = policy(PolicyConfig {
+            strategy: Strategy::Fixed,
+            schedule_ms: Some(vec![0.0, 1000.0, 5000.0]),
+            ..PolicyConfig::default()
+        });
+        assert_eq!(
+            delays(&mut policy, &[0.5; 4]),
+            [0.0, 1000.0, 5000.0, 5000.0]
+        );
+
+        let err = Policy::new(PolicyConfig {
+            strategy: Strategy::Fixed,
+            ..PolicyConfig::default()
+        })
+        .err()
+        .unwrap();
+        assert_eq!(err.path.as_deref(), Some("scheduleMs"));
+    }
+
+    #[test]
+    fn seeded_policies_repeat_their_delays() {
+        let config = PolicyConfig {
+            initial_delay_ms: 100.0,
+            max_delay_ms: 1000.0,
+            seed: Some(42.0),
+            ..PolicyConfig::default()
+        };
+        let expected = [107.0, 161.0, 378.0, 849.0];
+        assert_eq!(delays(&mut policy(config.clone()), &[0.0; 4]), expected);
+        assert_eq!(
+            delays(&mut policy(config), &[1.0; 4]),
+            expected,
+            "samples are ignored"
+        );
+    }
+
+    #[test]
+    fn retry_after_is_a_lower_bound() {
+        let mut policy = policy(PolicyConfig {
+            jitter_ratio: 0.0,
+            ..PolicyConfig::default()
+        });
+        assert_eq!(
+            policy.on_failure(Some(2000.0), None).unwrap().delay_ms,
+            2000.0
+        );
+        assert_eq!(
+            policy.on_failure(Some(10.0), None).unwrap().delay_ms,
+            1000.0
+        );
+    }
+
+    #[test]
+    fn gives_up_after_max_attempts_without_half_open() {
+        let mut policy = policy(PolicyConfig {
+            max_attempts: Some(2),
+            ..PolicyConfig::default()
+        });
+        assert!(policy.on_failure(None, None).is_some());
+        assert!(policy.on_failure(None, None).is_some());
+        assert_eq!(policy.on_failure(None, None), None);
+        assert_eq!(policy.state(), CircuitState::Open);
+        policy.reset();
+        assert_eq!(policy.state(), CircuitState::Closed);
+        assert_eq!(policy.on_failure(None, None).unwrap().attempt, 1);
+    }
+
+    #[test]
+    fn open_circuit_probes_after_cooldown() {
+        let mut policy = policy(PolicyConfig {
+            jitter_ratio: 0.0,
+            max_attempts: Some(1),
+            half_open_after_ms: Some(60_000.0),
+            ..PolicyConfig::default()
+        });
+        assert_eq!(
+            policy.on_failure(None, None),
+            Some(Decision {
+                attempt: 1,
+                delay_ms: 500.0,
+                probe: false
+            })
+        );
+        policy.on_attempt();
+        assert_eq!(policy.state(), CircuitState::Closed);
+
+        let open = policy.on_failure(None, None).unwrap();
+        assert!(open.probe);
+        assert_eq!(open.delay_ms, 60_000.0);
+        assert_eq!(policy.state(), CircuitState::Open);
+        policy.on_attempt();
+        assert_eq!(policy.state(), CircuitState::HalfOpen);
+
+        // A failed probe opens the circuit again.
+        assert!(policy.on_failure(None, None).unwrap().probe);
+        assert_eq!(policy.state(), CircuitState::Open);
+        policy.on_attempt();
+        policy.reset();
+        assert_eq!(policy.state(), CircuitState::Closed);
+    }
+}
@@ -0,0 +1,911 @@
+//! Correlation of bridge replies with pending requests.
+//!
+//! [`Tracker`] owns everything about a request except its promise: the id,
+//! the rules that match a reply to it, its deadline and what happens to it on
+//! disconnect. The client asks it which requests to settle and keeps only the
+//! resolve/reject callbacks.
+//!
+//! A dropped connection interrupts every pending request. A [`Retry`] policy
+//! overrides that per request: such requests are held over the disconnect
+//! and, once [`Tracker::reconnected`] reports them, sent again with the same
+//! id. The client reports each resend through [`Tracker::resent`].
+
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RequestKind {
+    CallService,
+    SendActionGoal,
+    CancelActionGoal,
+    ExecuteCli,
+}
+
+impl RequestKind {
+    /// The kind of request sent with rosbridge op `op`.
+    pub fn from_op(op: &str) -> Option<Self> {
+        match op {
+            "call_service" => Some(Self::CallService),
+            "send_action_goal" => Some(Self::SendActionGoal),
+            "cancel_action_goal" => Some(Self::CancelActionGoal),
+            "execute_cli" => Some(Self::ExecuteCli),
+            _ => None,
+        }
+    }
+
+    pub fn as_op(self) -> &'static str {
+        match self {
+            Self::CallService => "call_service",
+            Self::SendActionGoal => "send_action_goal",
+            Self::CancelActionGoal => "cancel_action_goal",
+            Self::ExecuteCli => "execute_cli",
+        }
+    }
+
+    fn id_prefix(self) -> &'static str {
+        match self {
+            Self::CallService => "svc",
+            Self::SendActionGoal => "action",
+            Self::CancelActionGoal => "cancel",
+            Self::ExecuteCli => "cli",
+        }
+    }
+}
+
+/// What happens to a request when the connection drops.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Retry {
+    /// Rejected as soon as the connection drops.
+    Never,
+    /// Sent again after every reconnect; for requests that are safe to repeat.
+    Idempotent,
+    /// Sent again after at most `attempts` reconnects: `backoff_ms` after the
+    /// first, twice as long after each further one.
+    Times { attempts: u32, backoff_ms: f64 },
+}
+
+/// `"never"`, `"idempotent"` or `{ attempts, backoffMs? }` from JS.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum RetryOption {
+    Named(String),
+    Times {
+        attempts: u32,
+        #[serde(rename = "backoffMs")]
+        backoff_ms: Option<f64>,
+    },
+}
+
+impl Retry {
+    fn from_option(option: RetryOption) -> Result<Self, BridgeError> {
+        match option {
+            RetryOption::Named(name) => match name.as_str() {
+                "never" => Ok(Self::Never),
+                "idempotent" => Ok(Self::Idempotent),
+                _ => Err(BridgeError::invalid_input(format!(
+                    "unknown retry policy `{name}`; expected `never`, `idempotent` or {{ attempts, backoffMs }}"
+                ))
+                .with_path("retry")),
+            },
+            RetryOption::Times {
+                attempts,
+                backoff_ms,
+            } => {
+                let backoff_ms = backoff_ms.unwrap_or(0.0);
+                if backoff_ms.is_nan() || backoff_ms < 0.0 {
+                    return Err(BridgeError::invalid_input(
+                        "retry backoffMs must be a non-negative number",
+                    )
+                    .with_path("retry.backoffMs"));
+                }
+                Ok(Self::Times {
+                    attempts,
+                    backoff_ms,
+                })
+            }
+        }
+    }
+}
+
+/// A request that left the tracker, and why the client should settle it.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Settled {
+    pub id: String,
+    pub kind: RequestKind,
+    /// Service or action name.
+    pub target: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub session_id: Option<String>,
+}
+
+/// A request held over a disconnect, and how to pick it up again.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Resumed {
+    pub request: Settled,
+    /// Send the request again after this many ms. `None` for action goals
+    /// kept without a retry policy, which are re-attached instead.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub resend_after_ms: Option<f64>,
+}
+
+/// What a disconnect does to one pending request.
+#[derive(PartialEq)]
+enum OnDisconnect {
+    Interrupt,
+    /// Keep it until the next reconnect picks it up.
+    Hold,
+}
+
+struct Pending {
+    kind: RequestKind,
+    target: String,
+    session_id: Option<String>,
+    deadline_ms: Option<f64>,
+    /// Tracking order, so matches and expiries favour older requests.
+    seq: u64,
+    retry: Option<Retry>,
+    /// Resends actually transmitted; see [`Tracker::resent`].
+    resends: u32,
+    held: bool,
+}
+
+impl Pending {
+    /// Without a retry policy, requests behave as with [`Retry::Never`],
+    /// except action goals kept for re-attaching.
+    fn on_disconnect(&self, keep_goals: bool) -> OnDisconnect {
+        match self.retry {
+            Some(Retry::Never) => OnDisconnect::Interrupt,
+            Some(Retry::Idempotent) => OnDisconnect::Hold,
+            Some(Retry::Times { attempts, .. }) if self.resends < attempts => OnDisconnect::Hold,
+            Some(Retry::Times { .. }) => OnDisconnect::Interrupt,
+            None if keep_goals && self.kind == RequestKind::SendActionGoal => OnDisconnect::Hold,
+            None => OnDisconnect::Interrupt,
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct Tracker {
+    /// Keeps allocated ids apart from those of other clients on the bridge.
+    namespace: Option<String>,
+    counter: u64,
+    pending: HashMap<String, Pending>,
+}
+
+impl Tracker {
+    pub fn with_namespace(namespace: impl Into<String>) -> Self {
+        Self {
+            namespace: Some(namespace.into()),
+            ..Self::default()
+        }
+    }
+
+    /// Starts tracking a request and returns its id, allocating one unless
+    /// given. Requests without a positive `timeout_ms` never expire.
+    pub fn track(
+        &mut self,
+        kind: RequestKind,
+        id: Option<String>,
+        target: &str,
+        session_id: Option<&str>,
+        timeout_ms: Option<f64>,
+        now_ms: f64,
+    ) -> Result<String, BridgeError> {
+        self.counter += 1;
+        let id = id.unwrap_or_else(|| match &self.namespace {
+            Some(namespace) => format!("{}-{namespace}-{}", kind.id_prefix(), self.counter),
+            None => format!("{}-{}", kind.id_prefix(), self.counter),
+        });
+        if self.pending.contains_key(&id) {
+            return Err(
+                BridgeError::invalid_input(format!("request id {id} is already pending"))
+                    .with_request_id(id),
+            );
+        }
+        self.pending.insert(
+            id.clone(),
+            Pending {
+                kind,
+                target: target.to_owned(),
+                session_id: session_id.map(str::to_owned),
+                deadline_ms: timeout_ms.filter(|ms| *ms > 0.0).map(|ms| now_ms + ms),
+                seq: self.counter,
+                retry: None,
+                resends: 0,
+                held: false,
+            },
+        );
+        Ok(id)
+    }
+
+    /// Sets what a disconnect does to pending request `id`.
+    pub fn set_retry(&mut self, id: &str, retry: Retry) -> Result<(), BridgeError> {
+        let pending = self.pending.get_mut(id).ok_or_else(|| {
+            BridgeError::invalid_input(format!("request {id} is not pending")).with_request_id(id)
+        })?;
+        pending.retry = Some(retry);
+        Ok(())
+    }
+
+    /// Finds the request a reply of `kind` belongs to, without settling it.
+    ///
+    /// Service calls match by id. Action goals match by id, else by session,
+    /// else the only pending goal. Cancels carry no id, so they match the
+    /// oldest cancel of the same action and session. CLI replies match by id,
+    /// else the only pending CLI run.
+    pub fn find(
+        &self,
+        kind: RequestKind,
+        id: Option<&str>,
+        session_id: Option<&str>,
+        target: Option<&str>,
+    ) -> Option<String> {
+        if let Some(id) = id {
+            if self.pending.get(id).is_some_and(|p| p.kind == kind) {
+                return Some(id.to_owned());
+            }
+        }
+        let candidates = self.pending.iter().filter(|(_, p)| p.kind == kind);
+        match kind {
+            RequestKind::CallService => None,
+            RequestKind::SendActionGoal => session_id
+                .and_then(|session_id| {
+                    candidates
+                        .clone()
+                        .filter(|(_, p)| p.session_id.as_deref() == Some(session_id))
+                        .min_by_key(|(_, p)| p.seq)
+                })
+                .map(|(id, _)| id.clone())
+                .or_else(|| only(candidates)),
+            RequestKind::CancelActionGoal => candidates
+                .filter(|(_, p)| {
+                    p.session_id.as_deref() == session_id
+                        && target.is_none_or(|target| p.target == target)
+                })
+                .min_by_key(|(_, p)| p.seq)
+                .map(|(id, _)| id.clone()),
+            RequestKind::ExecuteCli => only(candidates),
+        }
+    }
+
+    /// Finds the request an `error` or `status` frame is about, by id. `op`
+    /// is the op of the echoed request, if the frame carried one. A refused
+    /// `query_action_goal` is about the goal it tried to re-attach.
+    pub fn correlate(&self, op: Option<&str>, id: Option<&str>) -> Option<String> {
+        let kind = match op {
+            Some("query_action_goal") => Some(RequestKind::SendActionGoal),
+            Some(op) => Some(RequestKind::from_op(op)?),
+            None => None,
+        };
+        if kind == Some(RequestKind::ExecuteCli) {
+            // `executeCli` only sends an id when the caller picked one.
+            return self.find(RequestKind::ExecuteCli, id, None, None);
+        }
+        let id = id?;
+        let pending = self.pending.get(id)?;
+        kind.is_none_or(|kind| kind == pending.kind)
+            .then(|| id.to_owned())
+    }
+
+    pub fn get(&self, id: &str) -> Option<Settled> {
+        self.pending.get(id).map(|p| settled(id, p))
+    }
+
+    /// Stops tracking `id`; `None` if it was not pending.
+    pub fn settle(&mut self, id: &str) -> Option<Settled> {
+        let pending = self.pending.remove(id)?;
+        Some(settled(id, &pending))
+    }
+
+    /// [`Self::find`] and [`Self::settle`] in one step.
+    pub fn resolve(
+        &mut self,
+        kind: RequestKind,
+        id: Option<&str>,
+        session_id: Option<&str>,
+        target: Option<&str>,
+    ) -> Option<Settled> {
+        let id = self.find(kind, id, session_id, target)?;
+        self.settle(&id)
+    }
+
+    /// Removes and returns every request whose deadline has passed.
+    pub fn expire(&mut self, now_ms: f64) -> Vec<Settled> {
+        self.drain(|p| p.deadline_ms.is_some_and(|deadline| deadline <= now_ms))
+    }
+
+    pub fn next_deadline(&self) -> Option<f64> {
+        self.pending
+            .values()
+            .filter_map(|p| p.deadline_ms)
+            .min_by(f64::total_cmp)
+    }
+
+    /// Removes and returns the requests a lost connection interrupts, and
+    /// holds those a reconnect can pick up. With `keep_goals`, action goals
+    /// without a retry policy are held so they can be re-attached.
+    pub fn disconnect(&mut self, keep_goals: bool) -> Vec<Settled> {
+        let interrupted = self.drain(|p| p.on_disconnect(keep_goals) == OnDisconnect::Interrupt);
+        for pending in self.pending.values_mut() {
+            if pending.on_disconnect(keep_goals) == OnDisconnect::Hold {
+                pending.held = true;
+            }
+        }
+        interrupted
+    }
+
+    /// Releases the held requests once the connection is back, oldest first.
+    /// Resends only count once reported through [`Self::resent`].
+    pub fn reconnected(&mut self) -> Vec<Resumed> {
+        let mut held: Vec<(&String, &mut Pending)> =
+            self.pending.iter_mut().filter(|(_, p)| p.held).collect();
+        held.sort_by_key(|(_, p)| p.seq);
+        held.into_iter()
+            .map(|(id, p)| {
+                p.held = false;
+                let resend_after_ms = match p.retry {
+                    Some(Retry::Idempotent) => Some(0.0),
+                    Some(Retry::Times { backoff_ms, .. }) => {
+                        Some(backoff_ms * 2f64.powi(p.resends as i32))
+                    }
+                    Some(Retry::Never) | None => None,
+                };
+                Resumed {
+                    request: settled(id, p),
+                    resend_after_ms,
+                }
+            })
+            .collect()
+    }
+
+    /// Counts a resend of `id` once its envelope has been transmitted, so a
+    /// connection that drops again before the backoff ends costs no attempt.
+    /// `false` if `id` is not pending.
+    pub fn resent(&mut self, id: &str) -> bool {
+        let Some(pending) = self.pending.get_mut(id) else {
+            return false;
+        };
+        pending.resends += 1;
+        true
+    }
+
+    /// Removes and returns the held requests when no reconnect will follow.
+    pub fn abandon(&mut self) -> Vec<Settled> {
+        self.drain(|p| p.held)
+    }
+
+    /// Pending requests of `kind`, oldest first.
+    pub fn pending(&self, kind: RequestKind) -> Vec<Settled> {
+        let mut pending: Vec<(&String, &Pending)> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| p.kind == kind)
+            .collect();
+        pending.sort_by_key(|(_, p)| p.seq);
+        pending.into_iter().map(|(id, p)| settled(id, p)).collect()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    fn drain(&mut self, mut pred: impl FnMut(&Pending) -> bool) -> Vec<Settled> {
+        let mut ids: Vec<(u64, String)> = self
+            .pending
+            .iter()
+            .filter(|(_, p)| pred(p))
+            .map(|(id, p)| (p.seq, id.clone()))
+            .collect();
+        ids.sort();
+        ids.into_iter()
+            .filter_map(|(_, id)| self.settle(&id))
+            .collect()
+    }
+}
+
+fn only<'a>(mut candidates: impl Iterator<Item = (&'a String, &'a Pending)>) -> Option<String> {
+    match (candidates.next(), candidates.next()) {
+        (Some((id, _)), None) => Some(id.clone()),
+        _ => None,
+    }
+}
+
+fn settled(id: &str, pending: &Pending) -> Settled {
+    Settled {
+        id: id.to_owned(),
+        kind: pending.kind,
+        target: pending.target.clone(),
+        session_id: pending.session_id.clone(),
+    }
+}
+
+fn parse_kind(kind: &str) -> Result<RequestKind, JsValue> {
+    RequestKind::from_op(kind).ok_or_else(|| {
+        BridgeError::invalid_input(format!("unknown request kind `{kind}`"))
+            .with_path("kind")
+            .into()
+    })
+}
+
+fn settled_to_js(settled: Option<Settled>) -> Result<JsValue, JsValue> {
+    match settled {
+        Some(settled) => crate::to_js_object(&settled),
+        None => Ok(JsValue::UNDEFINED),
+    }
+}
+
+/// Pending requests of one client; see [`Tracker`]. Kinds are the rosbridge
+/// ops that start them: `call_service`, `send_action_goal`,
+/// `cancel_action_goal` and `execute_cli`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct RequestTracker {
+    inner: Tracker,
+}
+
+#[wasm_bindgen]
+impl RequestTracker {
+    /// `namespace` goes into allocated ids, e.g. `svc-<namespace>-1`.
+    #[wasm_bindgen(constructor)]
+    pub fn new(namespace: Option<String>) -> RequestTracker {
+        RequestTracker {
+            inner: namespace.map(Tracker::with_namespace).unwrap_or_default(),
+        }
+    }
+
+    /// Sets the retry policy of pending request `id`: `"never"`,
+    /// `"idempotent"` or `{ attempts, backoffMs? }`.
+    pub fn set_retry(&mut self, id: String, retry: JsValue) -> Result<(), JsValue> {
+        let option: RetryOption = serde_wasm_bindgen::from_value(retry).map_err(|_| {
+            BridgeError::invalid_input(
+                "retry must be `never`, `idempotent` or { attempts, backoffMs }",
+            )
+            .with_path("retry")
+        })?;
+        let retry = Retry::from_option(option).map_err(|e| e.with_request_id(id.as_str()))?;
+        Ok(self.inner.set_retry(&id, retry)?)
+    }
+
+    /// Starts tracking a request; returns its id.
+    pub fn track(
+        &mut self,
+        kind: String,
+        id: Option<String>,
+        target: String,
+        session_id: Option<String>,
+        timeout_ms: Option<f64>,
+        now_ms: f64,
+    ) -> Result<String, JsValue> {
+        let kind = parse_kind(&kind)?;
+        self.inner
+            .track(kind, id, &target, session_id.as_deref(), timeout_ms, now_ms)
+            .map_err(|e| e.with_op(kind.as_op()).into())
+    }
+
+    pub fn find(
+        &self,
+        kind: String,
+        id: Option<String>,
+        session_id: Option<String>,
+        target: Option<String>,
+    ) -> Result<Option<String>, JsValue> {
+        Ok(self.inner.find(
+            parse_kind(&kind)?,
+            id.as_deref(),
+            session_id.as_deref(),
+            target.as_deref(),
+        ))
+    }
+
+    pub fn correlate(&self, op: Option<String>, id: Option<String>) -> Option<String> {
+        self.inner.correlate(op.as_deref(), id.as_deref())
+    }
+
+    /// Returns `{ id, kind, target, session_id? }` or `undefined`.
+    pub fn get(&self, id: String) -> Result<JsValue, JsValue> {
+        settled_to_js(self.inner.get(&id))
+    }
+
+    /// Returns `{ id, kind, target, session_id? }` or `undefined`.
+    pub fn settle(&mut self, id: String) -> Result<JsValue, JsValue> {
+        settled_to_js(self.inner.settle(&id))
+    }
+
+    /// Settles the request a reply matches; returns it or `undefined`.
+    pub fn resolve(
+        &mut self,
+        kind: String,
+        id: Option<String>,
+        session_id: Option<String>,
+        target: Option<String>,
+    ) -> Result<JsValue, JsValue> {
+        let kind = parse_kind(&kind)?;
+        settled_to_js(self.inner.resolve(
+            kind,
+            id.as_deref(),
+            session_id.as_deref(),
+            target.as_deref(),
+        ))
+    }
+
+    pub fn expire(&mut self, now_ms: f64) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.expire(now_ms))
+    }
+
+    pub fn next_deadline(&self) -> Option<f64> {
+        self.inner.next_deadline()
+    }
+
+    pub fn disconnect(&mut self, keep_goals: Option<bool>) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.disconnect(keep_goals.unwrap_or(false)))
+    }
+
+    /// Pending requests of `kind`, oldest first, without settling them.
+    pub fn pending(&self, kind: String) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.pending(parse_kind(&kind)?))
+    }
+
+    /// `[{ request, resend_after_ms? }]`; see [`Tracker::reconnected`].
+    pub fn reconnected(&mut self) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.reconnected())
+    }
+
+    /// Counts a transmitted resend; see [`Tracker::resent`].
+    pub fn resent(&mut self, id: String) -> bool {
+        self.inner.resent(&id)
+    }
+
+    pub fn abandon(&mut self) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.abandon())
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.inner.pending_count()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn track(tracker: &mut Tracker, kind: RequestKind, id: Option<&str>, target: &str) -> String {
+        tracker
+            .track(kind, id.map(str::to_owned), target, None, None, 0.0)
+            .unwrap()
+    }
+
+    #[test]
+    fn allocates_ids_and_rejects_duplicates() {
+        let mut tracker = Tracker::default();
+        assert_eq!(
+            track(&mut tracker, RequestKind::CallService, None, "/a"),
+            "svc-1"
+        );
+        assert_eq!(
+            track(&mut tracker, RequestKind::ExecuteCli, Some("run"), "ls"),
+            "run"
+        );
+        let err = tracker
+            .track(
+                RequestKind::CallService,
+                Some("run".into()),
+                "/b",
+                None,
+                None,
+                0.0,
+            )
+            .unwrap_err();
+        assert_eq!(err.request_id.as_deref(), Some("run"));
+        assert_eq!(tracker.pending_count(), 2);
+
+        let mut tracker = Tracker::with_namespace("tab");
+        assert_eq!(
+            track(&mut tracker, RequestKind::SendActionGoal, None, "/fly"),
+            "action-tab-1"
+        );
+    }
+
+    #[test]
+    fn matches_goals_by_id_or_session() {
+        let mut tracker = Tracker::default();
+        let goal = tracker
+            .track(
+                RequestKind::SendActionGoal,
+                Some("g1".into()),
+                "/fly",
+                Some("s1"),
+                None,
+                0.0,
+            )
+            .unwrap();
+        // A service call with the same id is a different request.
+        assert_eq!(
+            tracker.find(RequestKind::CallService, Some(&goal), None, None),
+            None
+        );
+        assert_eq!(
+            tracker.find(RequestKind::SendActionGoal, None, Some("s1"), None),
+            Some(goal.clone())
+        );
+        let settled = tracker
+            .resolve(RequestKind::SendActionGoal, Some("other"), Some("s1"), None)
+            .unwrap();
+        assert_eq!(settled.target, "/fly");
+        assert_eq!(settled.session_id.as_deref(), Some("s1"));
+        assert_eq!(tracker.settle(&goal), None);
+    }
+
+    #[test]
+    fn goal_replies_without_match_need_a_single_pending_goal() {
+        let mut tracker = Tracker::default();
+        let first = track(&mut tracker, RequestKind::SendActionGoal, None, "/fly");
+        assert_eq!(
+            tracker.find(RequestKind::SendActionGoal, Some("?"), Some("?"), None),
+            Some(first)
+        );
+        track(&mut tracker, RequestKind::SendActionGoal, None, "/fly");
+        assert_eq!(
+            tracker.find(RequestKind::SendActionGoal, Some("?"), Some("?"), None),
+            None
+        );
+    }
+
+    #[test]
+    fn cancels_match_oldest_of_action_and_session() {
+        let mut tracker = Tracker::default();
+        let first = track(&mut tracker, RequestKind::CancelActionGoal, None, "/fly");
+        let second = track(&mut tracker, RequestKind::CancelActionGoal, None, "/fly");
+        track(&mut tracker, RequestKind::CancelActionGoal, None, "/swim");
+        let resolve = |tracker: &mut Tracker| {
+            tracker
+                .resolve(RequestKind::CancelActionGoal, None, None, Some("/fly"))
+                .map(|settled| settled.id)
+        };
+        assert_eq!(resolve(&mut tracker), Some(first));
+        assert_eq!(resolve(&mut tracker), Some(second));
+        assert_eq!(resolve(&mut tracker), None);
+    }
+
+    #[test]
+    fn cli_replies_without_id_need_a_single_pending_run() {
+        let mut tracker = Tracker::default();
+        let first = track(&mut tracker, RequestKind::ExecuteCli, None, "ls");
+        assert_eq!(
+            tracker.find(RequestKind::ExecuteCli, None, None, None),
+            Some(first.clone())
+        );
+        let second = track(&mut tracker, RequestKind::ExecuteCli, None, "pwd");
+        assert_eq!(
+            tracker.find(RequestKind::ExecuteCli, None, None, None),
+            None
+        );
+        assert_eq!(
+            tracker.find(RequestKind::ExecuteCli, Some(&second), None, None),
+            Some(second)
+        );
+        assert_eq!(
+            tracker.correlate(Some("execute_cli"), None),
+            None,
+            "ambiguous without an id"
+        );
+        tracker.settle(&first);
+        assert!(tracker.correlate(Some("execute_cli"), None).is_some());
+    }
+
+    #[test]
+    fn correlates_error_frames_by_op_and_id() {
+        let mut tracker = Tracker::default();
+        let call = track(&mut tracker, RequestKind::CallService, Some("x"), "/a");
+        assert_eq!(tracker.correlate(None, Some("x")), Some(call.clone()));
+        assert_eq!(
+            tracker.correlate(Some("call_service"), Some("x")),
+            Some(call)
+        );
+        assert_eq!(tracker.correlate(Some("send_action_goal"), Some("x")), None);
+        assert_eq!(tracker.correlate(None, Some("y")), None);
+        assert_eq!(tracker.correlate(Some("subscribe"), Some("x")), None);
+    }
+
+    #[test]
+    fn expires_by_deadline_in_tracking_order() {
+        let mut tracker = Tracker::default();
+        let slow = tracker
+            .track(
+                RequestKind::CallService,
+                None,
+                "/slow",
+                None,
+                Some(500.0),
+                1000.0,
+            )
+            .unwrap();
+        let fast = tracker
+            .track(
+                RequestKind::ExecuteCli,
+                None,
+                "ls",
+                None,
+                Some(100.0),
+                1000.0,
+            )
+            .unwrap();
+        tracker
+            .track(
+                RequestKind::CallService,
+                None,
+                "/forever",
+                None,
+                Some(0.0),
+                1000.0,
+            )
+            .unwrap();
+        assert_eq!(tracker.next_deadline(), Some(1100.0));
+        assert!(tracker.expire(1099.0).is_empty());
+        assert_eq!(
+            tracker
+                .expire(2000.0)
+                .into_iter()
+                .map(|s| s.id)
+                .collect::<Vec<_>>(),
+            [slow, fast]
+        );
+        assert_eq!(tracker.next_deadline(), None);
+        assert_eq!(tracker.pending_count(), 1);
+    }
+
+    #[test]
+    fn disconnect_interrupts_requests_without_retry() {
+        let mut tracker = Tracker::default();
+        track(&mut tracker, RequestKind::CallService, None, "/a");
+        track(&mut tracker, RequestKind::SendActionGoal, None, "/fly");
+        track(&mut tracker, RequestKind::CancelActionGoal, None, "/fly");
+        track(&mut tracker, RequestKind::ExecuteCli, None, "ls");
+        let kinds: Vec<RequestKind> = tracker
+            .disconnect(false)
+            .into_iter()
+            .map(|s| s.kind)
+            .collect();
+        assert_eq!(
+            kinds,
+            [
+                RequestKind::CallService,
+                RequestKind::SendActionGoal,
+                RequestKind::CancelActionGoal,
+                RequestKind::ExecuteCli
+            ]
+        );
+        assert_eq!(tracker.pending_count(), 0);
+    }
+
+    #[test]
+    fn keeps_goals_across_disconnect_for_reattaching() {
+        let mut tracker = Tracker::default();
+        let first = track(&mut tracker, RequestKind::SendActionGoal, None, "/navigate");
+        let second = track(&mut tracker, RequestKind::SendActionGoal, None, "/dock");
+        track(&mut tracker, RequestKind::ExecuteCli, None, "ls");
+        let kinds: Vec<RequestKind> = tracker
+            .disconnect(true)
+            .into_iter()
+            .map(|s| s.kind)
+            .collect();
+        assert_eq!(kinds, [RequestKind::ExecuteCli]);
+        let ids: Vec<String> = tracker
+            .pending(RequestKind::SendActionGoal)
+            .into_iter()
+            .map(|s| s.id)
+            .collect();
+        assert_eq!(ids, [first.clone(), second]);
+        assert_eq!(
+            tracker.correlate(Some("query_action_goal"), Some(&first)),
+            Some(first)
+        );
+    }
+
+    #[test]
+    fn retry_policies_decide_what_survives_a_disconnect() {
+        let mut tracker = Tracker::default();
+        let never = track(&mut tracker, RequestKind::CallService, None, "/a");
+        let always = track(&mut tracker, RequestKind::CallService, None, "/b");
+        let twice = track(&mut tracker, RequestKind::ExecuteCli, None, "ls");
+        let unset = track(&mut tracker, RequestKind::CallService, None, "/c");
+        tracker.set_retry(&never, Retry::Never).unwrap();
+        tracker.set_retry(&always, Retry::Idempotent).unwrap();
+        tracker
+            .set_retry(
+                &twice,
+                Retry::Times {
+                    attempts: 2,
+                    backoff_ms: 100.0,
+                },
+            )
+            .unwrap();
+        assert!(tracker.set_retry("missing", Retry::Never).is_err());
+
+        let resends = |tracker: &mut Tracker| -> Vec<(String, Option<f64>)> {
+            tracker
+                .reconnected()
+                .into_iter()
+                .map(|r| (r.request.id, r.resend_after_ms))
+                .collect()
+        };
+        let ids =
+            |settled: Vec<Settled>| -> Vec<String> { settled.into_iter().map(|s| s.id).collect() };
+
+        assert_eq!(ids(tracker.disconnect(false)), [never, unset]);
+        assert_eq!(
+            resends(&mut tracker),
+            [(always.clone(), Some(0.0)), (twice.clone(), Some(100.0))]
+        );
+        // Nothing is held until the next disconnect.
+        assert!(tracker.reconnected().is_empty());
+        // The connection dropped again before `twice` was resent.
+        assert!(tracker.resent(&always));
+        tracker.disconnect(false);
+        assert_eq!(
+            resends(&mut tracker),
+            [(always.clone(), Some(0.0)), (twice.clone(), Some(100.0))]
+        );
+        assert!(tracker.resent(&twice));
+        tracker.disconnect(false);
+        assert_eq!(
+            resends(&mut tracker),
+            [(always.clone(), Some(0.0)), (twice.clone(), Some(200.0))]
+        );
+        assert!(tracker.resent(&twice));
+        assert_eq!(ids(tracker.disconnect(false)), [twice]);
+        assert_eq!(ids(tracker.abandon()), [always]);
+        assert!(!tracker.resent("missing"));
+    }
+
+    #[test]
+    fn reconnect_reattaches_kept_goals() {
+        let mut tracker = Tracker::default();
+        let goal = track(&mut tracker, RequestKind::SendActionGoal, None, "/navigate");
+        let retried = track(&mut tracker, RequestKind::SendActionGoal, None, "/dock");
+        tracker.set_retry(&retried, Retry::Idempotent).unwrap();
+        assert!(tracker.disconnect(true).is_empty());
+        let resumed: Vec<(String, Option<f64>)> = tracker
+            .reconnected()
+            .into_iter()
+            .map(|r| (r.request.id, r.resend_after_ms))
+            .collect();
+        assert_eq!(resumed, [(goal.clone(), None), (retried, Some(0.0))]);
+
+        tracker.disconnect(true);
+        assert_eq!(tracker.abandon().len(), 2);
+        assert_eq!(tracker.get(&goal), None);
+    }
+
+    #[test]
+    fn parses_retry_options() {
+        let parse = |option| Retry::from_option(option);
+        assert_eq!(
+            parse(RetryOption::Named("idempotent".into())).unwrap(),
+            Retry::Idempotent
+        );
+        assert_eq!(
+            parse(RetryOption::Times {
+                attempts: 3,
+                backoff_ms: None
+            })
+            .unwrap(),
+            Retry::Times {
+                attempts: 3,
+                backoff_ms: 0.0
+            }
+        );
+        let err = parse(RetryOption::Named("always".into())).unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("retry"));
+        let err = parse(RetryOption::Times {
+            attempts: 1,
+            backoff_ms: Some(-1.0),
+        })
+        .unwrap_err();
+        assert_eq!(err.path.as_deref(), Some("retry.backoffMs"));
+    }
+}
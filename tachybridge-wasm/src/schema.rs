@@ -0,0 +1,159 @@
+//! Process-wide registry of parsed interface definitions.
+//!
+//! Definitions are registered from JS once and then looked up by type name
+//! for CDR, validation and default messages.
+
+use std::cell::RefCell;
+
+use wasm_bindgen::prelude::*;
+
+use crate::error::BridgeError;
+
+use crate::msgdef::{self, FieldType, MessageDef, MessageSet, ParseError};
+
+thread_local! {
+    static REGISTRY: RefCell<MessageSet> = RefCell::new(MessageSet::new());
+}
+
+pub(crate) fn with_registry<R>(f: impl FnOnce(&MessageSet) -> R) -> R {
+    REGISTRY.with(|registry| f(&registry.borrow()))
+}
+
+fn insert(defs: Vec<MessageDef>) -> Vec<String> {
+    REGISTRY.with(|registry| {
+        let mut registry = registry.borrow_mut();
+        defs.into_iter()
+            .map(|def| {
+                let name = def.name.clone();
+                registry.insert(name.clone(), def);
+                name
+            })
+            .collect()
+    })
+}
+
+/// Parses one interface and registers the messages it defines. Message
+/// types also accept the concatenated full-text form with `MSG:` sections.
+pub fn register_interface(type_name: &str, text: &str) -> Result<Vec<String>, ParseError> {
+    let defs = match type_name.split('/').collect::<Vec<_>>().as_slice() {
+        [_, "srv" | "action", _] => msgdef::parse_interface(type_name, text)?,
+        _ => msgdef::parse_full_text(type_name, text)?
+            .into_values()
+            .collect(),
+    };
+    Ok(insert(defs))
+}
+
+/// Registers every section of a `MSG:`/`SRV:`/`ACTION:` bundle.
+pub fn register_bundle(text: &str) -> Result<Vec<String>, ParseError> {
+    Ok(insert(msgdef::parse_bundle(text)?))
+}
+
+/// Message types referenced by `defs` that are not defined in it.
+pub fn unresolved(defs: &MessageSet) -> Vec<String> {
+    let mut missing: Vec<String> = defs
+        .values()
+        .flat_map(|def| &def.fields)
+        .filter_map(|field| match &field.ty {
+            FieldType::Message(name) if !defs.contains_key(name) => Some(name.clone()),
+            _ => None,
+        })
+        .collect();
+    missing.sort();
+    missing.dedup();
+    missing
+}
+
+fn parse_err(error: ParseError) -> JsValue {
+    BridgeError::invalid_input(format!("invalid message definition: {error}")).into()
+}
+
+/// Registers a bundle of definitions and returns the registered type names.
+#[wasm_bindgen]
+pub fn register_message_definitions(text: String) -> Result<Vec<String>, JsValue> {
+    register_bundle(&text).map_err(parse_err)
+}
+
+/// Registers the `.msg`, `.srv` or `.action` text of `msg_type`.
+#[wasm_bindgen]
+pub fn register_message_definition(msg_type: String, text: String) -> Result<Vec<String>, JsValue> {
+    register_interface(&msg_type, &text).map_err(parse_err)
+}
+
+#[wasm_bindgen]
+pub fn registered_message_types() -> Vec<String> {
+    let mut names: Vec<String> = with_registry(|defs| defs.keys().cloned().collect());
+    names.sort();
+    names
+}
+
+/// Nested types that are referenced but not registered yet.
+#[wasm_bindgen]
+pub fn unresolved_message_types() -> Vec<String> {
+    with_registry(unresolved)
+}
+
+/// Schema of a registered type, or `undefined` if it is unknown.
+#[wasm_bindgen]
+pub fn get_message_schema(msg_type: String) -> Result<JsValue, JsValue> {
+    let Some(name) = msgdef::canonical_name(&msg_type) else {
+        return Ok(JsValue::UNDEFINED);
+    };
+    match with_registry(|defs| defs.get(&name).map(MessageDef::to_json)) {
+        Some(schema) => crate::to_js_object(&schema),
+        None => Ok(JsValue::UNDEFINED),
+    }
+}
+
+#[wasm_bindgen]
+pub fn clear_message_definitions() {
+    REGISTRY.with(|registry| registry.borrow_mut().clear());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn registers_interfaces_and_reports_unresolved_types() {
+        clear_message_definitions();
+        let names = register_interface("demo/srv/Locate", "string name\n---\nPose pose\n").unwrap();
+        assert_eq!(
+            names,
+            ["demo/srv/Locate_Request", "demo/srv/Locate_Response"]
+        );
+        assert_eq!(with_registry(unresolved), ["demo/msg/Pose"]);
+
+        register_bundle("MSG: demo/Pose\nPoint position\n===\nMSG: demo/msg/Point\nfloat64 x\n")
+            .unwrap();
+        assert!(with_registry(unresolved).is_empty());
+        assert_eq!(
+            registered_message_types(),
+            [
+                "demo/msg/Point",
+                "demo/msg/Pose",
+                "demo/srv/Locate_Request",
+                "demo/srv/Locate_Response"
+            ]
+        );
+
+        let schema = with_registry(|defs| defs["demo/msg/Pose"].to_json());
+        assert_eq!(
+            schema,
+            serde_json::json!({
+                "name": "demo/msg/Pose",
+                "fields": [{ "name": "position", "type": "demo/msg/Point", "is_message": true }],
+                "constants": []
+            })
+        );
+    }
+
+    #[test]
+    fn keeps_registry_on_parse_errors() {
+        clear_message_definitions();
+        register_interface("demo/Ok", "int32 a\n").unwrap();
+        let err = register_interface("demo/Broken", "int32\n").unwrap_err();
+        assert_eq!(err.line, 1);
+        assert_eq!(registered_message_types(), ["demo/msg/Ok"]);
+    }
+}
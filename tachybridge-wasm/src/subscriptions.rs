@@ -0,0 +1,244 @@
+//! Reference-counted subscription bookkeeping.
+//!
+//! Every `subscribe` call gets a handle with its own id, while the bridge sees
+//! one subscription per topic. The topic's subscription id (its "wire id") is
+//! the id of the handle that created it unless the caller picks one, and the
+//! `unsubscribe` op is only due once the last handle of a topic is released.
+//! Changing a topic's options moves it to a fresh wire id, so the client can
+//! subscribe anew and then drop the old subscription.
+
+use std::collections::{BTreeMap, HashMap};
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Added {
+    /// Id of the new handle.
+    pub id: String,
+    /// Subscription id to send with `subscribe` for the topic.
+    pub wire_id: String,
+    /// The topic had no handles, so a `subscribe` op is due.
+    pub first: bool,
+    /// Wire id replaced by a requested or renewed one, to unsubscribe.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub replaced: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Removed {
+    pub topic: String,
+    pub wire_id: String,
+    /// No handles are left, so an `unsubscribe` op is due.
+    pub last: bool,
+}
+
+struct Topic {
+    wire_id: String,
+    handles: Vec<String>,
+}
+
+#[derive(Default)]
+pub struct Registry {
+    counter: u64,
+    topics: BTreeMap<String, Topic>,
+    handle_topics: HashMap<String, String>,
+}
+
+impl Registry {
+    /// Registers a handle on `topic`, optionally requesting the wire id. With
+    /// `renew`, a topic that is already subscribed moves to a fresh wire id
+    /// (the new handle's) unless one is requested.
+    pub fn add(&mut self, topic: &str, wire_id: Option<String>, renew: bool) -> Added {
+        self.counter += 1;
+        let id = format!("subscribe:{topic}:{}", self.counter);
+        self.handle_topics.insert(id.clone(), topic.to_owned());
+        match self.topics.get_mut(topic) {
+            Some(entry) => {
+                entry.handles.push(id.clone());
+                let replaced = match wire_id.or_else(|| renew.then(|| id.clone())) {
+                    Some(requested) if requested != entry.wire_id => {
+                        Some(std::mem::replace(&mut entry.wire_id, requested))
+                    }
+                    _ => None,
+                };
+                Added {
+                    id,
+                    wire_id: entry.wire_id.clone(),
+                    first: false,
+                    replaced,
+                }
+            }
+            None => {
+                let wire_id = wire_id.unwrap_or_else(|| id.clone());
+                self.topics.insert(
+                    topic.to_owned(),
+                    Topic {
+                        wire_id: wire_id.clone(),
+                        handles: vec![id.clone()],
+                    },
+                );
+                Added {
+                    id,
+                    wire_id,
+                    first: true,
+                    replaced: None,
+                }
+            }
+        }
+    }
+
+    /// Releases one handle; `None` if it was already released.
+    pub fn remove(&mut self, id: &str) -> Option<Removed> {
+        let topic = self.handle_topics.remove(id)?;
+        let entry = self.topics.get_mut(&topic)?;
+        entry.handles.retain(|handle| handle != id);
+        let last = entry.handles.is_empty();
+        let wire_id = entry.wire_id.clone();
+        if last {
+            self.topics.remove(&topic);
+        }
+        Some(Removed {
+            topic,
+            wire_id,
+            last,
+        })
+    }
+
+    /// Releases every handle on `topic` and returns its wire id.
+    pub fn remove_topic(&mut self, topic: &str) -> Option<String> {
+        let entry = self.topics.remove(topic)?;
+        for handle in &entry.handles {
+            self.handle_topics.remove(handle);
+        }
+        Some(entry.wire_id)
+    }
+
+    pub fn wire_id(&self, topic: &str) -> Option<&str> {
+        self.topics.get(topic).map(|entry| entry.wire_id.as_str())
+    }
+
+    pub fn handle_count(&self, topic: &str) -> usize {
+        self.topics
+            .get(topic)
+            .map_or(0, |entry| entry.handles.len())
+    }
+
+    pub fn topics(&self) -> Vec<String> {
+        self.topics.keys().cloned().collect()
+    }
+}
+
+/// Handle and subscription-id bookkeeping for `BridgeClientCore.subscribe`.
+#[wasm_bindgen]
+#[derive(Default)]
+pub struct SubscriptionRegistry {
+    inner: Registry,
+}
+
+#[wasm_bindgen]
+impl SubscriptionRegistry {
+    #[wasm_bindgen(constructor)]
+    pub fn new() -> SubscriptionRegistry {
+        SubscriptionRegistry::default()
+    }
+
+    /// Registers a handle; returns `{ id, wire_id, first, replaced? }`.
+    pub fn add(
+        &mut self,
+        topic: String,
+        wire_id: Option<String>,
+        renew: bool,
+    ) -> Result<JsValue, JsValue> {
+        crate::to_js_object(&self.inner.add(&topic, wire_id, renew))
+    }
+
+    /// Releases a handle; returns `{ topic, wire_id, last }` or `undefined`.
+    pub fn remove(&mut self, id: String) -> Result<JsValue, JsValue> {
+        match self.inner.remove(&id) {
+            Some(removed) => crate::to_js_object(&removed),
+            None => Ok(JsValue::UNDEFINED),
+        }
+    }
+
+    pub fn remove_topic(&mut self, topic: String) -> Option<String> {
+        self.inner.remove_topic(&topic)
+    }
+
+    pub fn wire_id(&self, topic: String) -> Option<String> {
+        self.inner.wire_id(&topic).map(str::to_owned)
+    }
+
+    pub fn handle_count(&self, topic: String) -> usize {
+        self.inner.handle_count(&topic)
+    }
+
+    pub fn topics(&self) -> Vec<String> {
+        self.inner.topics()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reference_counts_handles_per_topic() {
+        let mut registry = Registry::default();
+        let a = registry.add("/odom", None, false);
+        let b = registry.add("/odom", None, false);
+        assert_eq!(a.id, "subscribe:/odom:1");
+        assert!(a.first && !b.first);
+        assert_eq!(b.wire_id, a.id);
+        assert_eq!(registry.handle_count("/odom"), 2);
+
+        // The wire id outlives the handle that created it.
+        let removed = registry.remove(&a.id).unwrap();
+        assert!(!removed.last);
+        assert_eq!(removed.wire_id, "subscribe:/odom:1");
+        assert_eq!(registry.remove(&a.id), None);
+
+        let removed = registry.remove(&b.id).unwrap();
+        assert!(removed.last);
+        assert_eq!(registry.handle_count("/odom"), 0);
+        assert!(registry.add("/odom", None, false).first);
+    }
+
+    #[test]
+    fn honours_requested_wire_ids() {
+        let mut registry = Registry::default();
+        let a = registry.add("/scan", Some("scan-sub".into()), false);
+        assert_eq!(a.wire_id, "scan-sub");
+        let b = registry.add("/scan", Some("scan-sub".into()), false);
+        assert_eq!(b.replaced, None);
+        let c = registry.add("/scan", Some("scan-sub-2".into()), false);
+        assert_eq!(c.replaced.as_deref(), Some("scan-sub"));
+        assert_eq!(registry.wire_id("/scan"), Some("scan-sub-2"));
+    }
+
+    #[test]
+    fn renews_wire_ids_on_request() {
+        let mut registry = Registry::default();
+        let a = registry.add("/scan", None, true);
+        assert_eq!(a.replaced, None);
+        let b = registry.add("/scan", None, true);
+        assert_eq!(b.wire_id, b.id);
+        assert_eq!(b.replaced, Some(a.id));
+        // A requested id wins over a renewed one.
+        let c = registry.add("/scan", Some("scan-sub".into()), true);
+        assert_eq!(c.wire_id, "scan-sub");
+        assert_eq!(c.replaced, Some(b.id));
+    }
+
+    #[test]
+    fn removing_a_topic_releases_all_handles() {
+        let mut registry = Registry::default();
+        let a = registry.add("/a", None, false);
+        registry.add("/a", None, false);
+        registry.add("/b", None, false);
+        assert_eq!(registry.remove_topic("/a").as_deref(), Some(a.id.as_str()));
+        assert_eq!(registry.remove(&a.id), None);
+        assert_eq!(registry.topics(), ["/b"]);
+        assert_eq!(registry.remove_topic("/a"), None);
+    }
+}
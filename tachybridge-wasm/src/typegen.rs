@@ -0,0 +1,295 @@
+//! TypeScript declarations for ROS interfaces, emitted by `bridge-typegen`.
+//!
+//! Each interface becomes an interface inside `pkg.msg` / `pkg.srv` /
+//! `pkg.action` namespaces, and every type name is added to the global
+//! `Tachybridge*TypeMap` interfaces the client uses to type callbacks.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Write;
+
+use crate::msgdef::{self, ArrayKind, Field, FieldType, MessageDef, ParseError, Primitive};
+
+const INDENT: &str = "  ";
+
+fn scalar_type(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Bool => "boolean",
+        Primitive::Int64 | Primitive::Uint64 => "number | bigint",
+        Primitive::String | Primitive::WString => "string",
+        _ => "number",
+    }
+}
+
+/// Array fields arrive as plain arrays over JSON and as typed arrays over
+/// CBOR; rosbridge sends byte arrays as base64 text over JSON.
+fn array_type(primitive: Primitive) -> &'static str {
+    match primitive {
+        Primitive::Bool => "boolean[]",
+        Primitive::String | Primitive::WString => "string[]",
+        Primitive::Byte | Primitive::Char | Primitive::Uint8 => "number[] | Uint8Array | string",
+        Primitive::Int8 => "number[] | Int8Array",
+        Primitive::Int16 => "number[] | Int16Array",
+        Primitive::Uint16 => "number[] | Uint16Array",
+        Primitive::Int32 => "number[] | Int32Array",
+        Primitive::Uint32 => "number[] | Uint32Array",
+        Primitive::Int64 => "Array<number | bigint> | BigInt64Array",
+        Primitive::Uint64 => "Array<number | bigint> | BigUint64Array",
+        Primitive::Float32 => "number[] | Float32Array",
+        Primitive::Float64 => "number[] | Float64Array",
+    }
+}
+
+fn reference(name: &str) -> String {
+    name.replace('/', ".")
+}
+
+fn field_type(field: &Field, known: &BTreeSet<String>) -> String {
+    let message = |name: &String| {
+        if known.contains(name) {
+            reference(name)
+        } else {
+            // Dependency not among the input files.
+            "Record<string, unknown>".to_owned()
+        }
+    };
+    match (&field.ty, field.array) {
+        (FieldType::Primitive(primitive), ArrayKind::Single) => scalar_type(*primitive).to_owned(),
+        (FieldType::Primitive(primitive), _) => array_type(*primitive).to_owned(),
+        (FieldType::Message(name), ArrayKind::Single) => message(name),
+        (FieldType::Message(name), _) => format!("Array<{}>", message(name)),
+    }
+}
+
+fn write_interface(out: &mut String, def: &MessageDef, known: &BTreeSet<String>, depth: usize) {
+    let pad = INDENT.repeat(depth);
+    let short = def.name.rsplit('/').next().unwrap_or_default();
+    if !def.constants.is_empty() {
+        let _ = writeln!(out, "{pad}/**");
+        let _ = writeln!(out, "{pad} * Constants:");
+        for constant in &def.constants {
+            let _ = writeln!(
+                out,
+                "{pad} * - `{} = {}`",
+                constant.name,
+                constant.value.to_json()
+            );
+        }
+        let _ = writeln!(out, "{pad} */");
+    }
+    let _ = writeln!(out, "{pad}export interface {short} {{");
+    for field in &def.fields {
+        if let Some(default) = &field.default {
+            let _ = writeln!(out, "{pad}{INDENT}/** @default {} */", default.to_json());
+        }
+        let _ = writeln!(
+            out,
+            "{pad}{INDENT}{}: {};",
+            field.name,
+            field_type(field, known)
+        );
+    }
+    let _ = writeln!(out, "{pad}}}");
+}
+
+fn interface_kind(type_name: &str) -> Option<(&str, &str, &str)> {
+    match type_name.split('/').collect::<Vec<_>>().as_slice() {
+        [pkg, kind @ ("msg" | "srv" | "action"), name] => Some((pkg, kind, name)),
+        _ => None,
+    }
+}
+
+/// Renders declarations for `files`, given as `(pkg/kind/Name, text)` pairs.
+pub fn generate(files: &[(String, String)]) -> Result<String, ParseError> {
+    let mut defs = BTreeMap::new();
+    let mut interfaces = BTreeSet::new();
+    for (type_name, text) in files {
+        let Some((pkg, kind, name)) = interface_kind(type_name) else {
+            return Err(ParseError {
+                type_name: type_name.clone(),
+                line: 0,
+                message:
+                    "type name must look like `pkg/msg/Name`, `pkg/srv/Name` or `pkg/action/Name`"
+                        .to_owned(),
+            });
+        };
+        for def in msgdef::parse_interface(type_name, text)? {
+            defs.insert(def.name.clone(), def);
+        }
+        interfaces.insert((kind.to_owned(), pkg.to_owned(), name.to_owned()));
+    }
+    let known: BTreeSet<String> = defs.keys().cloned().collect();
+
+    let mut packages: BTreeMap<&str, BTreeMap<&str, Vec<&MessageDef>>> = BTreeMap::new();
+    for (name, def) in &defs {
+        let mut parts = name.splitn(3, '/');
+        let (Some(pkg), Some(kind)) = (parts.next(), parts.next()) else {
+            continue;
+        };
+        packages
+            .entry(pkg)
+            .or_default()
+            .entry(kind)
+            .or_default()
+            .push(def);
+    }
+
+    let mut out = String::from(
+        "// Generated by bridge-typegen from ROS interface definitions. Do not edit.\n",
+    );
+    for (pkg, kinds) in &packages {
+        let _ = writeln!(out, "\nexport declare namespace {pkg} {{");
+        for (index, (kind, defs)) in kinds.iter().enumerate() {
+            if index > 0 {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "{INDENT}export namespace {kind} {{");
+            for def in defs {
+                write_interface(&mut out, def, &known, 2);
+            }
+            let _ = writeln!(out, "{INDENT}}}");
+        }
+        out.push_str("}\n");
+    }
+
+    let pad = INDENT.repeat(2);
+    let mut maps: BTreeMap<&str, Vec<String>> = BTreeMap::new();
+    for (kind, pkg, name) in &interfaces {
+        let full = format!("{pkg}/{kind}/{name}");
+        let target = reference(&full);
+        let (map, value) = match kind.as_str() {
+            "srv" => (
+                "TachybridgeServiceTypeMap",
+                format!("{{ request: {target}_Request; response: {target}_Response }}"),
+            ),
+            "action" => (
+                "TachybridgeActionTypeMap",
+                format!(
+                    "{{ goal: {target}_Goal; result: {target}_Result; feedback: {target}_Feedback }}"
+                ),
+            ),
+            _ => ("TachybridgeMessageTypeMap", target),
+        };
+        let entries = maps.entry(map).or_default();
+        entries.push(format!("{pad}\"{full}\": {value};"));
+        entries.push(format!("{pad}\"{pkg}/{name}\": {value};"));
+    }
+    out.push_str("\ndeclare global {\n");
+    for (index, (map, entries)) in maps.iter().enumerate() {
+        if index > 0 {
+            out.push('\n');
+        }
+        let _ = writeln!(out, "{INDENT}interface {map} {{");
+        for entry in entries {
+            let _ = writeln!(out, "{entry}");
+        }
+        let _ = writeln!(out, "{INDENT}}}");
+    }
+    out.push_str("}\n");
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files(entries: &[(&str, &str)]) -> Vec<(String, String)> {
+        entries
+            .iter()
+            .map(|(name, text)| (name.to_string(), text.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn emits_namespaces_and_type_maps() {
+        let out = generate(&files(&[
+            ("geometry_msgs/msg/Vector3", "float64 x\nfloat64 y\nfloat64 z\n"),
+            (
+                "geometry_msgs/msg/Twist",
+                "Vector3 linear\nVector3 angular\n",
+            ),
+            (
+                "demo/srv/SetMode",
+                "uint8 MODE_AUTO=1\nuint8 mode 1\nstring[] tags\n---\nbool ok\nstd_msgs/Header header\n",
+            ),
+            (
+                "demo/action/Go",
+                "float32[] path\nint64 id\n---\nbyte[] log\n---\ngeometry_msgs/Twist[<=2] cmds\n",
+            ),
+        ]))
+        .unwrap();
+        let expected = r#"// Generated by bridge-typegen from ROS interface definitions. Do not edit.
+
+export declare namespace demo {
+  export namespace action {
+    export interface Go_Feedback {
+      cmds: Array<geometry_msgs.msg.Twist>;
+    }
+    export interface Go_Goal {
+      path: number[] | Float32Array;
+      id: number | bigint;
+    }
+    export interface Go_Result {
+      log: number[] | Uint8Array | string;
+    }
+  }
+
+  export namespace srv {
+    /**
+     * Constants:
+     * - `MODE_AUTO = 1`
+     */
+    export interface SetMode_Request {
+      /** @default 1 */
+      mode: number;
+      tags: string[];
+    }
+    export interface SetMode_Response {
+      ok: boolean;
+      header: Record<string, unknown>;
+    }
+  }
+}
+
+export declare namespace geometry_msgs {
+  export namespace msg {
+    export interface Twist {
+      linear: geometry_msgs.msg.Vector3;
+      angular: geometry_msgs.msg.Vector3;
+    }
+    export interface Vector3 {
+      x: number;
+      y: number;
+      z: number;
+    }
+  }
+}
+
+declare global {
+  interface TachybridgeActionTypeMap {
+    "demo/action/Go": { goal: demo.action.Go_Goal; result: demo.action.Go_Result; feedback: demo.action.Go_Feedback };
+    "demo/Go": { goal: demo.action.Go_Goal; result: demo.action.Go_Result; feedback: demo.action.Go_Feedback };
+  }
+
+  interface TachybridgeMessageTypeMap {
+    "geometry_msgs/msg/Twist": geometry_msgs.msg.Twist;
+    "geometry_msgs/Twist": geometry_msgs.msg.Twist;
+    "geometry_msgs/msg/Vector3": geometry_msgs.msg.Vector3;
+    "geometry_msgs/Vector3": geometry_msgs.msg.Vector3;
+  }
+
+  interface TachybridgeServiceTypeMap {
+    "demo/srv/SetMode": { request: demo.srv.SetMode_Request; response: demo.srv.SetMode_Response };
+    "demo/SetMode": { request: demo.srv.SetMode_Request; response: demo.srv.SetMode_Response };
+  }
+}
+"#;
+        assert_eq!(out, expected);
+    }
+
+    #[test]
+    fn rejects_unqualified_names_and_bad_files() {
+        assert!(generate(&files(&[("demo/Point", "float64 x\n")])).is_err());
+        let err = generate(&files(&[("demo/msg/Bad", "float64\n")])).unwrap_err();
+        assert_eq!(err.type_name, "demo/msg/Bad");
+    }
+}
@@ -1,5 +1,51 @@
+import type { BridgeError } from "./errors.js";
+
 export type JsonObject = Record<string, unknown>;
 
+declare global {
+  /** Message type name to payload interface, extended by `bridge-typegen` output. */
+  interface TachybridgeMessageTypeMap {}
+  /** Service type name to `{ request, response }`, extended by `bridge-typegen` output. */
+  interface TachybridgeServiceTypeMap {}
+  /** Action type name to `{ goal, result, feedback }`, extended by `bridge-typegen` output. */
+  interface TachybridgeActionTypeMap {}
+}
+
+/** Payload of message type `T`, or `JsonObject` when no declaration covers it. */
+export type MessageOf<T extends string> = T extends keyof TachybridgeMessageTypeMap
+  ? TachybridgeMessageTypeMap[T]
+  : JsonObject;
+
+export type ServiceRequestOf<T extends string> = T extends keyof TachybridgeServiceTypeMap
+  ? TachybridgeServiceTypeMap[T] extends { request: infer Request }
+    ? Request
+    : JsonObject
+  : JsonObject;
+
+export type ServiceResponseOf<T extends string> = T extends keyof TachybridgeServiceTypeMap
+  ? TachybridgeServiceTypeMap[T] extends { response: infer Response }
+    ? Response
+    : JsonObject
+  : JsonObject;
+
+export type ActionGoalOf<T extends string> = T extends keyof TachybridgeActionTypeMap
+  ? TachybridgeActionTypeMap[T] extends { goal: infer Goal }
+    ? Goal
+    : JsonObject
+  : JsonObject;
+
+export type ActionResultOf<T extends string> = T extends keyof TachybridgeActionTypeMap
+  ? TachybridgeActionTypeMap[T] extends { result: infer Result }
+    ? Result
+    : JsonObject
+  : JsonObject;
+
+export type ActionFeedbackOf<T extends string> = T extends keyof TachybridgeActionTypeMap
+  ? TachybridgeActionTypeMap[T] extends { feedback: infer Feedback }
+    ? Feedback
+    : JsonObject
+  : JsonObject;
+
 export type PublishMessage = {
   op: "publish";
   topic: string;
@@ -26,27 +72,230 @@ export type CliResponseMessage = {
 
 export type BridgeIncomingMessage = PublishMessage | ServiceResponseMessage | CliResponseMessage | JsonObject;
 
+export type ActionEventType = "request" | "feedback" | "result" | "error";
+
+export type BridgeIncomingEvent =
+  | { kind: "publish"; topic: string; msg: JsonObject }
+  | { kind: "service_response"; id?: string; service?: string; ok: boolean; values: JsonObject; error?: string }
+  | { kind: "service_request"; id?: string; service: string; args: JsonObject }
+  | { kind: "action_goal_request"; id: string; action: string; action_type?: string; goal: JsonObject }
+  | { kind: "action_cancel_request"; id: string; action: string }
+  | { kind: "cli_response"; id?: string; ok: boolean; return_code?: number; error?: string; frame: JsonObject }
+  | {
+      kind: "cancel_action_result";
+      action: string;
+      session_id?: string;
+      ok: boolean;
+      error?: string;
+      frame: JsonObject;
+    }
+  | { kind: "action_result"; id?: string; session_id?: string; action?: string; error?: string; result: JsonObject }
+  | { kind: "action_goal_status"; id: string; action: string; session_id?: string; found: boolean }
+  | {
+      kind: "action_event";
+      event: ActionEventType;
+      id?: string;
+      session_id?: string;
+      status?: number;
+      message?: string;
+      payload: JsonObject;
+    }
+  | { kind: "status"; level: string; msg: string; id?: string }
+  | { kind: "error"; code: string; message?: string; id?: string; request_op?: string; target?: string }
+  | { kind: "png"; data: string }
+  | { kind: "fragment"; id: string; data: string; num: number; total: number }
+  | { kind: "unknown"; op: string };
+
+/** Verbosity accepted by `setLevel`; `none` silences status messages. */
+export type BridgeStatusLevel = "info" | "warning" | "error" | "none";
+
+/** A rosbridge `status` message, matched to the request that caused it. */
+export type BridgeStatus = {
+  level: "info" | "warning" | "error" | string;
+  message: string;
+  /** Id of the op that caused it, if the bridge reported one. */
+  id?: string;
+  /** Op of the pending request or subscription with that id. */
+  op?: "subscribe" | BridgeRequestKind;
+  /** Topic, service or action of that request. */
+  target?: string;
+};
+
+export type BridgeProtocolErrorCode =
+  /** The frame does not match the rosbridge protocol; `path` names the field when known. */
+  | "protocol_violation"
+  | "decode_failed"
+  | "fragment_dropped"
+  | "bridge_error";
+
+export type BridgeProtocolErrorInfo = {
+  code: BridgeProtocolErrorCode;
+  op?: string;
+  message: string;
+};
+
+export type BridgeProtocolError = BridgeError & BridgeProtocolErrorInfo & { frame?: unknown };
+
+export type BridgeValidationIssue = {
+  /** Dotted field path such as `linear.x` or `points[2].y`. */
+  path: string;
+  message: string;
+};
+
+export type BridgeValidationError = BridgeError & {
+  code: "schema_violation";
+  type: string;
+  issues: BridgeValidationIssue[];
+};
+
+/**
+ * What happens to a request when the connection drops: `never` rejects it with
+ * `disconnected`, `idempotent` sends it again with the same id after every
+ * reconnect, and `{ attempts, backoffMs }` after at most `attempts`
+ * reconnects, waiting `backoffMs` (doubling each time) before sending.
+ */
+export type BridgeRequestRetry = "never" | "idempotent" | { attempts: number; backoffMs?: number };
+
 export type CallServiceOptions = {
   id?: string;
   timeoutMs?: number;
+  /** Ask the bridge to fragment the response into chunks of this many characters. */
+  fragmentSize?: number;
+  /** Without it, the call keeps waiting for its response until `timeoutMs`. */
+  retry?: BridgeRequestRetry;
+};
+
+/**
+ * Handles requests to a service advertised with `advertiseService`. The return
+ * value becomes the response; a thrown error is sent back as a failed call.
+ */
+export type ServiceHandler<T extends string = string> = (
+  request: ServiceRequestOf<T>
+) => ServiceResponseOf<T> | void | Promise<ServiceResponseOf<T> | void>;
+
+/** A goal sent to an action hosted with `advertiseAction`. */
+export type ActionServerGoal<T extends string = string> = {
+  /** Goal id assigned by the bridge. */
+  readonly id: string;
+  readonly action: string;
+  readonly goal: ActionGoalOf<T>;
+  /** Aborted when the caller cancels the goal or the action is unadvertised. */
+  readonly signal: AbortSignal;
+  publishFeedback(feedback: ActionFeedbackOf<T>): Promise<void>;
+};
+
+/**
+ * Executes goals of an action advertised with `advertiseAction`. Returning
+ * finishes the goal as succeeded and throwing as aborted; either one after the
+ * goal's `signal` fired finishes it as canceled.
+ */
+export type ActionGoalHandler<T extends string = string> = (
+  goal: ActionServerGoal<T>
+) => ActionResultOf<T> | void | Promise<ActionResultOf<T> | void>;
+
+export type AdvertiseOptions = {
+  /** Ask the bridge to keep the last message for late subscribers. */
+  latch?: boolean;
+  /** Outgoing queue size of the bridge-side publisher. */
+  queueSize?: number;
+};
+
+export type PublishOptions<T extends string = string> = {
+  /**
+   * Message type of the topic. Must match the advertised type; with
+   * `autoAdvertise`, used to advertise the topic on first publish.
+   */
+  type?: T;
+};
+
+/** Returned by `advertise`; `dispose` unadvertises the topic. */
+export type Publisher<T extends string = string> = {
+  readonly topic: string;
+  readonly type: T;
+  publish(msg: MessageOf<T>): Promise<void>;
+  /** Sends `unadvertise` and stops re-advertising after reconnects. Calling it again is a no-op. */
+  dispose(): Promise<void>;
 };
 
 export type ExecuteCliOptions = {
   id?: string;
   timeoutMs?: number;
+  /** Without it, a dropped connection rejects the run. */
+  retry?: BridgeRequestRetry;
+};
+
+/** When the bridge received a `cbor-raw` message, from its `secs` and `nsecs`. */
+export type BridgeReceiveTime = {
+  secs: number;
+  nsecs: number;
 };
 
 export type SubscribeOptions = {
   compression?: "none" | "png" | "cbor" | "cbor-raw" | string;
+  /**
+   * Full-text ROS 2 message definition for `type`. With `compression: "cbor-raw"`
+   * the raw CDR bytes are deserialized with it, or with the registered schema
+   * of `type` when it is left out, before the callback runs.
+   */
+  messageDefinition?: string;
+  /** Ask the bridge to fragment messages larger than this many characters. */
+  fragmentSize?: number;
+  /** Minimum interval between messages, in milliseconds, enforced by the bridge. */
+  throttleRate?: number;
+  /** Number of messages the bridge buffers while throttling. */
+  queueLength?: number;
+  /**
+   * Subscription id sent with `subscribe`/`unsubscribe`. Defaults to the id of
+   * the first handle on the topic.
+   */
+  id?: string;
+  /**
+   * rosbridge does not acknowledge `subscribe`. When set, wait this many
+   * milliseconds for an error `status` about the subscription and reject
+   * with it instead of resolving.
+   */
+  confirmMs?: number;
 };
 
-export type SendActionGoalOptions = {
+/** Returned by `subscribe`; the topic is unsubscribed once its last handle is released. */
+export type SubscriptionHandle = {
+  /** Unique per `subscribe` call, e.g. `subscribe:/odom:3`. */
+  readonly id: string;
+  readonly topic: string;
+  /** Removes this handle's callback. Calling it again is a no-op. */
+  unsubscribe(): Promise<void>;
+};
+
+export type BridgeMessageField = {
+  name: string;
+  /** Primitive name (`float64`, `string`, ...) or canonical `pkg/msg/Name`. */
+  type: string;
+  is_message: boolean;
+  array?: "fixed" | "sequence" | "bounded";
+  array_size?: number;
+  string_bound?: number;
+  default?: unknown;
+};
+
+export type BridgeMessageSchema = {
+  name: string;
+  fields: BridgeMessageField[];
+  constants: { name: string; type: string; value: unknown }[];
+};
+
+export type EncodeCdrOptions = {
+  bigEndian?: boolean;
+};
+
+export type SendActionGoalOptions<T extends string = string> = {
   action: string;
-  actionType: string;
-  goal: JsonObject;
+  actionType: T;
+  goal: ActionGoalOf<T>;
   id?: string;
   sessionId?: string;
   timeoutMs?: number;
+  /** Without it, a dropped connection rejects the goal unless `resumeActionGoals` is set. */
+  retry?: BridgeRequestRetry;
   onRequest?: (msg: JsonObject) => void;
   onFeedback?: (msg: JsonObject) => void;
   onResult?: (msg: JsonObject) => void;
@@ -65,13 +314,32 @@ export type ActionHandle = {
   completion: Promise<JsonObject>;
 };
 
+/** How reconnect delays grow; see `ReconnectPolicy` in the Rust core. */
+export type BridgeReconnectStrategy =
+  /** `initialDelayMs * multiplier^(n-1)`, capped at `maxDelayMs` and spread by `jitterRatio`. */
+  | "exponential"
+  /** A random delay between `initialDelayMs` and three times the previous one, capped at `maxDelayMs`. */
+  | "decorrelated"
+  /** The delays of `scheduleMs` in order; the last one repeats. */
+  | "fixed";
+
 export type BridgeReconnectOptions = {
   enabled: boolean;
+  strategy: BridgeReconnectStrategy;
   initialDelayMs: number;
   maxDelayMs: number;
   multiplier: number;
   jitterRatio: number;
+  scheduleMs?: number[];
+  /** Consecutive failed attempts before the circuit opens. Unlimited by default. */
+  maxAttempts?: number;
+  /** Cooldown of an open circuit before a single probe attempt. Without it, an open circuit gives up. */
+  halfOpenAfterMs?: number;
+  /** Seeds the jitter so delays are reproducible. */
+  seed?: number;
   shouldRetry?: (context: BridgeReconnectContext) => boolean;
+  /** Minimum delay the server asked for, e.g. parsed from a close reason. */
+  retryAfterMs?: (context: BridgeReconnectContext) => number | undefined;
 };
 
 export type BridgeReconnectReason =
@@ -90,6 +358,8 @@ export type BridgeReconnectContext = {
 export type BridgeReconnectScheduledEvent = {
   attempt: number;
   nextDelayMs: number;
+  /** The attempt probes an open circuit. */
+  probe: boolean;
   reason: BridgeReconnectReason;
   error?: Error;
 };
@@ -97,13 +367,58 @@ export type BridgeReconnectScheduledEvent = {
 export type BridgeClientOptions = {
   timeoutMs?: number;
   reconnect?: Partial<BridgeReconnectOptions>;
-  strictWasm?: boolean;
   codec?: BridgeCodecOption;
   webSocketFactory?: (url: string) => WebSocketLike;
   onSocketOpen?: (url: string) => void;
   onSocketClose?: () => void;
   onSocketError?: (error: Error) => void;
   onReconnectScheduled?: (event: BridgeReconnectScheduledEvent) => void;
+  /** Every change of `client.state`. */
+  onStateChange?: (change: BridgeStateChange) => void;
+  onProtocolError?: (error: BridgeProtocolError) => void;
+  /**
+   * rosbridge `status` messages. Error statuses about a pending service call
+   * or action goal also reject it.
+   */
+  onStatus?: (status: BridgeStatus) => void;
+  /**
+   * Keep `sendActionGoal` handles pending when the connection drops, and ask
+   * the bridge to re-attach them after reconnecting (tachybridge
+   * `query_action_goal`), so feedback and the result still arrive. Goals the
+   * bridge no longer knows are rejected.
+   */
+  resumeActionGoals?: boolean;
+  /**
+   * Queue frames sent while the connection is being re-established instead
+   * of throwing, and send them once it is back. Without it, every send fails
+   * with a `disconnected` error while the socket is down.
+   */
+  outboundQueue?: BridgeOutboundQueueOptions;
+  /**
+   * Validate `publish`, `callService` and `sendActionGoal` payloads against
+   * registered message schemas before sending. Requires the WASM module.
+   */
+  validateMessages?: boolean;
+  /**
+   * Fill fields missing from `publish`, `callService` and `sendActionGoal`
+   * payloads with registered schema defaults. Requires the WASM module.
+   */
+  fillDefaults?: boolean;
+  /**
+   * Advertise a topic automatically before its first `publish`, using the type
+   * passed to `publish`. Without it, publishing on an unadvertised topic sends a
+   * bare `publish`, which rosbridge rejects.
+   */
+  autoAdvertise?: boolean;
+  /**
+   * Split outgoing JSON publishes longer than this many characters (Unicode
+   * code points, not UTF-16 units) into `fragment` frames.
+   */
+  fragmentSize?: number;
+  /** Drop partially received fragmented messages after this long. Defaults to 10 s. */
+  fragmentTimeoutMs?: number;
+  /** Upper bound on buffered fragment data across messages. Defaults to 64 MiB. */
+  maxFragmentBytes?: number;
 };
 
 export interface WebSocketLike {
@@ -125,12 +440,237 @@ export type BridgeCodec = {
 export type BridgeCodecName = "json" | "cbor" | "auto";
 export type BridgeCodecOption = BridgeCodec | BridgeCodecName;
 
+/** Reassembles rosbridge `fragment` frames; see `FragmentAssembler` in the Rust core. */
+export interface BridgeFragmentAssembler {
+  push(id: string, num: number, total: number, data: string, nowMs: number): string | undefined;
+  expire(nowMs: number): string[];
+  next_deadline(): number | undefined;
+  clear(): void;
+  readonly pending: number;
+  readonly buffered_bytes: number;
+}
+
+export type BridgeSubscriptionAdded = {
+  /** Id of the new handle. */
+  id: string;
+  /** Subscription id to send with `subscribe`. */
+  wire_id: string;
+  /** The topic had no handles before, so `subscribe` must be sent. */
+  first: boolean;
+  /** Previous subscription id, replaced by a requested or renewed one. */
+  replaced?: string;
+};
+
+export type BridgeSubscriptionRemoved = {
+  topic: string;
+  wire_id: string;
+  /** No handles are left, so `unsubscribe` must be sent. */
+  last: boolean;
+};
+
+/** Per-topic handle reference counts; see `SubscriptionRegistry` in the Rust core. */
+export interface BridgeSubscriptionRegistry {
+  /** With `renew`, an already subscribed topic moves to a fresh wire id. */
+  add(topic: string, wireId: string | undefined, renew: boolean): BridgeSubscriptionAdded;
+  remove(id: string): BridgeSubscriptionRemoved | undefined;
+  remove_topic(topic: string): string | undefined;
+  wire_id(topic: string): string | undefined;
+  handle_count(topic: string): number;
+  topics(): string[];
+}
+
+export type BridgeAdvertisement = {
+  topic: string;
+  type: string;
+  latch?: boolean;
+  queue_size?: number;
+  /** Identifies this advertisement, so a stale publisher cannot unadvertise a newer one. */
+  token: number;
+};
+
+export type BridgePublishPlan = {
+  /** Message type of the topic, if known. */
+  type?: string;
+  /** Advertisement to send before the publish. */
+  advertise?: BridgeAdvertisement;
+};
+
+/** Advertised topics and the auto-advertise decision; see `PublisherRegistry` in the Rust core. */
+export interface BridgePublisherRegistry {
+  advertise(topic: string, type: string, latch?: boolean, queueSize?: number): BridgeAdvertisement;
+  unadvertise(topic: string, token?: number): boolean;
+  plan_publish(topic: string, type: string | undefined, autoAdvertise: boolean): BridgePublishPlan;
+  type_of(topic: string): string | undefined;
+  advertisements(): BridgeAdvertisement[];
+}
+
+/** Rosbridge op that starts a tracked request. */
+export type BridgeRequestKind = "call_service" | "send_action_goal" | "cancel_action_goal" | "execute_cli";
+
+export type BridgeTrackedRequest = {
+  id: string;
+  kind: BridgeRequestKind;
+  /** Service or action name; the command for `execute_cli`. */
+  target: string;
+  session_id?: string;
+};
+
+/** A request held over a disconnect; without `resend_after_ms` it is a goal to re-attach. */
+export type BridgeResumedRequest = {
+  request: BridgeTrackedRequest;
+  resend_after_ms?: number;
+};
+
+/** Pending requests, their deadlines and reply matching; see `RequestTracker` in the Rust core. */
+export interface BridgeRequestTracker {
+  track(
+    kind: BridgeRequestKind,
+    id: string | undefined,
+    target: string,
+    sessionId: string | undefined,
+    timeoutMs: number | undefined,
+    nowMs: number
+  ): string;
+  /** Throws for an unknown policy or a request that is not pending. */
+  set_retry(id: string, retry: BridgeRequestRetry): void;
+  find(kind: BridgeRequestKind, id?: string, sessionId?: string, target?: string): string | undefined;
+  correlate(op?: string, id?: string): string | undefined;
+  get(id: string): BridgeTrackedRequest | undefined;
+  settle(id: string): BridgeTrackedRequest | undefined;
+  resolve(kind: BridgeRequestKind, id?: string, sessionId?: string, target?: string): BridgeTrackedRequest | undefined;
+  expire(nowMs: number): BridgeTrackedRequest[];
+  next_deadline(): number | undefined;
+  /** With `keepGoals`, action goals are held so they can be re-attached. */
+  disconnect(keepGoals?: boolean): BridgeTrackedRequest[];
+  /** Releases the requests held over a disconnect. */
+  reconnected(): BridgeResumedRequest[];
+  /** Counts a resend of `id` once it has gone out; false if it is not pending. */
+  resent(id: string): boolean;
+  /** Removes the held requests when no reconnect will follow. */
+  abandon(): BridgeTrackedRequest[];
+  pending(kind: BridgeRequestKind): BridgeTrackedRequest[];
+  pending_count(): number;
+}
+
+/**
+ * Connection state of a client. `rebinding` restores subscriptions,
+ * advertisements and the status level on a fresh socket; `failed` means the
+ * connection was lost and no reconnect will follow.
+ */
+export type BridgeConnectionState =
+  | "idle"
+  | "connecting"
+  | "rebinding"
+  | "open"
+  | "reconnect_wait"
+  | "closed"
+  | "failed";
+
+export type BridgeConnectionEvent =
+  | "connect"
+  | "socket_open"
+  | "rebound"
+  | "schedule_reconnect"
+  | "attempt"
+  | "give_up"
+  | "close";
+
+export type BridgeStateChange = {
+  from: BridgeConnectionState;
+  to: BridgeConnectionState;
+  /** Why the connection was lost, for `reconnect_wait` and `failed`. */
+  error?: Error;
+};
+
+/** Validated connection state transitions; see `ConnectionStateMachine` in the Rust core. */
+export interface BridgeConnectionStateMachine {
+  state(): BridgeConnectionState;
+  can(event: BridgeConnectionEvent): boolean;
+  apply(event: BridgeConnectionEvent): Omit<BridgeStateChange, "error"> | undefined;
+}
+
+export type BridgeCircuitState = "closed" | "open" | "half_open";
+
+export type BridgeReconnectDecision = {
+  attempt: number;
+  delay_ms: number;
+  probe: boolean;
+};
+
+/** `BridgeReconnectOptions` without the switches and callbacks the client handles itself. */
+export type BridgeReconnectPolicyConfig = Omit<BridgeReconnectOptions, "enabled" | "shouldRetry" | "retryAfterMs">;
+
+/** Reconnect delays and circuit breaker; see `ReconnectPolicy` in the Rust core. */
+export interface BridgeReconnectPolicy {
+  state(): BridgeCircuitState;
+  failures(): number;
+  /** `random` is the jitter sample used when no `seed` is configured. */
+  on_failure(retryAfterMs?: number, random?: number): BridgeReconnectDecision | undefined;
+  on_attempt(): void;
+  reset(): void;
+}
+
+/**
+ * What happens to a frame sent while the connection is down: `drop` discards
+ * it, `keep_latest` keeps only the newest frame per op and topic, service or
+ * action, and `keep_all` keeps every frame.
+ */
+export type BridgeQueuePolicy = "drop" | "keep_latest" | "keep_all";
+
+export type BridgeOutboundQueueOptions = {
+  /** Policy per rosbridge op, e.g. `{ publish: "keep_latest" }`. Ops without one fail as usual. */
+  ops?: Record<string, BridgeQueuePolicy>;
+  /** Policy per topic, service or action; overrides `ops`. */
+  targets?: Record<string, BridgeQueuePolicy>;
+  /** Cap on the encoded size of all queued frames. Defaults to 1 MiB. */
+  maxBytes?: number;
+};
+
+export type BridgeQueueAdmission =
+  | { action: "queued"; id: number; replaces?: number }
+  | { action: "dropped" }
+  | { action: "refused" };
+
+export type BridgeQueuedFrame = {
+  id: number;
+  op: string;
+  target?: string;
+  bytes: number;
+};
+
+/** Frames waiting for the connection; see `OutboundQueue` in the Rust core. */
+export interface BridgeOutboundQueue {
+  /** Throws when the frame does not fit in `maxBytes`. */
+  offer(op: string, target: string | undefined, bytes: number): BridgeQueueAdmission;
+  front(): BridgeQueuedFrame | undefined;
+  remove(id: number): boolean;
+  clear(): void;
+  len(): number;
+  bytes(): number;
+}
+
 export type WasmProtocol = {
-  build_subscribe(topic: string, type: string, compression?: string): JsonObject;
-  build_unsubscribe(topic: string): JsonObject;
-  build_advertise(topic: string, type: string): JsonObject;
+  build_subscribe(
+    topic: string,
+    type: string,
+    compression?: string,
+    fragmentSize?: number,
+    throttleRate?: number,
+    queueLength?: number,
+    id?: string
+  ): JsonObject;
+  build_unsubscribe(topic: string, id?: string): JsonObject;
+  build_advertise(topic: string, type: string, latch?: boolean, queueSize?: number): JsonObject;
+  build_unadvertise(topic: string): JsonObject;
   build_publish(topic: string, msg: JsonObject): JsonObject;
-  build_call_service(service: string, type: string, args: JsonObject, id?: string): JsonObject;
+  build_set_level(level: BridgeStatusLevel, id?: string): JsonObject;
+  build_call_service(
+    service: string,
+    type: string,
+    args: JsonObject,
+    id?: string,
+    fragmentSize?: number
+  ): JsonObject;
   build_send_action_goal(
     action: string,
     actionType: string,
@@ -139,4 +679,35 @@ export type WasmProtocol = {
     sessionId?: string
   ): JsonObject;
   build_cancel_action_goal(action: string, actionType: string, sessionId?: string): JsonObject;
+  build_query_action_goal(action: string, id: string, sessionId?: string): JsonObject;
+  build_advertise_service(service: string, type: string): JsonObject;
+  build_unadvertise_service(service: string): JsonObject;
+  build_service_response(service: string, id: string | undefined, result: boolean, values: JsonObject | string): JsonObject;
+  build_advertise_action(action: string, actionType: string): JsonObject;
+  build_unadvertise_action(action: string): JsonObject;
+  build_action_feedback(action: string, id: string, feedback: JsonObject): JsonObject;
+  build_action_result(action: string, id: string, status: number, result: JsonObject | string): JsonObject;
+  parse_incoming(frame: unknown): BridgeIncomingEvent;
+  encode_cbor?(value: unknown): Uint8Array;
+  decode_cbor?(bytes: Uint8Array): unknown;
+  decode_cdr?(bytes: Uint8Array, type: string, definition?: string): JsonObject;
+  encode_cdr?(msg: JsonObject, type: string, definition?: string, bigEndian?: boolean): Uint8Array;
+  register_message_definitions?(text: string): string[];
+  register_message_definition?(type: string, text: string): string[];
+  registered_message_types?(): string[];
+  unresolved_message_types?(): string[];
+  get_message_schema?(type: string): BridgeMessageSchema | undefined;
+  clear_message_definitions?(): void;
+  validate_message?(type: string, msg: JsonObject): void;
+  default_message?(type: string): JsonObject;
+  merge_with_defaults?(type: string, partial: JsonObject): JsonObject;
+  build_fragments(text: string, id: string, size: number): JsonObject[];
+  decode_png?(data: string): string;
+  FragmentAssembler?: new (timeoutMs: number, maxBytes: number) => BridgeFragmentAssembler;
+  SubscriptionRegistry?: new () => BridgeSubscriptionRegistry;
+  PublisherRegistry?: new () => BridgePublisherRegistry;
+  RequestTracker?: new (namespace?: string) => BridgeRequestTracker;
+  ReconnectPolicy?: new (config: BridgeReconnectPolicyConfig) => BridgeReconnectPolicy;
+  ConnectionStateMachine?: new () => BridgeConnectionStateMachine;
+  OutboundQueue?: new (config: BridgeOutboundQueueOptions) => BridgeOutboundQueue;
 };
@@ -0,0 +1,315 @@
+//! Schema validation of outgoing payloads against registered definitions.
+
+use serde::Serialize;
+use wasm_bindgen::prelude::*;
+
+use crate::cbor::{self, CborValue};
+use crate::error::{BridgeError, ErrorCode};
+use crate::msgdef::{self, ArrayKind, Field, FieldType, MessageSet, Primitive};
+use crate::schema;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Issue {
+    /// Dotted field path such as `linear.x` or `points[2].y`.
+    pub path: String,
+    pub message: String,
+}
+
+fn join(path: &str, name: &str) -> String {
+    if path.is_empty() {
+        name.to_owned()
+    } else {
+        format!("{path}.{name}")
+    }
+}
+
+struct Validator<'d> {
+    defs: &'d MessageSet,
+    issues: Vec<Issue>,
+}
+
+impl Validator<'_> {
+    fn issue(&mut self, path: &str, message: impl Into<String>) {
+        self.issues.push(Issue {
+            path: path.to_owned(),
+            message: message.into(),
+        });
+    }
+
+    fn message(&mut self, type_name: &str, value: &CborValue, path: &str) {
+        let Some(def) = self.defs.get(type_name) else {
+            self.issue(path, format!("no schema registered for {type_name}"));
+            return;
+        };
+        let CborValue::Map(entries) = value else {
+            self.issue(path, "expected an object");
+            return;
+        };
+        for (key, value) in entries {
+            let CborValue::Text(key) = key else {
+                self.issue(path, "object keys must be strings");
+                continue;
+            };
+            let field_path = join(path, key);
+            match def.fields.iter().find(|field| field.name == *key) {
+                Some(field) => self.field(field, value, &field_path),
+                None => self.issue(&field_path, format!("unknown field for {}", def.name)),
+            }
+        }
+    }
+
+    fn field(&mut self, field: &Field, value: &CborValue, path: &str) {
+        if field.array == ArrayKind::Single {
+            self.single(field, value, path);
+            return;
+        }
+        // rosbridge accepts base64 text for byte arrays.
+        let bytes_like = matches!(
+            field.ty,
+            FieldType::Primitive(Primitive::Uint8 | Primitive::Byte | Primitive::Char)
+        );
+        if bytes_like && matches!(value, CborValue::Text(_)) {
+            return;
+        }
+        let Some(items) = value.array_items() else {
+            self.issue(path, "expected an array");
+            return;
+        };
+        match field.array {
+            ArrayKind::Fixed(n) if items.len() != n => {
+                self.issue(
+                    path,
+                    format!("expected {n} elements, found {}", items.len()),
+                );
+            }
+            ArrayKind::Bounded(n) if items.len() > n => {
+                self.issue(
+                    path,
+                    format!("at most {n} elements allowed, found {}", items.len()),
+                );
+            }
+            _ => {}
+        }
+        for (index, item) in items.iter().enumerate() {
+            self.single(field, item, &format!("{path}[{index}]"));
+        }
+    }
+
+    fn single(&mut self, field: &Field, value: &CborValue, path: &str) {
+        match &field.ty {
+            FieldType::Message(name) => self.message(name, value, path),
+            FieldType::Primitive(primitive) => {
+                if let Err(message) = check_primitive(*primitive, field.string_bound, value) {
+                    self.issue(path, message);
+                }
+            }
+        }
+    }
+}
+
+fn check_primitive(
+    primitive: Primitive,
+    string_bound: Option<usize>,
+    value: &CborValue,
+) -> Result<(), String> {
+    if let Some((min, max)) = primitive.int_range() {
+        let n = value
+            .as_integer()
+            .ok_or_else(|| format!("expected an integer ({})", primitive.name()))?;
+        if n < min || n > max {
+            return Err(format!(
+                "{n} is out of range for {} ({min}..={max})",
+                primitive.name()
+            ));
+        }
+        return Ok(());
+    }
+    match primitive {
+        Primitive::Bool if !matches!(value, CborValue::Bool(_)) => {
+            Err("expected a boolean".to_owned())
+        }
+        Primitive::Float32 | Primitive::Float64 => {
+            let f = value
+                .as_f64()
+                .ok_or_else(|| format!("expected a number ({})", primitive.name()))?;
+            if primitive == Primitive::Float32 && f.is_finite() && f.abs() > f64::from(f32::MAX) {
+                return Err(format!("{f} is out of range for float32"));
+            }
+            Ok(())
+        }
+        Primitive::String | Primitive::WString => {
+            let CborValue::Text(text) = value else {
+                return Err("expected a string".to_owned());
+            };
+            match string_bound {
+                Some(bound) if primitive.bounded_len(text) > bound => {
+                    Err(format!("string is longer than its bound of {bound}"))
+                }
+                _ => Ok(()),
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Checks `value` against the schema of `type_name`, returning every issue
+/// found. Missing fields are allowed since rosbridge fills in defaults.
+pub fn validate(value: &CborValue, type_name: &str, defs: &MessageSet) -> Vec<Issue> {
+    let mut validator = Validator {
+        defs,
+        issues: Vec::new(),
+    };
+    match msgdef::canonical_name(type_name) {
+        Some(name) => validator.message(&name, value, ""),
+        None => validator.issue("", format!("invalid type name {type_name}")),
+    }
+    validator.issues
+}
+
+fn set(target: &JsValue, key: &str, value: &JsValue) -> Result<(), JsValue> {
+    js_sys::Reflect::set(target, &JsValue::from_str(key), value).map(|_| ())
+}
+
+/// Validates `msg` against the registered schema of `msg_type`. Throws a
+/// `BridgeError` with `code: "schema_violation"`, the `path` of the first
+/// issue and an `issues` array of `{ path, message }` when it does not match.
+#[wasm_bindgen]
+pub fn validate_message(msg_type: String, msg: JsValue) -> Result<(), JsValue> {
+    let value = cbor::js_to_value(&msg, 0)?;
+    let issues = schema::with_registry(|defs| validate(&value, &msg_type, defs));
+    if issues.is_empty() {
+        return Ok(());
+    }
+    let summary: Vec<String> = issues
+        .iter()
+        .map(|issue| match issue.path.as_str() {
+            "" => issue.message.clone(),
+            path => format!("{path}: {}", issue.message),
+        })
+        .collect();
+    let mut error = BridgeError::new(
+        ErrorCode::SchemaViolation,
+        format!("invalid {msg_type}: {}", summary.join("; ")),
+    );
+    if let Some(first) = issues.first() {
+        error = error.with_path(first.path.as_str());
+    }
+    let error: JsValue = error.into();
+    set(&error, "type", &JsValue::from_str(&msg_type))?;
+    set(&error, "issues", &crate::to_js_object(&issues)?)?;
+    Err(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cbor::TypedArray;
+
+    fn text(s: &str) -> CborValue {
+        CborValue::Text(s.to_owned())
+    }
+
+    fn map(entries: Vec<(&str, CborValue)>) -> CborValue {
+        CborValue::Map(entries.into_iter().map(|(k, v)| (text(k), v)).collect())
+    }
+
+    fn defs() -> MessageSet {
+        msgdef::parse_full_text(
+            "demo/Drive",
+            "geometry_msgs/Vector3 linear\n\
+             int8 gear\n\
+             uint64 ticks\n\
+             string<=4 mode\n\
+             float32[<=2] gains\n\
+             uint8[] blob\n\
+             geometry_msgs/Vector3[2] corners\n\
+             ===\n\
+             MSG: geometry_msgs/Vector3\n\
+             float64 x\nfloat64 y\nfloat64 z\n",
+        )
+        .unwrap()
+    }
+
+    fn paths(issues: &[Issue]) -> Vec<(&str, &str)> {
+        issues
+            .iter()
+            .map(|issue| (issue.path.as_str(), issue.message.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn accepts_valid_and_sparse_messages() {
+        let value = map(vec![
+            ("linear", map(vec![("x", CborValue::Float(1.0))])),
+            ("gear", CborValue::Negative(0)),
+            ("ticks", CborValue::Unsigned(u64::MAX)),
+            ("mode", text("fast")),
+            ("gains", TypedArray::Float32(vec![0.5]).to_cbor()),
+            ("blob", text("AAEC")),
+        ]);
+        assert!(validate(&value, "demo/msg/Drive", &defs()).is_empty());
+        assert!(validate(&map(vec![]), "demo/Drive", &defs()).is_empty());
+    }
+
+    #[test]
+    fn lists_every_bad_field_path() {
+        let value = map(vec![
+            (
+                "linear",
+                map(vec![("X", CborValue::Float(1.0)), ("y", text("1"))]),
+            ),
+            ("gear", CborValue::Unsigned(200)),
+            ("ticks", CborValue::Float(1.5)),
+            ("mode", text("turbo")),
+            ("gains", CborValue::Array(vec![CborValue::Float(1.0); 3])),
+            ("blob", CborValue::Array(vec![CborValue::Negative(0)])),
+            (
+                "corners",
+                CborValue::Array(vec![map(vec![("z", CborValue::Bool(true))])]),
+            ),
+        ]);
+        assert_eq!(
+            paths(&validate(&value, "demo/Drive", &defs())),
+            [
+                ("linear.X", "unknown field for geometry_msgs/msg/Vector3"),
+                ("linear.y", "expected a number (float64)"),
+                ("gear", "200 is out of range for int8 (-128..=127)"),
+                ("ticks", "expected an integer (uint64)"),
+                ("mode", "string is longer than its bound of 4"),
+                ("gains", "at most 2 elements allowed, found 3"),
+                ("blob[0]", "-1 is out of range for uint8 (0..=255)"),
+                ("corners", "expected 2 elements, found 1"),
+                ("corners[0].z", "expected a number (float64)"),
+            ]
+        );
+    }
+
+    #[test]
+    fn counts_string_bounds_like_cdr() {
+        let defs = msgdef::parse_full_text("demo/Label", "string<=4 mode\n").unwrap();
+        // "añb" has 3 characters in 4 bytes.
+        let at_bound = map(vec![("mode", text("añb"))]);
+        assert!(validate(&at_bound, "demo/Label", &defs).is_empty());
+        assert!(crate::cdr::serialize(&at_bound, "demo/Label", &defs, true).is_ok());
+
+        // "añob" has 4 characters but 5 bytes.
+        let over = map(vec![("mode", text("añob"))]);
+        assert_eq!(
+            paths(&validate(&over, "demo/Label", &defs)),
+            [("mode", "string is longer than its bound of 4")]
+        );
+        assert!(crate::cdr::serialize(&over, "demo/Label", &defs, true).is_err());
+    }
+
+    #[test]
+    fn reports_unregistered_types() {
+        assert_eq!(
+            paths(&validate(&map(vec![]), "demo/Missing", &defs())),
+            [("", "no schema registered for demo/msg/Missing")]
+        );
+        assert_eq!(
+            paths(&validate(&text("x"), "demo/Drive", &defs())),
+            [("", "expected an object")]
+        );
+    }
+}
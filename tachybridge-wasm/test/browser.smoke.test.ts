@@ -27,7 +27,6 @@ describe("browser smoke", () => {
   it("can connect with injected websocket factory", async () => {
     const sockets: FakeWebSocket[] = [];
     const client = new BridgeClient({
-      strictWasm: false,
       webSocketFactory: (url) => {
         const ws = new FakeWebSocket(url);
         sockets.push(ws);
@@ -55,7 +54,6 @@ describe("browser smoke", () => {
   it("can send CBOR binary frame with cbor codec", async () => {
     const sockets: FakeWebSocket[] = [];
     const client = new BridgeClient({
-      strictWasm: false,
       codec: cborCodec,
       webSocketFactory: (url) => {
         const ws = new FakeWebSocket(url);
@@ -0,0 +1,669 @@
+import { describe, expect, it, vi } from "vitest";
+import { BridgeClientCore } from "../src/client-core.js";
+import { BridgeError } from "../src/errors.js";
+import type {
+  BridgeClientOptions,
+  BridgeProtocolError,
+  BridgeStatus,
+  BridgeStatusLevel,
+  JsonObject,
+  WasmProtocol,
+  WebSocketLike
+} from "../src/types.js";
+import { wasmProtocol } from "./wasm.js";
+
+class LoopbackSocket implements WebSocketLike {
+  readyState = 0;
+  onopen: ((event: unknown) => void) | null = null;
+  onmessage: ((event: { data: unknown }) => void) | null = null;
+  onerror: ((event: unknown) => void) | null = null;
+  onclose: ((event: unknown) => void) | null = null;
+  readonly sent: JsonObject[] = [];
+
+  constructor() {
+    setTimeout(() => {
+      this.readyState = 1;
+      this.onopen?.({});
+    }, 0);
+  }
+
+  send(data: string | Uint8Array): void {
+    this.sent.push(JSON.parse(String(data)) as JsonObject);
+  }
+
+  receive(frame: JsonObject): void {
+    this.onmessage?.({ data: JSON.stringify(frame) });
+  }
+
+  close(): void {
+    this.readyState = 3;
+  }
+}
+
+async function connectClient(
+  protocol: WasmProtocol,
+  options: BridgeClientOptions = {}
+): Promise<{ client: BridgeClientCore; socket: LoopbackSocket }> {
+  let socket: LoopbackSocket | undefined;
+  const client = new BridgeClientCore(() => Promise.resolve(protocol), {
+    reconnect: { enabled: false },
+    ...options,
+    webSocketFactory: () => {
+      socket = new LoopbackSocket();
+      return socket;
+    }
+  });
+  await client.connect("ws://loopback");
+  return { client, socket: socket as LoopbackSocket };
+}
+
+describe("cbor-raw subscriptions", () => {
+  it("decodes raw bytes with the message definition", async () => {
+    const decodeCdr = vi.fn((bytes: Uint8Array, type: string, definition: string) => ({
+      size: bytes.length,
+      type,
+      definition
+    }));
+    const { client, socket } = await connectClient({ ...wasmProtocol, decode_cdr: decodeCdr });
+    const received: unknown[][] = [];
+    await client.subscribe("/scan", "demo/msg/Scan", (...args) => received.push(args), {
+      compression: "cbor-raw",
+      messageDefinition: "float32[] ranges"
+    });
+
+    socket.receive({ op: "publish", topic: "/scan", msg: { bytes: [0, 1, 0, 0], secs: 1, nsecs: 2 } });
+
+    await vi.waitFor(() => expect(received).toHaveLength(1));
+    expect(received[0]).toEqual([
+      { size: 4, type: "demo/msg/Scan", definition: "float32[] ranges" },
+      { secs: 1, nsecs: 2 }
+    ]);
+    expect(decodeCdr.mock.calls[0][0]).toBeInstanceOf(Uint8Array);
+  });
+
+  it("decodes raw bytes with the registered schema", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    await client.registerMessageDefinition("demo/msg/RawReading", "float64 value\n");
+    const received: unknown[][] = [];
+    await client.subscribe("/reading", "demo/msg/RawReading", (...args) => received.push(args), {
+      compression: "cbor-raw"
+    });
+
+    // Encapsulation header, then 1.5 as a little-endian float64.
+    const bytes = [0, 1, 0, 0, 0, 0, 0, 0, 0, 0, 0xf8, 0x3f];
+    socket.receive({ op: "publish", topic: "/reading", msg: { bytes, secs: 7, nsecs: 500 } });
+
+    await vi.waitFor(() => expect(received).toHaveLength(1));
+    expect(received[0]).toEqual([{ value: 1.5 }, { secs: 7, nsecs: 500 }]);
+  });
+
+  it("reports decode failures instead of invoking callbacks", async () => {
+    const errors: BridgeProtocolError[] = [];
+    const { client, socket } = await connectClient(wasmProtocol, { onProtocolError: (error) => errors.push(error) });
+    const callback = vi.fn();
+    await client.subscribe("/scan", "demo/msg/Scan", callback, {
+      compression: "cbor-raw",
+      messageDefinition: "float32[] ranges"
+    });
+
+    socket.receive({ op: "publish", topic: "/scan", msg: { bytes: [0, 1, 0, 0] } });
+
+    await vi.waitFor(() => expect(errors).toHaveLength(1));
+    expect(errors[0]).toMatchObject({ code: "decode_failed", op: "publish" });
+    expect(callback).not.toHaveBeenCalled();
+  });
+});
+
+describe("wasm-only features", () => {
+  it("reject clearly when the wasm module is unavailable", async () => {
+    const client = new BridgeClientCore(() => Promise.reject(new Error("no wasm")), {
+      reconnect: { enabled: false },
+      webSocketFactory: () => new LoopbackSocket()
+    });
+    const unavailable = { code: "unsupported", message: "Failed to load the bridge_wasm module: no wasm" };
+    await expect(client.connect("ws://loopback")).rejects.toMatchObject(unavailable);
+    await expect(client.registerMessageDefinitions("MSG: demo/Point\nfloat64 x\n")).rejects.toMatchObject(
+      unavailable
+    );
+    await expect(client.decodeCdr("demo/msg/Point", new Uint8Array([0, 1, 0, 0]))).rejects.toMatchObject(unavailable);
+  });
+});
+
+describe("payload validation", () => {
+  it("validates against the advertised, request and goal types before sending", async () => {
+    const validateMessage = vi.fn((type: string, msg: JsonObject) => {
+      if ("bad" in msg) {
+        throw Object.assign(new Error(`invalid ${type}`), { code: "schema_violation", type, issues: [] });
+      }
+    });
+    const { client, socket } = await connectClient(
+      { ...wasmProtocol, validate_message: validateMessage },
+      { validateMessages: true }
+    );
+
+    await client.advertise("/cmd_vel", "geometry_msgs/msg/Twist");
+    const rejected = client.publish("/cmd_vel", { bad: 1 });
+    await expect(rejected).rejects.toBeInstanceOf(BridgeError);
+    await expect(rejected).rejects.toMatchObject({
+      code: "schema_violation",
+      type: "geometry_msgs/msg/Twist",
+      issues: []
+    });
+    await expect(client.callService("/add", "demo/AddTwo", { bad: 1 })).rejects.toThrow(
+      "invalid demo/srv/AddTwo_Request"
+    );
+    await expect(
+      client.sendActionGoal({ action: "/go", actionType: "demo/action/Go", goal: { bad: 1 } })
+    ).rejects.toThrow("invalid demo/action/Go_Goal");
+    await client.publish("/unadvertised", { bad: 1 });
+
+    expect(socket.sent.map((frame) => frame.op)).toEqual(["advertise", "publish"]);
+    expect(validateMessage).toHaveBeenCalledTimes(3);
+  });
+});
+
+describe("default filling", () => {
+  it("merges defaults before validating and sending", async () => {
+    const calls: string[] = [];
+    const protocol: WasmProtocol = {
+      ...wasmProtocol,
+      merge_with_defaults: (type, partial) => {
+        calls.push(`merge ${type}`);
+        return { linear: { x: 0, y: 0, z: 0 }, ...partial };
+      },
+      validate_message: (type, msg) => {
+        calls.push(`validate ${type} ${Object.keys(msg).join(",")}`);
+      }
+    };
+    const { client, socket } = await connectClient(protocol, { fillDefaults: true, validateMessages: true });
+
+    await client.advertise("/cmd_vel", "geometry_msgs/msg/Twist");
+    await client.publish("/cmd_vel", { angular: { z: 1 } });
+
+    expect(calls).toEqual(["merge geometry_msgs/msg/Twist", "validate geometry_msgs/msg/Twist linear,angular"]);
+    expect(socket.sent[1]).toMatchObject({ op: "publish", msg: { linear: { x: 0 }, angular: { z: 1 } } });
+  });
+});
+
+describe("fragmentation", () => {
+  it("reassembles fragmented frames before dispatching", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const received: JsonObject[] = [];
+    await client.subscribe("/map", "nav_msgs/msg/OccupancyGrid", (msg) => received.push(msg), { fragmentSize: 8 });
+    expect(socket.sent[0]).toMatchObject({ op: "subscribe", fragment_size: 8 });
+
+    const text = JSON.stringify({ op: "publish", topic: "/map", msg: { data: [1, 2, 3] } });
+    const parts = [text.slice(0, 10), text.slice(10, 30), text.slice(30)];
+    for (const num of [2, 0, 1]) {
+      socket.receive({ op: "fragment", id: "map-1", data: parts[num], num, total: 3 });
+    }
+
+    await vi.waitFor(() => expect(received).toEqual([{ data: [1, 2, 3] }]));
+  });
+
+  it("reports fragments that exceed the buffer", async () => {
+    const errors: BridgeProtocolError[] = [];
+    const { socket } = await connectClient(wasmProtocol, {
+      maxFragmentBytes: 4,
+      onProtocolError: (error) => errors.push(error)
+    });
+
+    socket.receive({ op: "fragment", id: "big", data: "0123456789", num: 0, total: 2 });
+
+    await vi.waitFor(() => expect(errors).toHaveLength(1));
+    expect(errors[0]).toMatchObject({ code: "fragment_dropped", op: "fragment" });
+  });
+
+  it("times out incomplete messages without waiting for another fragment", async () => {
+    const errors: BridgeProtocolError[] = [];
+    const { socket } = await connectClient(wasmProtocol, {
+      fragmentTimeoutMs: 5,
+      onProtocolError: (error) => errors.push(error)
+    });
+
+    socket.receive({ op: "fragment", id: "map-1", data: "{", num: 0, total: 2 });
+
+    await vi.waitFor(() => expect(errors).toHaveLength(1));
+    expect(errors[0]).toMatchObject({ code: "fragment_dropped", message: "fragmented message map-1 timed out" });
+  });
+
+  it("drops fragments when the wasm module has no assembler", async () => {
+    const errors: BridgeProtocolError[] = [];
+    const { socket } = await connectClient(
+      { ...wasmProtocol, FragmentAssembler: undefined },
+      { onProtocolError: (error) => errors.push(error) }
+    );
+
+    socket.receive({ op: "fragment", id: "map-1", data: "{", num: 0, total: 2 });
+
+    await vi.waitFor(() => expect(errors).toHaveLength(1));
+    expect(errors[0]).toMatchObject({
+      code: "fragment_dropped",
+      message: "Fragment reassembly requires the bridge_wasm module"
+    });
+  });
+
+  it("splits large outgoing publishes", async () => {
+    const points = Array.from({ length: 40 }, (_, index) => index);
+    const { client, socket } = await connectClient(wasmProtocol, { fragmentSize: 64 });
+    await client.publish("/cloud", { points });
+    await client.publish("/s", {});
+
+    const fragments = socket.sent.filter((frame) => frame.op === "fragment");
+    expect(fragments.length).toBeGreaterThan(1);
+    expect(fragments.map((frame) => frame.num)).toEqual(fragments.map((_, index) => index));
+    expect(JSON.parse(fragments.map((frame) => frame.data).join(""))).toEqual({
+      op: "publish",
+      topic: "/cloud",
+      msg: { points }
+    });
+    expect(socket.sent.at(-1)).toEqual({ op: "publish", topic: "/s", msg: {} });
+  });
+
+  it("measures outgoing frames in characters rather than UTF-16 units", async () => {
+    const frame = { op: "publish", topic: "/t", msg: { data: "😀".repeat(10) } };
+    const size = Array.from(JSON.stringify(frame)).length;
+    const { client, socket } = await connectClient(wasmProtocol, { fragmentSize: size });
+    await client.publish("/t", frame.msg);
+
+    expect(socket.sent).toEqual([frame]);
+  });
+});
+
+describe("png compression", () => {
+  it("dispatches the frame carried by a png frame", async () => {
+    const inner = { op: "publish", topic: "/map", msg: { data: [0, 100, -1] } };
+    const decodePng = vi.fn((data: string) => (data === "PNGDATA" ? JSON.stringify(inner) : "{"));
+    const errors: BridgeProtocolError[] = [];
+    const { client, socket } = await connectClient(
+      { ...wasmProtocol, decode_png: decodePng },
+      { onProtocolError: (error) => errors.push(error) }
+    );
+    const received: JsonObject[] = [];
+    await client.subscribe("/map", "nav_msgs/msg/OccupancyGrid", (msg) => received.push(msg), { compression: "png" });
+
+    socket.receive({ op: "png", data: "PNGDATA" });
+    socket.receive({ op: "png", data: "broken" });
+
+    await vi.waitFor(() => expect(errors).toHaveLength(1));
+    expect(received).toEqual([{ data: [0, 100, -1] }]);
+    expect(errors[0]).toMatchObject({ code: "decode_failed", op: "png" });
+  });
+
+  it("reports png frames when the wasm module is unavailable", async () => {
+    const errors: BridgeProtocolError[] = [];
+    const { socket } = await connectClient(
+      { ...wasmProtocol, decode_png: undefined },
+      { onProtocolError: (error) => errors.push(error) }
+    );
+
+    socket.receive({ op: "png", data: "iVBORw0KGgo=" });
+
+    await vi.waitFor(() => expect(errors).toHaveLength(1));
+    expect(errors[0].message).toBe("PNG decompression requires the bridge_wasm module");
+  });
+});
+
+describe("subscribe options", () => {
+  it("sends rosbridge v2 options and resubscribes only when they change", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const options = { throttleRate: 100, queueLength: 1, fragmentSize: 4096, id: "sub-status" };
+    await client.subscribe("/status", "std_msgs/msg/String", () => {}, options);
+    await client.subscribe("/status", "std_msgs/msg/String", () => {}, options);
+    await client.subscribe("/status", "std_msgs/msg/String", () => {}, { id: "sub-status" });
+    await client.subscribe("/status", "std_msgs/msg/String", () => {}, { ...options, id: "sub-status-2" });
+    await client.unsubscribe("/status");
+
+    expect(socket.sent).toEqual([
+      {
+        op: "subscribe",
+        topic: "/status",
+        type: "std_msgs/msg/String",
+        throttle_rate: 100,
+        queue_length: 1,
+        fragment_size: 4096,
+        id: "sub-status"
+      },
+      expect.objectContaining({ op: "subscribe", throttle_rate: 100, id: "sub-status-2" }),
+      { op: "unsubscribe", topic: "/status", id: "sub-status" },
+      { op: "unsubscribe", topic: "/status", id: "sub-status-2" }
+    ]);
+  });
+
+  it("resubscribes under a fresh id when a handle changes the throttle", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const fast: JsonObject[] = [];
+    const slow: JsonObject[] = [];
+    const first = await client.subscribe("/imu", "sensor_msgs/msg/Imu", (msg) => fast.push(msg), {
+      throttleRate: 100
+    });
+    const second = await client.subscribe("/imu", "sensor_msgs/msg/Imu", (msg) => slow.push(msg), {
+      throttleRate: 500
+    });
+    await client.subscribe("/imu", "sensor_msgs/msg/Imu", () => {});
+
+    socket.receive({ op: "publish", topic: "/imu", msg: { seq: 1 } });
+    expect(fast).toEqual([{ seq: 1 }]);
+    expect(slow).toEqual([{ seq: 1 }]);
+    expect(socket.sent).toEqual([
+      { op: "subscribe", topic: "/imu", type: "sensor_msgs/msg/Imu", throttle_rate: 100, id: first.id },
+      { op: "subscribe", topic: "/imu", type: "sensor_msgs/msg/Imu", throttle_rate: 500, id: second.id },
+      { op: "unsubscribe", topic: "/imu", id: first.id }
+    ]);
+  });
+});
+
+describe("subscription handles", () => {
+  it("keeps the topic subscribed until the last handle is released", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const first: JsonObject[] = [];
+    const second: JsonObject[] = [];
+    const a = await client.subscribe("/odom", "nav_msgs/msg/Odometry", (msg) => first.push(msg));
+    const b = await client.subscribe("/odom", "nav_msgs/msg/Odometry", (msg) => second.push(msg));
+    expect(a).toMatchObject({ id: "subscribe:/odom:1", topic: "/odom" });
+    expect(b.id).toBe("subscribe:/odom:2");
+
+    socket.receive({ op: "publish", topic: "/odom", msg: { seq: 1 } });
+    await a.unsubscribe();
+    await a.unsubscribe();
+    socket.receive({ op: "publish", topic: "/odom", msg: { seq: 2 } });
+    expect(first).toEqual([{ seq: 1 }]);
+    expect(second).toEqual([{ seq: 1 }, { seq: 2 }]);
+    expect(socket.sent).toEqual([{ op: "subscribe", topic: "/odom", type: "nav_msgs/msg/Odometry", id: a.id }]);
+
+    await b.unsubscribe();
+    expect(socket.sent.at(-1)).toEqual({ op: "unsubscribe", topic: "/odom", id: a.id });
+  });
+
+  it("releases every handle when unsubscribing by topic", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const handle = await client.subscribe("/odom", "nav_msgs/msg/Odometry", () => {});
+    await client.subscribe("/odom", "nav_msgs/msg/Odometry", () => {});
+    await client.unsubscribe("/odom");
+    await handle.unsubscribe();
+    expect(socket.sent.filter((frame) => frame.op === "unsubscribe")).toEqual([
+      { op: "unsubscribe", topic: "/odom", id: handle.id }
+    ]);
+  });
+});
+
+describe("status messages", () => {
+  it("reports statuses with the request they are about", async () => {
+    const statuses: BridgeStatus[] = [];
+    const { client, socket } = await connectClient(wasmProtocol, { onStatus: (status) => statuses.push(status) });
+    await client.setLevel("warning");
+    await client.subscribe("/odom", "nav_msgs/msg/Odometry", () => {});
+    socket.receive({ op: "status", level: "warning", msg: "slow consumer", id: "subscribe:/odom:1" });
+    socket.receive({ op: "status", level: "info", msg: "hello" });
+
+    expect(socket.sent[0]).toEqual({ op: "set_level", level: "warning" });
+    expect(statuses).toEqual([
+      { level: "warning", message: "slow consumer", id: "subscribe:/odom:1", op: "subscribe", target: "/odom" },
+      { level: "info", message: "hello" }
+    ]);
+    await expect(client.setLevel("verbose" as BridgeStatusLevel)).rejects.toThrow(/unknown status level/);
+  });
+
+  it("rejects the service call an error status is about", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const call = client.callService("/missing", "std_srvs/srv/Trigger", {}, { id: "svc-1" });
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(1));
+    socket.receive({ op: "status", level: "error", msg: "Service /missing does not exist", id: "svc-1" });
+    await expect(call).rejects.toThrow("Service /missing does not exist");
+  });
+
+  it("rejects a confirmed subscribe on an error status", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const subscribing = client.subscribe("/bad", "not a type", () => {}, { confirmMs: 1000 });
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(1));
+    socket.receive({ op: "status", level: "error", msg: "Unable to load the manifest", id: "subscribe:/bad:1" });
+
+    await expect(subscribing).rejects.toThrow("Subscribe to /bad failed: Unable to load the manifest");
+    expect(socket.sent.at(-1)).toEqual({ op: "unsubscribe", topic: "/bad", id: "subscribe:/bad:1" });
+
+    const handle = await client.subscribe("/good", "std_msgs/msg/String", () => {}, { confirmMs: 5 });
+    expect(handle.topic).toBe("/good");
+  });
+});
+
+describe("error frames", () => {
+  it("rejects the request an error frame echoes", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const call = client.callService("/nope", "std_srvs/srv/Trigger", {}, { id: "svc-1" });
+    const cli = client.executeCli("ros2 topic list");
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(2));
+
+    for (const received of socket.sent) {
+      socket.receive({ op: "error", error: "unsupported_operation", received });
+    }
+
+    await expect(call).rejects.toBeInstanceOf(BridgeError);
+    await expect(call).rejects.toMatchObject({
+      message: "unsupported_operation",
+      code: "unsupported_operation",
+      op: "call_service",
+      requestId: "svc-1",
+      target: "/nope"
+    });
+    await expect(cli).rejects.toMatchObject({ code: "unsupported_operation", op: "execute_cli" });
+  });
+
+  it("reports errors that match no request", async () => {
+    const errors: BridgeProtocolError[] = [];
+    const { socket } = await connectClient(wasmProtocol, { onProtocolError: (error) => errors.push(error) });
+    socket.receive({ op: "error", error: "invalid_json" });
+    expect(errors).toHaveLength(1);
+    expect(errors[0]).toMatchObject({ code: "bridge_error", message: "invalid_json" });
+  });
+});
+
+describe("bridge errors", () => {
+  it("rejects with coded errors carrying the request", async () => {
+    const { client } = await connectClient(wasmProtocol);
+    const call = client.callService("/slow", "std_srvs/srv/Trigger", {}, { id: "svc-9", timeoutMs: 5 });
+    await expect(call).rejects.toBeInstanceOf(BridgeError);
+    await expect(call).rejects.toMatchObject({
+      name: "BridgeError",
+      code: "timeout",
+      op: "call_service",
+      requestId: "svc-9",
+      target: "/slow"
+    });
+    await expect(client.executeCli("  ")).rejects.toMatchObject({ code: "invalid_input", op: "execute_cli" });
+    await expect(client.setLevel("loud" as BridgeStatusLevel)).rejects.toMatchObject({
+      code: "invalid_input",
+      op: "set_level",
+      path: "level"
+    });
+
+    client.close();
+    await expect(client.publish("/chatter", {})).rejects.toMatchObject({ code: "disconnected", op: "publish" });
+  });
+
+  it("keeps the code and extra fields of errors thrown by the wasm module", () => {
+    const thrown = Object.assign(new Error("invalid demo/Drive: gear: out of range"), {
+      name: "BridgeError",
+      code: "schema_violation",
+      path: "gear",
+      issues: [{ path: "gear", message: "out of range" }]
+    });
+    const error = BridgeError.from(thrown, "invalid_input", { target: "demo/Drive" });
+    expect(error).toBeInstanceOf(BridgeError);
+    expect(error).toMatchObject({ code: "schema_violation", path: "gear", target: "demo/Drive" });
+    expect((error as BridgeError & { issues: unknown[] }).issues).toHaveLength(1);
+    expect(BridgeError.from("boom", "encode_failed")).toMatchObject({ code: "encode_failed", message: "boom" });
+  });
+});
+
+describe("service servers", () => {
+  it("answers forwarded calls with the handler result or error", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    await client.advertiseService("/confirm", "demo/srv/Confirm", async (request) => {
+      if (request.prompt === "explode?") {
+        throw new Error("operator declined");
+      }
+      return { accepted: true };
+    });
+    expect(socket.sent).toEqual([{ op: "advertise_service", service: "/confirm", type: "demo/srv/Confirm" }]);
+
+    socket.receive({ op: "call_service", service: "/confirm", id: "req-1", args: { prompt: "go?" } });
+    socket.receive({ op: "call_service", service: "/confirm", id: "req-2", args: { prompt: "explode?" } });
+    socket.receive({ op: "call_service", service: "/unknown", id: "req-3" });
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(4));
+
+    expect(socket.sent.slice(1)).toEqual([
+      { op: "service_response", service: "/confirm", id: "req-1", result: true, values: { accepted: true } },
+      { op: "service_response", service: "/confirm", id: "req-2", result: false, values: "operator declined" },
+      {
+        op: "service_response",
+        service: "/unknown",
+        id: "req-3",
+        result: false,
+        values: "Service /unknown is not advertised by this client"
+      }
+    ]);
+
+    await client.unadvertiseService("/confirm");
+    expect(socket.sent.at(-1)).toEqual({ op: "unadvertise_service", service: "/confirm" });
+  });
+});
+
+describe("action servers", () => {
+  it("streams feedback and reports terminal goal statuses", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    await client.advertiseAction("/teleop", "demo/action/Teleop", async (goal) => {
+      await goal.publishFeedback({ step: 1 });
+      if (goal.goal.mode === "wait") {
+        await new Promise((resolve) => goal.signal.addEventListener("abort", resolve));
+      }
+      if (goal.goal.mode === "fail") {
+        throw new Error("joystick disconnected");
+      }
+      return { done: true };
+    });
+    expect(socket.sent).toEqual([{ op: "advertise_action", action: "/teleop", type: "demo/action/Teleop" }]);
+
+    socket.receive({ op: "send_action_goal", id: "g1", action: "/teleop", args: {} });
+    socket.receive({ op: "send_action_goal", id: "g2", action: "/teleop", args: { mode: "fail" } });
+    socket.receive({ op: "send_action_goal", id: "g3", action: "/teleop", args: { mode: "wait" } });
+    await vi.waitFor(() => expect(socket.sent.filter((frame) => frame.op === "action_feedback")).toHaveLength(3));
+    socket.receive({ op: "cancel_action_goal", id: "g3", action: "/teleop" });
+    await vi.waitFor(() => expect(socket.sent.filter((frame) => frame.op === "action_result")).toHaveLength(3));
+
+    const results = socket.sent.filter((frame) => frame.op === "action_result");
+    expect(results).toEqual(
+      expect.arrayContaining([
+        { op: "action_result", action: "/teleop", id: "g1", status: 4, values: { done: true }, result: true },
+        { op: "action_result", action: "/teleop", id: "g2", status: 6, values: "joystick disconnected", result: false },
+        { op: "action_result", action: "/teleop", id: "g3", status: 5, values: { done: true }, result: false }
+      ])
+    );
+    expect(socket.sent).toContainEqual({ op: "action_feedback", action: "/teleop", id: "g1", values: { step: 1 } });
+  });
+});
+
+describe("publishers", () => {
+  it("advertises with latch and queue size and unadvertises on dispose", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const publisher = await client.advertise("/cmd_vel", "geometry_msgs/msg/Twist", { latch: true, queueSize: 1 });
+    await publisher.publish({ linear: { x: 1 } });
+    await publisher.dispose();
+    await publisher.dispose();
+
+    expect(socket.sent).toEqual([
+      { op: "advertise", topic: "/cmd_vel", type: "geometry_msgs/msg/Twist", latch: true, queue_size: 1 },
+      { op: "publish", topic: "/cmd_vel", msg: { linear: { x: 1 } } },
+      { op: "unadvertise", topic: "/cmd_vel" }
+    ]);
+  });
+
+  it("leaves a newer advertisement of the topic alone", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const stale = await client.advertise("/status", "std_msgs/msg/String");
+    await client.advertise("/status", "std_msgs/msg/String", { latch: true });
+    await stale.dispose();
+    expect(socket.sent.map((frame) => frame.op)).toEqual(["advertise", "advertise"]);
+  });
+});
+
+describe("auto-advertise", () => {
+  it("advertises a topic before its first publish only", async () => {
+    const { client, socket } = await connectClient(wasmProtocol, { autoAdvertise: true });
+    await client.publish("/chatter", { data: "a" }, { type: "std_msgs/msg/String" });
+    await client.publish("/chatter", { data: "b" });
+    await client.publish("/chatter", { data: "c" }, { type: "std_msgs/String" });
+
+    expect(socket.sent).toEqual([
+      { op: "advertise", topic: "/chatter", type: "std_msgs/msg/String" },
+      { op: "publish", topic: "/chatter", msg: { data: "a" } },
+      { op: "publish", topic: "/chatter", msg: { data: "b" } },
+      { op: "publish", topic: "/chatter", msg: { data: "c" } }
+    ]);
+  });
+
+  it("rejects publishes without a type or with a conflicting one", async () => {
+    const { client, socket } = await connectClient(wasmProtocol, { autoAdvertise: true });
+    await expect(client.publish("/odom", {})).rejects.toThrow(/cannot auto-advertise \/odom/);
+    await client.advertise("/odom", "nav_msgs/msg/Odometry");
+    await expect(client.publish("/odom", {}, { type: "std_msgs/msg/String" })).rejects.toThrow(
+      "cannot publish std_msgs/msg/String on /odom: it is advertised as nav_msgs/msg/Odometry"
+    );
+    expect(socket.sent.map((frame) => frame.op)).toEqual(["advertise"]);
+  });
+
+  it("publishes unadvertised topics as-is when disabled", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    await client.publish("/raw", { data: 1 }, { type: "std_msgs/msg/Int32" });
+    expect(socket.sent).toEqual([{ op: "publish", topic: "/raw", msg: { data: 1 } }]);
+  });
+});
+
+describe("request tracking", () => {
+  it("matches CLI replies by id once several runs are pending", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const list = client.executeCli("ros2 topic list", { id: "cli-list" });
+    const echo = client.executeCli("ros2 topic echo /odom --once", { id: "cli-echo" });
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(2));
+
+    // Ambiguous without an id, so dropped.
+    socket.receive({ op: "cli_response", success: true, output: "ignored" });
+    socket.receive({ op: "cli_response", id: "cli-echo", success: true, output: "echo" });
+    socket.receive({ op: "cli_response", success: true, output: "list" });
+
+    await expect(echo).resolves.toMatchObject({ output: "echo" });
+    await expect(list).resolves.toMatchObject({ output: "list" });
+  });
+
+  it("answers concurrent cancels of one goal in order", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const cancel = { action: "/fly", actionType: "demo/action/Fly", sessionId: "s1" };
+    const first = client.cancelActionGoal(cancel);
+    const second = client.cancelActionGoal(cancel);
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(2));
+
+    socket.receive({ op: "cancel_action_result", action: "/fly", session_id: "s1", result: true, n: 1 });
+    socket.receive({ op: "cancel_action_result", action: "/fly", session_id: "s1", result: true, n: 2 });
+
+    await expect(first).resolves.toMatchObject({ n: 1 });
+    await expect(second).resolves.toMatchObject({ n: 2 });
+  });
+
+  it("times out each request at its own deadline", async () => {
+    const { client, socket } = await connectClient(wasmProtocol);
+    const settled: string[] = [];
+    const record = (name: string) => (error: BridgeError) => settled.push(`${name}:${error.code}`);
+    const slow = client
+      .callService("/slow", "std_srvs/srv/Trigger", {}, { id: "slow", timeoutMs: 40 })
+      .catch(record("slow"));
+    const fast = client
+      .callService("/fast", "std_srvs/srv/Trigger", {}, { id: "fast", timeoutMs: 5 })
+      .catch(record("fast"));
+    const answered = client.callService("/ok", "std_srvs/srv/Trigger", {}, { id: "ok", timeoutMs: 5 });
+    await vi.waitFor(() => expect(socket.sent).toHaveLength(3));
+    socket.receive({ op: "service_response", service: "/ok", id: "ok", result: true, values: { success: true } });
+
+    await expect(answered).resolves.toEqual({ success: true });
+    await Promise.all([slow, fast]);
+    expect(settled).toEqual(["fast:timeout", "slow:timeout"]);
+  });
+});
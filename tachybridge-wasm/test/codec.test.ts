@@ -1,5 +1,6 @@
 import { describe, expect, it } from "vitest";
-import { cborCodec, jsonCodec } from "../src/codec.js";
+import { autoCodec, cborCodec, jsonCodec, resolveCodec } from "../src/codec.js";
+import { wasmProtocol } from "./wasm.js";
 
 describe("codec", () => {
   it("json codec decodes string payload", () => {
@@ -11,16 +12,52 @@ describe("codec", () => {
   });
 
   it("cbor codec encodes/decodes binary payload", () => {
+    const codec = resolveCodec("cbor", wasmProtocol);
     const msg = { op: "call_service", service: "/demo/sum", args: { a: 1, b: 2 }, id: "svc-1" };
-    const encoded = cborCodec.encode(msg);
+    const encoded = codec.encode(msg);
     expect(encoded).toBeInstanceOf(Uint8Array);
-    const decoded = cborCodec.decode(encoded);
+    const decoded = codec.decode(encoded);
     expect((decoded as { op: string }).op).toBe("call_service");
     expect((decoded as { args: { a: number } }).args.a).toBe(1);
   });
 
   it("cbor codec throws on invalid binary payload", () => {
     const invalid = new Uint8Array([0xff, 0x00]);
-    expect(() => cborCodec.decode(invalid)).toThrow();
+    expect(() => resolveCodec("cbor", wasmProtocol).decode(invalid)).toThrow();
+  });
+
+  it("delegates cbor to the wasm core when the protocol provides it", () => {
+    const calls: string[] = [];
+    const codec = resolveCodec("cbor", {
+      ...wasmProtocol,
+      encode_cbor: (value) => {
+        calls.push("encode");
+        return wasmProtocol.encode_cbor!(value);
+      },
+      decode_cbor: (bytes) => {
+        calls.push("decode");
+        return wasmProtocol.decode_cbor!(bytes);
+      }
+    });
+
+    const decoded = codec.decode(codec.encode({ op: "publish", topic: "/demo", msg: {} }));
+    expect((decoded as { topic: string }).topic).toBe("/demo");
+    expect(calls).toEqual(["encode", "decode"]);
+    expect(resolveCodec(cborCodec, wasmProtocol)).not.toBe(cborCodec);
+  });
+
+  it("needs the wasm core for cbor frames", () => {
+    const msg = { op: "publish", topic: "/demo", msg: {} };
+    expect(resolveCodec("cbor")).toBe(cborCodec);
+    expect(() => cborCodec.encode(msg)).toThrow("CBOR frames require the bridge_wasm module");
+    expect(autoCodec.decode(new TextEncoder().encode('{"op":"publish"}'))).toEqual({ op: "publish" });
+  });
+
+  it("binds the exported codecs to the protocol they are resolved with", () => {
+    const msg = { op: "publish", topic: "/demo", msg: {} };
+    const cbor = resolveCodec(cborCodec, wasmProtocol);
+    expect(cbor.decode(cbor.encode(msg))).toEqual(msg);
+    expect(resolveCodec(autoCodec, wasmProtocol).decode(cbor.encode(msg))).toEqual(msg);
+    expect(() => cborCodec.encode(msg)).toThrow("CBOR frames require the bridge_wasm module");
   });
 });
@@ -29,7 +29,7 @@ describe.each([
 
   beforeAll(async () => {
     server = await createMockupRosbridgeServer(codec === "json" ? 9190 : 9191);
-    client = new BridgeClient({ strictWasm: false, timeoutMs: 3000, codec });
+    client = new BridgeClient({ timeoutMs: 3000, codec });
     await client.connect(server.url);
   });
 
@@ -81,6 +81,12 @@ describe.each([
     expect(typeof recv.secs).toBe("number");
   });
 
+  it("subscribe with an unknown type rejects on the bridge's error status", async () => {
+    await expect(client.subscribe("/mock/broken", "no such type", () => {}, { confirmMs: 500 })).rejects.toThrow(
+      /Subscribe to \/mock\/broken failed: Unable to load the manifest/
+    );
+  });
+
   it("call_service success/failure", async () => {
     const success = await client.callService("/demo/sum", "example/AddTwoInts", { a: 1, b: 2 });
     expect((success.echoed_args as Record<string, unknown>).a).toBe(1);
@@ -147,4 +153,64 @@ describe.each([
 
     await expect(action.completion).rejects.toThrow(/unknown_action_type/);
   });
+
+  it("hosted action goals, feedback, results and cancels are forwarded", async () => {
+    const host = new BridgeClient({ timeoutMs: 3000, codec });
+    await host.connect(server.url);
+    try {
+      await host.advertiseAction("/teleop/drive", "demo/action/Drive", async (goal) => {
+        await goal.publishFeedback({ remaining: Number(goal.goal.distance) / 2 });
+        if (goal.goal.distance === 0) {
+          // Wait for the caller to cancel.
+          await new Promise((resolve) => goal.signal.addEventListener("abort", resolve));
+          return { traveled: 0 };
+        }
+        if (Number(goal.goal.distance) < 0) {
+          throw new Error("operator rejected the goal");
+        }
+        return { traveled: goal.goal.distance };
+      });
+
+      const feedbacks: Array<Record<string, unknown>> = [];
+      const done = await client.sendActionGoal({
+        action: "/teleop/drive",
+        actionType: "demo/action/Drive",
+        goal: { distance: 4 },
+        id: `hosted-success-${codec}`,
+        onFeedback: (msg) => feedbacks.push(msg)
+      });
+      await expect(done.completion).resolves.toEqual({ traveled: 4 });
+      expect(feedbacks).toEqual([{ remaining: 2 }]);
+
+      const rejected = await client.sendActionGoal({
+        action: "/teleop/drive",
+        actionType: "demo/action/Drive",
+        goal: { distance: -1 },
+        id: `hosted-abort-${codec}`
+      });
+      await expect(rejected.completion).rejects.toThrow(/operator rejected the goal/);
+
+      let running: Record<string, unknown> | undefined;
+      const canceled = await client.sendActionGoal({
+        action: "/teleop/drive",
+        actionType: "demo/action/Drive",
+        goal: { distance: 0 },
+        id: `hosted-cancel-${codec}`,
+        sessionId: `hosted-cancel-${codec}`,
+        onFeedback: (msg) => {
+          running = msg;
+        }
+      });
+      await waitFor(() => running);
+      const cancelAck = await client.cancelActionGoal({
+        action: "/teleop/drive",
+        actionType: "demo/action/Drive",
+        sessionId: `hosted-cancel-${codec}`
+      });
+      expect(cancelAck.result).toBe(true);
+      await expect(canceled.completion).rejects.toThrow(/non-success status 2/);
+    } finally {
+      host.close();
+    }
+  });
 });
@@ -1,12 +1,15 @@
 import { afterEach, describe, expect, it, vi } from "vitest";
 import { BridgeClientCore } from "../src/client-core.js";
 import type {
+  BridgeClientOptions,
   BridgeReconnectScheduledEvent,
   BridgeReconnectOptions,
+  BridgeRequestRetry,
   JsonObject,
   WasmProtocol,
   WebSocketLike
 } from "../src/types.js";
+import { wasmProtocol } from "./wasm.js";
 
 type SocketOutcome = "open" | "fail";
 
@@ -16,6 +19,7 @@ class ScriptedWebSocket implements WebSocketLike {
   onmessage: ((event: { data: unknown }) => void) | null = null;
   onerror: ((event: unknown) => void) | null = null;
   onclose: ((event: unknown) => void) | null = null;
+  readonly sent: JsonObject[] = [];
   private closed = false;
 
   constructor(private readonly outcome: SocketOutcome) {
@@ -35,8 +39,12 @@ class ScriptedWebSocket implements WebSocketLike {
     }, 0);
   }
 
-  send(_data: string | Uint8Array): void {
-    // no-op for reconnect scheduling tests
+  send(data: string | Uint8Array): void {
+    this.sent.push(JSON.parse(data as string) as JsonObject);
+  }
+
+  receive(frame: JsonObject): void {
+    this.onmessage?.({ data: JSON.stringify(frame) });
   }
 
   close(): void {
@@ -53,20 +61,23 @@ class ScriptedWebSocket implements WebSocketLike {
 
 function protocolStub(): Promise<WasmProtocol> {
   return Promise.resolve({
+    ...wasmProtocol,
     build_subscribe: (_topic: string, _type: string, _compression?: string): JsonObject => ({ op: "subscribe" }),
     build_unsubscribe: (_topic: string): JsonObject => ({ op: "unsubscribe" }),
     build_advertise: (_topic: string, _type: string): JsonObject => ({ op: "advertise" }),
-    build_publish: (_topic: string, _msg: JsonObject): JsonObject => ({ op: "publish" }),
-    build_call_service: (_service: string, _type: string, _args: JsonObject, _id?: string): JsonObject => ({
-      op: "call_service"
+    build_publish: (topic: string, msg: JsonObject): JsonObject => ({ op: "publish", topic, msg }),
+    build_call_service: (service: string, _type: string, _args: JsonObject, id?: string): JsonObject => ({
+      op: "call_service",
+      service,
+      id
     }),
     build_send_action_goal: (
-      _action: string,
+      action: string,
       _actionType: string,
       _goal: JsonObject,
-      _id?: string,
-      _sessionId?: string
-    ): JsonObject => ({ op: "send_action_goal" }),
+      id?: string,
+      sessionId?: string
+    ): JsonObject => ({ op: "send_action_goal", action, id, session_id: sessionId }),
     build_cancel_action_goal: (_action: string, _actionType: string, _sessionId?: string): JsonObject => ({
       op: "cancel_action_goal"
     })
@@ -76,13 +87,15 @@ function protocolStub(): Promise<WasmProtocol> {
 function makeClient(
   outcomes: SocketOutcome[],
   events: BridgeReconnectScheduledEvent[],
-  reconnect: Partial<BridgeReconnectOptions> = {}
+  reconnect: Partial<BridgeReconnectOptions> = {},
+  options: BridgeClientOptions = {}
 ): {
   client: BridgeClientCore;
   sockets: ScriptedWebSocket[];
 } {
   const sockets: ScriptedWebSocket[] = [];
   const client = new BridgeClientCore(protocolStub, {
+    ...options,
     reconnect: {
       enabled: true,
       initialDelayMs: 100,
@@ -197,6 +210,80 @@ describe("reconnect backoff", () => {
     expect(events.length).toBe(1);
   });
 
+  it("repeats seeded jitter exactly", async () => {
+    vi.useFakeTimers();
+    const events: BridgeReconnectScheduledEvent[] = [];
+    const { client } = makeClient(["fail", "fail", "fail"], events, { jitterRatio: 0.2, seed: 42 });
+
+    void client.connect("ws://test").catch(() => undefined);
+    await vi.advanceTimersByTimeAsync(1);
+    await advanceReconnectCycle(107);
+    await advanceReconnectCycle(161);
+
+    // Same sequence as the Rust ReconnectPolicy with seed 42.
+    expect(events.map((e) => e.nextDelayMs)).toEqual([107, 161, 378]);
+    client.close();
+  });
+
+  it("follows a fixed schedule and honours retryAfterMs", async () => {
+    vi.useFakeTimers();
+    const events: BridgeReconnectScheduledEvent[] = [];
+    const { client } = makeClient(["fail", "fail", "fail"], events, {
+      strategy: "fixed",
+      scheduleMs: [0, 50],
+      retryAfterMs: (context) => (context.attempt === 3 ? 2000 : undefined)
+    });
+
+    void client.connect("ws://test").catch(() => undefined);
+    await vi.advanceTimersByTimeAsync(1);
+    await advanceReconnectCycle(0);
+    await advanceReconnectCycle(50);
+
+    expect(events.map((e) => e.nextDelayMs)).toEqual([0, 50, 2000]);
+    client.close();
+  });
+
+  it("opens the circuit after maxAttempts and probes once it is half-open", async () => {
+    vi.useFakeTimers();
+    const events: BridgeReconnectScheduledEvent[] = [];
+    const { client, sockets } = makeClient(["fail", "fail", "fail", "fail"], events, {
+      maxAttempts: 2,
+      halfOpenAfterMs: 5000
+    });
+
+    void client.connect("ws://test").catch(() => undefined);
+    await vi.advanceTimersByTimeAsync(1);
+    await advanceReconnectCycle(100);
+    await advanceReconnectCycle(200);
+    expect(events.map((e) => [e.nextDelayMs, e.probe])).toEqual([
+      [100, false],
+      [200, false],
+      [5000, true]
+    ]);
+
+    await advanceReconnectCycle(4000);
+    expect(sockets.length).toBe(3);
+    await advanceReconnectCycle(1000);
+    expect(sockets.length).toBe(4);
+    expect(events.at(-1)).toMatchObject({ attempt: 4, nextDelayMs: 5000, probe: true });
+    client.close();
+  });
+
+  it("gives up after maxAttempts without a half-open cooldown", async () => {
+    vi.useFakeTimers();
+    const events: BridgeReconnectScheduledEvent[] = [];
+    const { client, sockets } = makeClient(["fail", "fail", "fail"], events, { maxAttempts: 1 });
+
+    void client.connect("ws://test").catch(() => undefined);
+    await vi.advanceTimersByTimeAsync(1);
+    await advanceReconnectCycle(100);
+    await vi.advanceTimersByTimeAsync(10_000);
+
+    expect(events.map((e) => e.attempt)).toEqual([1]);
+    expect(sockets.length).toBe(2);
+    client.close();
+  });
+
   it("keeps only one reconnect timer per failure cycle", async () => {
     vi.useFakeTimers();
     const events: BridgeReconnectScheduledEvent[] = [];
@@ -253,3 +340,320 @@ describe("reconnect backoff", () => {
     client.close();
   });
 });
+
+describe("connection state", () => {
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  it("reports every state through reconnects and give-up", async () => {
+    vi.useFakeTimers();
+    const states: string[] = [];
+    const { client, sockets } = makeClient(
+      ["open", "fail"],
+      [],
+      { maxAttempts: 1 },
+      { onStateChange: ({ from, to }) => states.push(`${from}->${to}`) }
+    );
+    expect(client.state).toBe("idle");
+
+    const connecting = client.connect("ws://test");
+    await vi.advanceTimersByTimeAsync(1);
+    await connecting;
+    expect(client.state).toBe("open");
+
+    sockets[0].close();
+    await vi.advanceTimersByTimeAsync(1);
+    expect(client.state).toBe("reconnect_wait");
+    await vi.advanceTimersByTimeAsync(101);
+    expect(client.state).toBe("failed");
+
+    client.close();
+    expect(states).toEqual([
+      "idle->connecting",
+      "connecting->rebinding",
+      "rebinding->open",
+      "open->reconnect_wait",
+      "reconnect_wait->connecting",
+      "connecting->failed",
+      "failed->closed"
+    ]);
+  });
+
+  it("ends in failed when reconnecting is disabled", async () => {
+    vi.useFakeTimers();
+    const changes: { to: string; error?: Error }[] = [];
+    const { client } = makeClient(["fail"], [], { enabled: false }, { onStateChange: (change) => changes.push(change) });
+
+    void client.connect("ws://test").catch(() => undefined);
+    await vi.advanceTimersByTimeAsync(1);
+
+    expect(changes.map((change) => change.to)).toEqual(["connecting", "failed"]);
+    expect(changes[1].error).toMatchObject({ code: "disconnected" });
+    client.close();
+  });
+});
+
+describe("outbound queue", () => {
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  async function disconnect(options: BridgeClientOptions): Promise<ReturnType<typeof makeClient>> {
+    const connection = makeClient(["open", "open", "open"], [], {}, options);
+    const connecting = connection.client.connect("ws://test");
+    await vi.advanceTimersByTimeAsync(1);
+    await connecting;
+    connection.sockets[0].close();
+    await vi.advanceTimersByTimeAsync(1);
+    expect(connection.client.state).toBe("reconnect_wait");
+    return connection;
+  }
+
+  it("delivers queued frames once after rebinding, following per-target policies", async () => {
+    vi.useFakeTimers();
+    const { client, sockets } = await disconnect({
+      outboundQueue: {
+        ops: { publish: "keep_all" },
+        targets: { "/cmd_vel": "drop", "/battery": "keep_latest" }
+      }
+    });
+
+    await client.publish("/cmd_vel", { linear: 1 });
+    await client.publish("/config", { rate: 1 });
+    await client.publish("/battery", { level: 90 });
+    await client.publish("/battery", { level: 80 });
+    await client.publish("/config", { rate: 2 });
+    await expect(client.setLevel("warning")).rejects.toMatchObject({
+      code: "disconnected",
+      message: "WebSocket is not connected"
+    });
+
+    await vi.advanceTimersByTimeAsync(101);
+    expect(client.state).toBe("open");
+    expect(sockets[1].sent.filter((frame) => frame.op === "publish")).toEqual([
+      { op: "publish", topic: "/config", msg: { rate: 1 } },
+      { op: "publish", topic: "/battery", msg: { level: 80 } },
+      { op: "publish", topic: "/config", msg: { rate: 2 } }
+    ]);
+
+    sockets[1].close();
+    await vi.advanceTimersByTimeAsync(1);
+    await vi.advanceTimersByTimeAsync(101);
+    expect(sockets[2].sent.filter((frame) => frame.op === "publish")).toEqual([]);
+    client.close();
+  });
+
+  it("rejects frames that do not fit in maxBytes", async () => {
+    vi.useFakeTimers();
+    const { client } = await disconnect({ outboundQueue: { ops: { publish: "keep_all" }, maxBytes: 64 } });
+
+    await client.publish("/config", { rate: 1 });
+    await expect(client.publish("/config", { rate: 2 })).rejects.toMatchObject({
+      code: "disconnected",
+      op: "publish",
+      message: expect.stringContaining("outbound queue is full: `publish` on /config")
+    });
+    client.close();
+  });
+
+  it("queues frames sent while rebinding behind the older queued ones", async () => {
+    vi.useFakeTimers();
+    let client: BridgeClientCore | undefined;
+    const connection = makeClient(["open", "open"], [], {}, {
+      outboundQueue: { ops: { publish: "keep_all" } },
+      onStateChange: ({ to }) => {
+        if (to === "rebinding" && connection.sockets.length === 2) {
+          void client?.publish("/config", { rate: 2 });
+        }
+      }
+    });
+    client = connection.client;
+    const connecting = client.connect("ws://test");
+    await vi.advanceTimersByTimeAsync(1);
+    await connecting;
+    for (const topic of ["/a", "/b", "/c"]) {
+      await client.subscribe(topic, "std_msgs/msg/String", () => {});
+    }
+    connection.sockets[0].close();
+    await vi.advanceTimersByTimeAsync(1);
+    await client.publish("/config", { rate: 1 });
+
+    await vi.advanceTimersByTimeAsync(101);
+    expect(client.state).toBe("open");
+    expect(connection.sockets[1].sent.map((frame) => frame.op)).toEqual([
+      "subscribe",
+      "subscribe",
+      "subscribe",
+      "publish",
+      "publish"
+    ]);
+    expect(connection.sockets[1].sent.filter((frame) => frame.op === "publish")).toEqual([
+      { op: "publish", topic: "/config", msg: { rate: 1 } },
+      { op: "publish", topic: "/config", msg: { rate: 2 } }
+    ]);
+    client.close();
+  });
+
+  it("throws as before without an outbound queue", async () => {
+    vi.useFakeTimers();
+    const { client } = await disconnect({});
+    await expect(client.publish("/config", { rate: 1 })).rejects.toThrow("WebSocket is not connected");
+    client.close();
+  });
+});
+
+describe("action goal resume", () => {
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  async function reconnectWithGoals(resumeActionGoals: boolean) {
+    const connection = makeClient(["open", "open"], [], {}, { resumeActionGoals });
+    const connecting = connection.client.connect("ws://test");
+    await vi.advanceTimersByTimeAsync(1);
+    await connecting;
+    const feedback: JsonObject[] = [];
+    const navigate = await connection.client.sendActionGoal({
+      action: "/navigate",
+      actionType: "nav/action/Navigate",
+      goal: {},
+      id: "nav-1",
+      sessionId: "s1",
+      onFeedback: (msg) => feedback.push(msg)
+    });
+    const dock = await connection.client.sendActionGoal({
+      action: "/dock",
+      actionType: "nav/action/Dock",
+      goal: {},
+      id: "dock-1"
+    });
+    // Checked by the tests; these handlers only keep early rejections from being reported as unhandled.
+    void navigate.completion.catch(() => undefined);
+    void dock.completion.catch(() => undefined);
+    connection.sockets[0].close();
+    await vi.advanceTimersByTimeAsync(1);
+    return { ...connection, feedback, navigate, dock };
+  }
+
+  it("re-attaches pending goals after reconnecting", async () => {
+    vi.useFakeTimers();
+    const { client, sockets, feedback, navigate, dock } = await reconnectWithGoals(true);
+    const docking = expect(dock.completion).rejects.toMatchObject({ code: "disconnected", requestId: "dock-1" });
+
+    await vi.advanceTimersByTimeAsync(101);
+    expect(client.state).toBe("open");
+    expect(sockets[1].sent.filter((frame) => frame.op === "query_action_goal")).toEqual([
+      { op: "query_action_goal", action: "/navigate", id: "nav-1", session_id: "s1" },
+      { op: "query_action_goal", action: "/dock", id: "dock-1" }
+    ]);
+
+    sockets[1].receive({ op: "action_goal_status", action: "/navigate", id: "nav-1", session_id: "s1", found: true });
+    sockets[1].receive({ op: "action_goal_status", action: "/dock", id: "dock-1", found: false });
+    sockets[1].receive({ type: "feedback", id: "nav-1", session_id: "s1", feedback: { progress: 50 } });
+    sockets[1].receive({ type: "result", id: "nav-1", session_id: "s1", status: 0, result: { arrived: true } });
+
+    await expect(navigate.completion).resolves.toEqual({ arrived: true });
+    expect(feedback).toEqual([{ progress: 50 }]);
+    await docking;
+    client.close();
+  });
+
+  it("rejects goals on disconnect by default and kept goals on close", async () => {
+    vi.useFakeTimers();
+    const interrupted = await reconnectWithGoals(false);
+    await expect(interrupted.navigate.completion).rejects.toThrow("interrupted by disconnect");
+    await expect(interrupted.dock.completion).rejects.toMatchObject({ code: "disconnected" });
+    interrupted.client.close();
+
+    const kept = await reconnectWithGoals(true);
+    kept.client.close();
+    await expect(kept.navigate.completion).rejects.toMatchObject({ code: "disconnected", requestId: "nav-1" });
+    await expect(kept.dock.completion).rejects.toMatchObject({ code: "disconnected", requestId: "dock-1" });
+  });
+});
+
+describe("request retry", () => {
+  afterEach(() => {
+    vi.useRealTimers();
+  });
+
+  async function connected() {
+    const connection = makeClient(["open", "open"], []);
+    const connecting = connection.client.connect("ws://test");
+    await vi.advanceTimersByTimeAsync(1);
+    await connecting;
+    return connection;
+  }
+
+  it("resends idempotent calls with the same id after reconnecting", async () => {
+    vi.useFakeTimers();
+    const { client, sockets } = await connected();
+    const reply = client.callService("/get_map", "nav_msgs/srv/GetMap", {}, { id: "map-1", retry: "idempotent" });
+    await vi.advanceTimersByTimeAsync(1);
+    sockets[0].close();
+
+    await vi.advanceTimersByTimeAsync(101);
+    expect(client.state).toBe("open");
+    expect(sockets[1].sent.filter((frame) => frame.op === "call_service")).toEqual([
+      { op: "call_service", service: "/get_map", id: "map-1" }
+    ]);
+    sockets[1].receive({ op: "service_response", service: "/get_map", id: "map-1", result: true, values: { ok: true } });
+    await expect(reply).resolves.toEqual({ ok: true });
+    client.close();
+  });
+
+  it("rejects calls that must not be retried as soon as the connection drops", async () => {
+    vi.useFakeTimers();
+    const { client, sockets } = await connected();
+    const reply = client.callService("/reset", "std_srvs/srv/Empty", {}, { id: "reset-1", retry: "never" });
+    const rejected = expect(reply).rejects.toMatchObject({ code: "disconnected", requestId: "reset-1" });
+    await vi.advanceTimersByTimeAsync(1);
+    sockets[0].close();
+    await vi.advanceTimersByTimeAsync(1);
+
+    await rejected;
+    expect(client.state).toBe("reconnect_wait");
+    await expect(
+      client.callService("/reset", "std_srvs/srv/Empty", {}, { retry: "always" as BridgeRequestRetry })
+    ).rejects.toMatchObject({ code: "invalid_input", path: "retry" });
+    client.close();
+  });
+
+  it("rejects service calls without a retry policy when the connection drops", async () => {
+    vi.useFakeTimers();
+    const { client, sockets } = await connected();
+    const reply = client.callService("/get_map", "nav_msgs/srv/GetMap", {}, { id: "map-2", timeoutMs: 10_000 });
+    const rejected = expect(reply).rejects.toMatchObject({ code: "disconnected", requestId: "map-2" });
+    await vi.advanceTimersByTimeAsync(1);
+    sockets[0].close();
+    await vi.advanceTimersByTimeAsync(1);
+
+    await rejected;
+    await vi.advanceTimersByTimeAsync(100);
+    expect(client.state).toBe("open");
+    expect(sockets[1].sent.filter((frame) => frame.op === "call_service")).toEqual([]);
+    client.close();
+  });
+
+  it("resends after the backoff until the attempts run out", async () => {
+    vi.useFakeTimers();
+    const { client, sockets } = await connected();
+    const run = client.executeCli("ros2 topic list", { id: "cli-1", retry: { attempts: 1, backoffMs: 50 } });
+    void run.catch(() => undefined);
+    await vi.advanceTimersByTimeAsync(1);
+    sockets[0].close();
+
+    await vi.advanceTimersByTimeAsync(101);
+    expect(client.state).toBe("open");
+    const runs = () => sockets[1].sent.filter((frame) => frame.op === "execute_cli");
+    expect(runs()).toEqual([]);
+    await vi.advanceTimersByTimeAsync(50);
+    expect(runs()).toEqual([{ op: "execute_cli", command: "ros2 topic list", id: "cli-1" }]);
+
+    sockets[1].close();
+    await vi.advanceTimersByTimeAsync(1);
+    await expect(run).rejects.toMatchObject({ code: "disconnected", requestId: "cli-1" });
+    client.close();
+  });
+});
@@ -0,0 +1,55 @@
+import { describe, expect, expectTypeOf, it } from "vitest";
+import { BridgeClientCore } from "../src/client-core.js";
+import type { JsonObject, MessageOf, ServiceRequestOf, ServiceResponseOf } from "../src/types.js";
+import { wasmProtocol } from "./wasm.js";
+
+// Shape of `bridge-typegen` output for `typegen_test/msg/Point` and `typegen_test/srv/Scale`.
+declare namespace typegen_test {
+  namespace msg {
+    interface Point {
+      x: number;
+      y: number;
+    }
+  }
+  namespace srv {
+    interface Scale_Request {
+      factor: number;
+    }
+    interface Scale_Response {
+      points: Array<typegen_test.msg.Point>;
+    }
+  }
+}
+
+declare global {
+  interface TachybridgeMessageTypeMap {
+    "typegen_test/msg/Point": typegen_test.msg.Point;
+    "typegen_test/Point": typegen_test.msg.Point;
+  }
+
+  interface TachybridgeServiceTypeMap {
+    "typegen_test/srv/Scale": { request: typegen_test.srv.Scale_Request; response: typegen_test.srv.Scale_Response };
+  }
+}
+
+describe("generated type maps", () => {
+  it("resolve declared types and fall back to JsonObject", () => {
+    expectTypeOf<MessageOf<"typegen_test/msg/Point">>().toEqualTypeOf<typegen_test.msg.Point>();
+    expectTypeOf<MessageOf<"typegen_test/Point">>().toEqualTypeOf<typegen_test.msg.Point>();
+    expectTypeOf<MessageOf<"unknown/msg/Thing">>().toEqualTypeOf<JsonObject>();
+    expectTypeOf<MessageOf<string>>().toEqualTypeOf<JsonObject>();
+    expectTypeOf<ServiceRequestOf<"typegen_test/srv/Scale">>().toEqualTypeOf<typegen_test.srv.Scale_Request>();
+    expectTypeOf<ServiceResponseOf<"typegen_test/srv/Scale">>().toEqualTypeOf<typegen_test.srv.Scale_Response>();
+    expectTypeOf<ServiceRequestOf<string>>().toEqualTypeOf<JsonObject>();
+  });
+
+  it("type subscribe callbacks and service calls", () => {
+    const client = new BridgeClientCore(() => Promise.resolve(wasmProtocol));
+    expectTypeOf(client.subscribe<"typegen_test/msg/Point">)
+      .parameter(2)
+      .toEqualTypeOf<(msg: typegen_test.msg.Point) => void>();
+    expectTypeOf(client.callService<"typegen_test/srv/Scale">)
+      .returns.resolves.toEqualTypeOf<typegen_test.srv.Scale_Response>();
+    expect(client).toBeInstanceOf(BridgeClientCore);
+  });
+});
@@ -0,0 +1,10 @@
+import { createRequire } from "node:module";
+import type { WasmProtocol } from "../src/types.js";
+
+const require = createRequire(import.meta.url);
+
+/**
+ * The Rust core as built for node by `scripts/build-wasm.sh`. Client tests run
+ * against it, so they exercise the same code `cargo test` covers.
+ */
+export const wasmProtocol = require("../src/wasm/node/bridge_wasm.js") as WasmProtocol;